@@ -0,0 +1,135 @@
+use crate::clicker::ClickMode;
+use crate::input::{InputBackend, MouseButton};
+use crate::keys::KeyId;
+use std::time::Duration;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MOUSE HELPERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Smooth drag from one position to another with interpolation
+pub fn drag_mouse(input: &dyn InputBackend, from: (i32, i32), to: (i32, i32)) {
+    let steps: u32 = 15;
+    input.move_absolute(from.0, from.1);
+    input.sleep(Duration::from_millis(30));
+    input.mouse_down(MouseButton::Left);
+    input.sleep(Duration::from_millis(30));
+    for i in 1..=steps {
+        let t = i as f64 / steps as f64;
+        let x = from.0 + ((to.0 - from.0) as f64 * t) as i32;
+        let y = from.1 + ((to.1 - from.1) as f64 * t) as i32;
+        input.move_absolute(x, y);
+        input.sleep(Duration::from_millis(5));
+    }
+    input.sleep(Duration::from_millis(30));
+    input.mouse_up(MouseButton::Left);
+}
+
+/// Press and release a mouse button, holding it for `hold_ms`
+pub fn click_button(input: &dyn InputBackend, button: MouseButton, hold_ms: u64) {
+    input.mouse_down(button);
+    input.sleep(Duration::from_millis(hold_ms));
+    input.mouse_up(button);
+}
+
+/// Perform a click based on the current mode
+pub fn perform_click(input: &dyn InputBackend, mode: ClickMode, hold_ms: u64) {
+    match mode {
+        ClickMode::Left => click_button(input, MouseButton::Left, hold_ms),
+        ClickMode::Right => click_button(input, MouseButton::Right, hold_ms),
+        ClickMode::Double => {
+            click_button(input, MouseButton::Left, hold_ms / 2);
+            input.sleep(Duration::from_millis(2));
+            click_button(input, MouseButton::Left, hold_ms / 2);
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// KEYBOARD HELPERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Press and keep holding a key (handles both keyboard keys and the
+/// Mouse3..Mouse5 buttons)
+pub fn hold_key(input: &dyn InputBackend, key: KeyId) {
+    match key.button() {
+        Some(button) => input.mouse_down(button),
+        None => input.key_down(key),
+    }
+}
+
+/// Release a key pressed with `hold_key`
+pub fn release_key(input: &dyn InputBackend, key: KeyId) {
+    match key.button() {
+        Some(button) => input.mouse_up(button),
+        None => input.key_up(key),
+    }
+}
+
+/// Press and release a key, holding it for `hold_ms`
+pub fn tap_key(input: &dyn InputBackend, key: KeyId, hold_ms: u64) {
+    hold_key(input, key);
+    input.sleep(Duration::from_millis(hold_ms));
+    release_key(input, key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent::*, MockBackend};
+
+    #[test]
+    fn drag_interpolates_between_endpoints() {
+        let mock = MockBackend::new();
+        drag_mouse(&mock, (0, 0), (150, -30));
+
+        let timeline = mock.timeline();
+        assert_eq!(timeline.first(), Some(&(0, MoveAbsolute(0, 0))));
+        assert_eq!(timeline[1], (30, MouseDown(MouseButton::Left)));
+
+        let steps: Vec<_> = timeline[2..timeline.len() - 1].to_vec();
+        assert_eq!(steps.len(), 15);
+        for (i, (t, e)) in steps.iter().enumerate() {
+            let n = i as i32 + 1;
+            assert_eq!(*t, 60 + 5 * i as u64);
+            let MoveAbsolute(x, y) = *e else { panic!("unexpected {:?}", e) };
+            assert!((x - 10 * n).abs() <= 1 && (y + 2 * n).abs() <= 1, "step {} at ({}, {})", n, x, y);
+        }
+        assert_eq!(steps.last().unwrap().1, MoveAbsolute(150, -30));
+
+        assert_eq!(timeline.last(), Some(&(165, MouseUp(MouseButton::Left))));
+    }
+
+    #[test]
+    fn click_modes_press_the_right_buttons() {
+        let mock = MockBackend::new();
+        perform_click(&mock, ClickMode::Left, 40);
+        perform_click(&mock, ClickMode::Right, 40);
+        perform_click(&mock, ClickMode::Double, 40);
+
+        assert_eq!(mock.timeline(), vec![
+            (0, MouseDown(MouseButton::Left)),
+            (40, MouseUp(MouseButton::Left)),
+            (40, MouseDown(MouseButton::Right)),
+            (80, MouseUp(MouseButton::Right)),
+            (80, MouseDown(MouseButton::Left)),
+            (100, MouseUp(MouseButton::Left)),
+            (102, MouseDown(MouseButton::Left)),
+            (122, MouseUp(MouseButton::Left)),
+        ]);
+    }
+
+    #[test]
+    fn tap_accepts_keys_and_mouse_buttons() {
+        let mock = MockBackend::new();
+        tap_key(&mock, KeyId::parse("AltLeft").unwrap(), 30);
+        tap_key(&mock, KeyId::parse("Mouse5").unwrap(), 30);
+
+        assert_eq!(mock.timeline(), vec![
+            (0, KeyDown(0xA4)),
+            (30, KeyUp(0xA4)),
+            (30, MouseDown(MouseButton::X2)),
+            (60, MouseUp(MouseButton::X2)),
+        ]);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::actions::{click_button, drag_mouse, hold_key, release_key, tap_key};
+use crate::cancel::CancelToken;
+use crate::hotkeys::Hotkey;
+use crate::input::{GuardedInput, InputBackend, MouseButton};
+use crate::keys::KeyId;
+use crate::triggers::Trigger;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Position, INT};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// RHAI AUTOMATIONS — `*.rhai` scripts from the config directory
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Scripts only see the functions registered in `build_engine`: no file,
+// network or process access, and `eval` is disabled. Each run has a wall
+// clock limit and can be cancelled; both are checked between operations
+// and during every sleep, including the holds of `press_key` and `click`.
+// Scripts cannot `import` modules. Keys and buttons the script leaves held are
+// released when it ends, however it ends.
+
+/// A script file started by a hotkey
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptBinding {
+    pub file: String,
+    #[serde(with = "crate::hotkeys::binding")]
+    pub hotkey: Option<Hotkey>,
+    /// Hold stops the script when the hotkey is released
+    #[serde(default)]
+    pub trigger: Trigger,
+}
+
+/// Live input state for script queries, kept behind a trait so tests can
+/// supply a fixed state
+pub trait InputState {
+    fn cursor_position(&self) -> (i32, i32);
+    fn is_pressed(&self, key: KeyId) -> bool;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AutomationError {
+    /// The script file name or directory is unusable
+    File(String),
+    /// Syntax error or runtime error raised by the script
+    Script(String),
+    TimedOut,
+    Cancelled,
+}
+
+impl fmt::Display for AutomationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutomationError::File(e) => write!(f, "{}", e),
+            AutomationError::Script(e) => write!(f, "{}", e),
+            AutomationError::TimedOut => write!(f, "time limit reached"),
+            AutomationError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+const TIMED_OUT: &str = "timed out";
+const CANCELLED: &str = "cancelled";
+
+/// Deadline and cancel flag, checked by the engine and after every function
+/// that sleeps
+#[derive(Clone)]
+struct Guard {
+    deadline: Instant,
+    cancel: CancelToken,
+}
+
+impl Guard {
+    fn stop_reason(&self) -> Option<&'static str> {
+        if self.cancel.is_cancelled() {
+            Some(CANCELLED)
+        } else if Instant::now() >= self.deadline {
+            Some(TIMED_OUT)
+        } else {
+            None
+        }
+    }
+
+    fn check(&self) -> Result<(), Box<EvalAltResult>> {
+        match self.stop_reason() {
+            Some(reason) => Err(EvalAltResult::ErrorTerminated(reason.into(), Position::NONE).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn button(name: &str) -> Result<MouseButton, Box<EvalAltResult>> {
+    match name {
+        "left" => Ok(MouseButton::Left),
+        "right" => Ok(MouseButton::Right),
+        "middle" => Ok(MouseButton::Middle),
+        "x1" => Ok(MouseButton::X1),
+        "x2" => Ok(MouseButton::X2),
+        _ => Err(format!("unknown mouse button '{}'", name).into()),
+    }
+}
+
+fn key(name: &str) -> Result<KeyId, Box<EvalAltResult>> {
+    KeyId::parse(name).map_err(|e| e.into())
+}
+
+fn int(value: INT) -> Result<i32, Box<EvalAltResult>> {
+    i32::try_from(value).map_err(|_| format!("{} is out of range", value).into())
+}
+
+fn millis(value: INT) -> Result<u64, Box<EvalAltResult>> {
+    u64::try_from(value).map_err(|_| "duration must not be negative".into())
+}
+
+fn build_engine(input: Arc<dyn InputBackend>, state: Arc<dyn InputState>, guard: Guard) -> Engine {
+    let mut engine = Engine::new();
+
+    // Sandbox limits
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(64 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    let progress_guard = guard.clone();
+    engine.on_progress(move |_| progress_guard.stop_reason().map(Dynamic::from));
+    engine.on_print(|s| println!("Script: {}", s));
+
+    // Keyboard
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn("press_key", move |k: &str| -> Result<(), Box<EvalAltResult>> {
+        tap_key(&*i, key(k)?, 30);
+        g.check()
+    });
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn("press_key", move |k: &str, hold: INT| -> Result<(), Box<EvalAltResult>> {
+        tap_key(&*i, key(k)?, millis(hold)?);
+        g.check()
+    });
+    let i = input.clone();
+    engine.register_fn("key_down", move |k: &str| -> Result<(), Box<EvalAltResult>> {
+        hold_key(&*i, key(k)?);
+        Ok(())
+    });
+    let i = input.clone();
+    engine.register_fn("key_up", move |k: &str| -> Result<(), Box<EvalAltResult>> {
+        release_key(&*i, key(k)?);
+        Ok(())
+    });
+
+    // Mouse
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn("click", move || -> Result<(), Box<EvalAltResult>> {
+        click_button(&*i, MouseButton::Left, 30);
+        g.check()
+    });
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn("click", move |b: &str| -> Result<(), Box<EvalAltResult>> {
+        click_button(&*i, button(b)?, 30);
+        g.check()
+    });
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn("click", move |b: &str, hold: INT| -> Result<(), Box<EvalAltResult>> {
+        click_button(&*i, button(b)?, millis(hold)?);
+        g.check()
+    });
+    let i = input.clone();
+    engine.register_fn("mouse_down", move |b: &str| -> Result<(), Box<EvalAltResult>> {
+        i.mouse_down(button(b)?);
+        Ok(())
+    });
+    let i = input.clone();
+    engine.register_fn("mouse_up", move |b: &str| -> Result<(), Box<EvalAltResult>> {
+        i.mouse_up(button(b)?);
+        Ok(())
+    });
+    let i = input.clone();
+    engine.register_fn("move_mouse_absolute", move |x: INT, y: INT| -> Result<(), Box<EvalAltResult>> {
+        i.move_absolute(int(x)?, int(y)?);
+        Ok(())
+    });
+    let i = input.clone();
+    engine.register_fn("move_mouse_relative", move |x: INT, y: INT| -> Result<(), Box<EvalAltResult>> {
+        i.move_relative(int(x)?, int(y)?);
+        Ok(())
+    });
+    let (i, g) = (input.clone(), guard.clone());
+    engine.register_fn(
+        "drag_mouse",
+        move |x1: INT, y1: INT, x2: INT, y2: INT| -> Result<(), Box<EvalAltResult>> {
+            drag_mouse(&*i, (int(x1)?, int(y1)?), (int(x2)?, int(y2)?));
+            g.check()
+        },
+    );
+    let i = input.clone();
+    engine.register_fn("scroll", move |notches: INT| -> Result<(), Box<EvalAltResult>> {
+        i.scroll(int(notches)?);
+        Ok(())
+    });
+
+    // Timing: `input` is the run's GuardedInput, whose sleep stops early on
+    // cancellation and at the deadline
+    let i = input;
+    engine.register_fn("sleep", move |ms: INT| -> Result<(), Box<EvalAltResult>> {
+        i.sleep(Duration::from_millis(millis(ms)?));
+        guard.check()
+    });
+
+    // Queries
+    let s = state.clone();
+    engine.register_fn("cursor_position", move || -> Array {
+        let (x, y) = s.cursor_position();
+        vec![Dynamic::from(x as INT), Dynamic::from(y as INT)]
+    });
+    let s = state;
+    engine.register_fn("is_pressed", move |k: &str| -> Result<bool, Box<EvalAltResult>> {
+        Ok(s.is_pressed(key(k)?))
+    });
+
+    engine
+}
+
+pub fn run_source(
+    input: Arc<dyn InputBackend>,
+    state: Arc<dyn InputState>,
+    source: &str,
+    time_limit: Duration,
+    cancel: &CancelToken,
+) -> Result<(), AutomationError> {
+    let guard = Guard { deadline: Instant::now() + time_limit, cancel: cancel.clone() };
+    let input = Arc::new(GuardedInput::with_deadline(input, cancel.clone(), guard.deadline));
+    let engine = build_engine(input, state, guard);
+    let ast = engine.compile(source).map_err(|e| AutomationError::Script(e.to_string()))?;
+    engine.run_ast(&ast).map_err(|e| match *e {
+        EvalAltResult::ErrorTerminated(reason, _) if reason.to_string() == CANCELLED => AutomationError::Cancelled,
+        EvalAltResult::ErrorTerminated(..) => AutomationError::TimedOut,
+        other => AutomationError::Script(other.to_string()),
+    })
+}
+
+pub fn run_file(
+    input: Arc<dyn InputBackend>,
+    state: Arc<dyn InputState>,
+    dir: &Path,
+    file: &str,
+    time_limit: Duration,
+    cancel: &CancelToken,
+) -> Result<(), AutomationError> {
+    let path = script_path(dir, file)?;
+    let source = fs::read_to_string(&path)
+        .map_err(|e| AutomationError::File(format!("cannot read {}: {}", path.display(), e)))?;
+    run_source(input, state, &source, time_limit, cancel)
+}
+
+/// Resolve a script name inside `dir`, refusing anything that would reach
+/// outside it
+pub fn script_path(dir: &Path, file: &str) -> Result<PathBuf, AutomationError> {
+    let plain = Path::new(file).file_name().is_some_and(|name| name == file);
+    if !plain || !file.ends_with(".rhai") {
+        return Err(AutomationError::File(format!("'{}' is not a .rhai file name", file)));
+    }
+    Ok(dir.join(file))
+}
+
+/// `*.rhai` file names in `dir`, sorted
+pub fn list_scripts(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".rhai"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent::*, MockBackend};
+
+    struct FixedState;
+
+    impl InputState for FixedState {
+        fn cursor_position(&self) -> (i32, i32) {
+            (640, 360)
+        }
+        fn is_pressed(&self, key: KeyId) -> bool {
+            key.name() == "ShiftLeft"
+        }
+    }
+
+    fn run(mock: &Arc<MockBackend>, source: &str, limit_ms: u64) -> Result<(), AutomationError> {
+        run_source(mock.clone(), Arc::new(FixedState), source, Duration::from_millis(limit_ms), &CancelToken::new())
+    }
+
+    #[test]
+    fn script_drives_input_and_reads_state() {
+        let mock = Arc::new(MockBackend::new());
+        let source = r#"
+            let pos = cursor_position();
+            move_mouse_absolute(pos[0] + 10, pos[1]);
+            if is_pressed("ShiftLeft") && !is_pressed("KeyQ") {
+                for n in 0..2 { press_key("KeyQ", 20); sleep(100); }
+            }
+            click("right", 5);
+            scroll(-2);
+        "#;
+        run(&mock, source, 5_000).unwrap();
+
+        assert_eq!(mock.timeline(), vec![
+            (0, MoveAbsolute(650, 360)),
+            (0, KeyDown(0x51)), (20, KeyUp(0x51)),
+            (120, KeyDown(0x51)), (140, KeyUp(0x51)),
+            (240, MouseDown(MouseButton::Right)), (245, MouseUp(MouseButton::Right)),
+            (245, Scroll(-2)),
+        ]);
+    }
+
+    #[test]
+    fn bad_arguments_are_script_errors() {
+        let mock = Arc::new(MockBackend::new());
+        let err = run(&mock, r#"press_key("NotAKey")"#, 1_000).unwrap_err();
+        assert!(matches!(err, AutomationError::Script(ref e) if e.contains("unknown key 'NotAKey'")), "{:?}", err);
+
+        let err = run(&mock, "let x = ;", 1_000).unwrap_err();
+        assert!(matches!(err, AutomationError::Script(_)));
+        assert!(mock.events().is_empty());
+    }
+
+    #[test]
+    fn eval_is_disabled() {
+        let mock = Arc::new(MockBackend::new());
+        assert!(matches!(run(&mock, r#"eval("click()")"#, 1_000), Err(AutomationError::Script(_))));
+        assert!(mock.events().is_empty());
+    }
+
+    #[test]
+    fn endless_loop_hits_time_limit() {
+        let mock = Arc::new(MockBackend::new());
+        let started = Instant::now();
+        assert_eq!(run(&mock, "loop { }", 50), Err(AutomationError::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn long_key_hold_hits_time_limit() {
+        let mock = Arc::new(MockBackend::new());
+        let started = Instant::now();
+        // Far too long to finish, even with the mock's instant sleep
+        assert_eq!(run(&mock, r#"press_key("KeyW", 1_000_000_000_000); click()"#, 50), Err(AutomationError::TimedOut));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(mock.events(), vec![KeyDown(0x57), KeyUp(0x57)]);
+    }
+
+    #[test]
+    fn imports_are_not_resolved() {
+        let mock = Arc::new(MockBackend::new());
+        let err = run(&mock, r#"import "helpers" as h; click()"#, 1_000).unwrap_err();
+        assert!(matches!(err, AutomationError::Script(_)), "{:?}", err);
+        assert!(mock.events().is_empty());
+    }
+
+    #[test]
+    fn cancellation_stops_a_sleeping_script() {
+        let mock = Arc::new(MockBackend::new());
+        let cancel = CancelToken::new();
+        let token = cancel.clone();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            token.cancel();
+        });
+        // The mock's sleep is instant, so keep the script busy long enough
+        let result = run_source(
+            mock.clone(), Arc::new(FixedState),
+            r#"key_down("KeyW"); loop { sleep(1); }"#, Duration::from_secs(60), &cancel,
+        );
+        stopper.join().unwrap();
+
+        assert_eq!(result, Err(AutomationError::Cancelled));
+        // The held key is let go once the script stops
+        assert_eq!(mock.events(), vec![KeyDown(0x57), KeyUp(0x57)]);
+    }
+
+    #[test]
+    fn script_names_must_stay_in_the_directory() {
+        let dir = Path::new("/config/scripts");
+        assert_eq!(script_path(dir, "farm.rhai"), Ok(dir.join("farm.rhai")));
+        assert!(script_path(dir, "../settings.rhai").is_err());
+        assert!(script_path(dir, "/etc/passwd.rhai").is_err());
+        assert!(script_path(dir, "notes.txt").is_err());
+    }
+}
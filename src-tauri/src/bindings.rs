@@ -0,0 +1,260 @@
+use crate::clicker::ClickerState;
+use crate::hotkeys::Hotkey;
+use crate::macros::MacroConfig;
+use crate::profiles::ProfileStore;
+use crate::triggers::Trigger;
+use serde::Serialize;
+use std::fmt;
+use std::mem;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// BINDING REGISTRY — every live hotkey, checked against the others
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Live means the active profile's clicker and macro hotkeys plus the global
+// ones. Two bindings on the same hotkey and trigger would both act on one
+// press, so updates that create one are rejected. Overlaps, where pressing
+// one chord can set off another binding on the way, are only reported.
+// Every other profile is held to the same rule, since it goes live as soon
+// as it is activated.
+
+/// The setting a hotkey belongs to
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BindingId {
+    ClickerToggle,
+    Record,
+    Panic,
+    Macro { name: String },
+    Script { file: String },
+    ProfileCycle,
+    WindowToggle,
+    OverlayToggle,
+}
+
+impl fmt::Display for BindingId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingId::ClickerToggle => f.write_str("the clicker toggle"),
+            BindingId::Record => f.write_str("the record key"),
+            BindingId::Panic => f.write_str("the emergency stop"),
+            BindingId::Macro { name } => write!(f, "macro '{}'", name),
+            BindingId::Script { file } => write!(f, "script '{}'", file),
+            BindingId::ProfileCycle => f.write_str("the next profile key"),
+            BindingId::WindowToggle => f.write_str("the show/hide window key"),
+            BindingId::OverlayToggle => f.write_str("the overlay key"),
+        }
+    }
+}
+
+pub struct Binding {
+    pub id: BindingId,
+    pub hotkey: Hotkey,
+    pub trigger: Trigger,
+}
+
+/// Every bound hotkey of the given settings
+pub fn collect(clicker: &ClickerState, mc: &MacroConfig, store: &ProfileStore) -> Vec<Binding> {
+    let press = |id: BindingId, hotkey: Option<Hotkey>| (id, hotkey, Trigger::Press);
+    let all = [
+        (BindingId::ClickerToggle, clicker.toggle_key, clicker.toggle_trigger),
+        press(BindingId::Record, mc.record_key),
+        press(BindingId::Panic, mc.panic_key),
+    ]
+    .into_iter()
+    .chain(mc.macros.iter().map(|m| (BindingId::Macro { name: m.name.clone() }, m.hotkey, m.trigger)))
+    .chain(mc.scripts.iter().map(|s| (BindingId::Script { file: s.file.clone() }, s.hotkey, s.trigger)))
+    .chain([
+        press(BindingId::ProfileCycle, store.cycle_key),
+        press(BindingId::WindowToggle, store.window_key),
+        press(BindingId::OverlayToggle, store.overlay_key),
+    ]);
+    all.filter_map(|(id, hotkey, trigger)| Some(Binding { id, hotkey: hotkey?, trigger })).collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+    /// Same hotkey and trigger type: one press acts twice
+    Duplicate,
+    /// Same hotkey with different triggers, or one hotkey pressed as part of
+    /// another's chord
+    Overlap,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub bindings: [BindingId; 2],
+    pub message: String,
+}
+
+fn conflict(a: &Binding, b: &Binding) -> Option<Conflict> {
+    let (kind, message) = if a.hotkey == b.hotkey {
+        if mem::discriminant(&a.trigger) == mem::discriminant(&b.trigger) {
+            (ConflictKind::Duplicate, format!("{} is bound to both {} and {}", a.hotkey, a.id, b.id))
+        } else {
+            let message = format!("{} is bound to both {} and {}, with different triggers", a.hotkey, a.id, b.id);
+            (ConflictKind::Overlap, message)
+        }
+    } else if a.hotkey.is_part_of(&b.hotkey) {
+        (ConflictKind::Overlap, overlap_message(b, a))
+    } else if b.hotkey.is_part_of(&a.hotkey) {
+        (ConflictKind::Overlap, overlap_message(a, b))
+    } else {
+        return None;
+    };
+    Some(Conflict { kind, bindings: [a.id.clone(), b.id.clone()], message })
+}
+
+fn overlap_message(chord: &Binding, part: &Binding) -> String {
+    format!("pressing {} for {} can also set off {} ({})", chord.hotkey, chord.id, part.id, part.hotkey)
+}
+
+/// Every pair of bindings that conflict, in binding order
+pub fn conflicts(bindings: &[Binding]) -> Vec<Conflict> {
+    let mut found = Vec::new();
+    for (i, a) in bindings.iter().enumerate() {
+        found.extend(bindings[i + 1..].iter().filter_map(|b| conflict(a, b)));
+    }
+    found
+}
+
+/// Why a settings update was refused. Plain validation errors have no
+/// conflicts; duplicate hotkeys list each one.
+#[derive(Debug, Serialize)]
+pub struct UpdateError {
+    pub message: String,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl From<String> for UpdateError {
+    fn from(message: String) -> UpdateError {
+        UpdateError { message, conflicts: Vec::new() }
+    }
+}
+
+/// Refuse settings with duplicate hotkeys; overlaps are returned as warnings
+pub fn check(bindings: &[Binding]) -> Result<Vec<Conflict>, UpdateError> {
+    let (duplicates, overlaps): (Vec<_>, Vec<_>) =
+        conflicts(bindings).into_iter().partition(|c| c.kind == ConflictKind::Duplicate);
+    if duplicates.is_empty() {
+        return Ok(overlaps);
+    }
+    let message = duplicates.iter().map(|c| c.message.as_str()).collect::<Vec<_>>().join("; ");
+    Err(UpdateError { message, conflicts: duplicates })
+}
+
+/// `check` the live settings and every inactive profile of `store`, each
+/// with the global hotkeys. Only the live settings' overlaps are returned.
+pub fn check_store(clicker: &ClickerState, mc: &MacroConfig, store: &ProfileStore) -> Result<Vec<Conflict>, UpdateError> {
+    for profile in store.profiles().iter().filter(|p| p.name != store.active_name()) {
+        check(&collect(&profile.clicker, &profile.macro_config, store)).map_err(|e| UpdateError {
+            message: format!("profile '{}': {}", profile.name, e.message),
+            conflicts: e.conflicts,
+        })?;
+    }
+    check(&collect(clicker, mc, store))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::ScriptBinding;
+    use crate::macros::Macro;
+
+    fn hotkey(text: &str) -> Option<Hotkey> {
+        Some(Hotkey::parse(text).unwrap())
+    }
+
+    fn macro_on(name: &str, key: &str, trigger: Trigger) -> Macro {
+        Macro { name: name.into(), hotkey: hotkey(key), trigger, steps: Vec::new() }
+    }
+
+    /// Defaults: toggle F6, record F10, panic F12, show/hide Insert
+    fn live(macros: Vec<Macro>) -> Vec<Binding> {
+        let mc = MacroConfig { macros, ..MacroConfig::default() };
+        collect(&ClickerState::default(), &mc, &ProfileStore::default())
+    }
+
+    #[test]
+    fn defaults_do_not_conflict() {
+        let bindings = live(Vec::new());
+        assert_eq!(bindings.len(), 4);
+        assert_eq!(check(&bindings).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_the_same_hotkey_twice() {
+        let err = check(&live(vec![
+            macro_on("Part 1", "F6", Trigger::Press),
+            macro_on("Part 2", "F6", Trigger::Press),
+        ]))
+        .unwrap_err();
+        let pairs: Vec<_> = err.conflicts.iter().map(|c| c.bindings.clone()).collect();
+        let part = |n: &str| BindingId::Macro { name: n.into() };
+        assert_eq!(pairs, vec![
+            [BindingId::ClickerToggle, part("Part 1")],
+            [BindingId::ClickerToggle, part("Part 2")],
+            [part("Part 1"), part("Part 2")],
+        ]);
+        assert!(err.message.starts_with("F6 is bound to both the clicker toggle and macro 'Part 1'; "), "{}", err.message);
+    }
+
+    #[test]
+    fn warns_about_overlaps() {
+        let warnings = check(&live(vec![
+            macro_on("Loot", "Ctrl+F6", Trigger::Press),
+            macro_on("Stop", "F12", Trigger::DoubleTap { window_ms: 300 }),
+        ]))
+        .unwrap();
+        assert_eq!(warnings.iter().map(|c| c.message.as_str()).collect::<Vec<_>>(), vec![
+            "pressing Ctrl+F6 for macro 'Loot' can also set off the clicker toggle (F6)",
+            "F12 is bound to both the emergency stop and macro 'Stop', with different triggers",
+        ]);
+        assert!(warnings.iter().all(|c| c.kind == ConflictKind::Overlap));
+    }
+
+    #[test]
+    fn covers_scripts_and_global_keys() {
+        let mc = MacroConfig {
+            scripts: vec![ScriptBinding { file: "farm.rhai".into(), hotkey: hotkey("Insert"), trigger: Trigger::Press }],
+            ..MacroConfig::default()
+        };
+        let mut store = ProfileStore::default();
+        store.overlay_key = hotkey("ShiftLeft");
+        let clicker = ClickerState { toggle_key: hotkey("Shift+F6"), ..ClickerState::default() };
+
+        let found = conflicts(&collect(&clicker, &mc, &store));
+        let kinds: Vec<_> = found.iter().map(|c| (c.kind, c.bindings.clone())).collect();
+        assert_eq!(kinds, vec![
+            (ConflictKind::Overlap, [BindingId::ClickerToggle, BindingId::OverlayToggle]),
+            (ConflictKind::Duplicate, [BindingId::Script { file: "farm.rhai".into() }, BindingId::WindowToggle]),
+        ]);
+    }
+
+    #[test]
+    fn conflicts_serialize_for_the_frontend() {
+        let err = check(&live(vec![macro_on("Loot", "F10", Trigger::Press)])).unwrap_err();
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["conflicts"][0]["kind"], "duplicate");
+        assert_eq!(json["conflicts"][0]["bindings"][0]["type"], "record");
+        assert_eq!(json["conflicts"][0]["bindings"][1]["name"], "Loot");
+    }
+
+    #[test]
+    fn checks_inactive_profiles_against_the_global_keys() {
+        let mut store = ProfileStore::default();
+        store.create("Aim").unwrap();
+        let mut aim = store.get("Aim").unwrap().clone();
+        aim.macro_config.macros.push(macro_on("Scope", "Insert", Trigger::Press));
+        store.put(aim);
+
+        let live = store.active().clone();
+        let err = check_store(&live.clicker, &live.macro_config, &store).unwrap_err();
+        assert_eq!(err.message, "profile 'Aim': Insert is bound to both macro 'Scope' and the show/hide window key");
+
+        store.window_key = hotkey("Home");
+        assert!(check_store(&live.clicker, &live.macro_config, &store).is_ok());
+    }
+}
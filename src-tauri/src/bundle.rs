@@ -0,0 +1,487 @@
+use crate::automation::{script_path, ScriptBinding};
+use crate::bindings;
+use crate::config;
+use crate::macros::{Macro, MacroConfig};
+use crate::profiles::{Profile, ProfileStore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// BUNDLES — shareable files with profiles and macros
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// {
+//   "format": "cliky-bundle",
+//   "version": 1,
+//   "profiles": [ { "name", "clicker", "macro_config" }, … ],
+//   "macros": [ { "name", "hotkey", "steps" }, … ]
+// }
+//
+// Profiles use the settings.json profile layout. Loose macros are imported
+// into the active profile. Every entry is checked on its own, hotkeys
+// included, so one bad entry is reported and skipped instead of failing the
+// whole import.
+
+pub const FORMAT: &str = "cliky-bundle";
+pub const VERSION: u32 = 1;
+
+/// What to do when an imported profile or macro has a name already in use
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Conflict {
+    /// Keep the existing one and leave the imported one out
+    Skip,
+    /// Import under a free name like "Name (2)"
+    Rename,
+    /// Overwrite the existing one
+    Replace,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    /// Names the imported profiles ended up with
+    pub profiles: Vec<String>,
+    /// Names the imported loose macros ended up with
+    pub macros: Vec<String>,
+    /// Entries imported with changes (renamed, key unbound, …)
+    pub warnings: Vec<String>,
+    /// Entries left out, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Bundle text for the named profiles and macros of the active profile
+pub fn export(store: &ProfileStore, profiles: &[String], macros: &[String]) -> Result<String, String> {
+    let profiles = profiles
+        .iter()
+        .map(|name| {
+            let mut p = store.get(name).ok_or_else(|| format!("no profile named '{}'", name))?.clone();
+            p.clicker.running = false;
+            Ok(p)
+        })
+        .collect::<Result<Vec<Profile>, String>>()?;
+    let own = &store.active().macro_config.macros;
+    let macros = macros
+        .iter()
+        .map(|name| {
+            own.iter().find(|m| &m.name == name).cloned().ok_or_else(|| format!("no macro named '{}'", name))
+        })
+        .collect::<Result<Vec<Macro>, String>>()?;
+    let bundle = json!({ "format": FORMAT, "version": VERSION, "profiles": profiles, "macros": macros });
+    Ok(serde_json::to_string_pretty(&bundle).expect("bundle is always serializable"))
+}
+
+/// Import a bundle into `store`. Only an unreadable bundle is an error;
+/// problems with single entries end up in the report.
+pub fn import(text: &str, store: &mut ProfileStore, conflict: Conflict) -> Result<ImportReport, String> {
+    let doc: Value = serde_json::from_str(text).map_err(|e| format!("not valid JSON: {}", e))?;
+    if doc.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(format!("not a {} file", FORMAT));
+    }
+    let version = doc.get("version").and_then(Value::as_u64).ok_or("missing version")?;
+    if version > VERSION as u64 {
+        return Err(format!("bundle format {} is newer than this build reads ({})", version, VERSION));
+    }
+
+    let mut report = ImportReport::default();
+    for (i, entry) in entries(&doc, "profiles")?.iter().enumerate() {
+        let mut entry = entry.clone();
+        let fixes = Fixes {
+            skipped: remove_macros_with_unknown_keys(&mut entry),
+            warnings: config::sanitize_profile(&mut entry, &config::CURRENT),
+        };
+        match serde_json::from_value::<Profile>(entry) {
+            Ok(profile) => import_profile(profile, fixes, store, conflict, &mut report),
+            Err(e) => report.skipped.push(format!("profile #{}: {}", i + 1, e)),
+        }
+    }
+    for (i, entry) in entries(&doc, "macros")?.iter().enumerate() {
+        let mut entry = entry.clone();
+        let name = entry.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+        if let Some(problem) = unknown_key_step(&mut entry) {
+            report.skipped.push(format!("macro '{}': {}", name, problem));
+            continue;
+        }
+        let warnings = config::sanitize_macro(&mut entry, &config::CURRENT);
+        match serde_json::from_value::<Macro>(entry) {
+            Ok(m) => import_macro(m, warnings, store, conflict, &mut report),
+            Err(e) => report.skipped.push(format!("macro #{}: {}", i + 1, e)),
+        }
+    }
+    Ok(report)
+}
+
+/// Changes made to a profile entry before it was read, reported only if the
+/// profile is imported
+struct Fixes {
+    warnings: Vec<String>,
+    skipped: Vec<String>,
+}
+
+/// First step of a macro entry that presses a key this build does not know
+fn unknown_key_step(m: &mut Value) -> Option<String> {
+    m.get_mut("steps").map(|steps| config::remove_unknown_key_steps(steps, &config::CURRENT)).unwrap_or_default().into_iter().next()
+}
+
+/// Take out a profile's macros with unknown keys. A macro like that is
+/// skipped as a whole rather than run with a gap in it.
+fn remove_macros_with_unknown_keys(profile: &mut Value) -> Vec<String> {
+    let mut skipped = Vec::new();
+    if let Some(macros) = profile.pointer_mut("/macro_config/macros").and_then(Value::as_array_mut) {
+        macros.retain_mut(|m| match unknown_key_step(m) {
+            Some(problem) => {
+                let name = m.get("name").and_then(Value::as_str).unwrap_or_default();
+                skipped.push(format!("macro '{}': {}", name, problem));
+                false
+            }
+            None => true,
+        });
+    }
+    skipped
+}
+
+fn entries<'a>(doc: &'a Value, field: &str) -> Result<&'a [Value], String> {
+    match doc.get(field) {
+        None => Ok(&[]),
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(format!("'{}' must be a list", field)),
+    }
+}
+
+/// `base`, or "base (2)", "base (3)", … whichever is free
+fn free_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    (1..)
+        .map(|n| if n == 1 { base.to_string() } else { format!("{} ({})", base, n) })
+        .find(|name| !taken(name))
+        .expect("some name is free")
+}
+
+/// Resolve a name clash; `None` means the entry is skipped
+fn resolve_name(
+    kind: &str, name: &str, conflict: Conflict, taken: impl Fn(&str) -> bool, report: &mut ImportReport,
+) -> Option<String> {
+    if !taken(name) {
+        return Some(name.to_string());
+    }
+    match conflict {
+        Conflict::Skip => {
+            report.skipped.push(format!("{} '{}': name already in use", kind, name));
+            None
+        }
+        Conflict::Rename => {
+            let renamed = free_name(name, taken);
+            report.warnings.push(format!("{} '{}' imported as '{}'", kind, name, renamed));
+            Some(renamed)
+        }
+        Conflict::Replace => {
+            report.warnings.push(format!("{} '{}' replaced", kind, name));
+            Some(name.to_string())
+        }
+    }
+}
+
+fn import_profile(
+    mut profile: Profile, fixes: Fixes, store: &mut ProfileStore, conflict: Conflict, report: &mut ImportReport,
+) {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        report.skipped.push("profile without a name".into());
+        return;
+    }
+    // Settings a profile cannot run with skip it as a whole; they would
+    // also make every later reload of settings.json fail
+    let context = format!("profile '{}'", name);
+    let dropped = check_macro_config(&mut profile.macro_config, &context);
+    let checked = profile.clicker.validate().and_then(|_| profile.macro_config.validate()).and_then(|_| {
+        let live = bindings::collect(&profile.clicker, &profile.macro_config, store);
+        bindings::check(&live).map(|_| ()).map_err(|e| e.message)
+    });
+    if let Err(e) = checked {
+        report.skipped.push(format!("{}: {}", context, e));
+        return;
+    }
+    let Some(name) = resolve_name("profile", &name, conflict, |n| store.get(n).is_some(), report) else {
+        return;
+    };
+    profile.name = name;
+    profile.clicker.running = false;
+
+    report.warnings.extend(fixes.warnings.iter().map(|w| format!("{}: {}", context, w)));
+    report.skipped.extend(fixes.skipped.iter().map(|s| format!("{}, {}", context, s)));
+    report.skipped.extend(dropped);
+
+    report.profiles.push(profile.name.clone());
+    store.put(profile);
+}
+
+fn import_macro(
+    mut m: Macro, warnings: Vec<String>, store: &mut ProfileStore, conflict: Conflict, report: &mut ImportReport,
+) {
+    let context = format!("macro '{}'", m.name);
+    if !check_macro(&m, &context, &mut report.skipped) {
+        return;
+    }
+    let active = store.active();
+    let taken = |n: &str| active.macro_config.macros.iter().any(|existing| existing.name == n);
+    // Renames and replacements are only reported once the hotkey is known
+    // to fit in
+    let mut resolved = ImportReport::default();
+    let name = resolve_name("macro", &m.name, conflict, taken, &mut resolved);
+    report.skipped.append(&mut resolved.skipped);
+    let Some(name) = name else {
+        return;
+    };
+    m.name = name;
+    let mut config = active.macro_config.clone();
+    match config.macros.iter_mut().find(|existing| existing.name == m.name) {
+        Some(existing) => *existing = m.clone(),
+        None => config.macros.push(m.clone()),
+    }
+    if let Err(e) = bindings::check(&bindings::collect(&active.clicker, &config, store)) {
+        report.skipped.push(format!("{}: {}", context, e.message));
+        return;
+    }
+    report.warnings.append(&mut resolved.warnings);
+    report.warnings.extend(warnings.iter().map(|w| format!("{}: {}", context, w)));
+    report.macros.push(m.name);
+    store.active_mut().macro_config = config;
+}
+
+/// Take out the macros and script bindings that cannot be used, returning
+/// what was left out
+fn check_macro_config(config: &mut MacroConfig, context: &str) -> Vec<String> {
+    let mut skipped = Vec::new();
+    let mut seen: Vec<String> = Vec::new();
+    config.macros.retain(|m| {
+        let context = format!("{}, macro '{}'", context, m.name);
+        if seen.contains(&m.name) {
+            skipped.push(format!("{}: name used twice", context));
+            return false;
+        }
+        seen.push(m.name.clone());
+        check_macro(m, &context, &mut skipped)
+    });
+    let check_binding = |binding: &ScriptBinding| {
+        script_path(Path::new(""), &binding.file).map_err(|e| e.to_string())?;
+        binding.trigger.validate()
+    };
+    config.scripts.retain(|binding| match check_binding(binding) {
+        Ok(_) => true,
+        Err(e) => {
+            skipped.push(format!("{}, script binding: {}", context, e));
+            false
+        }
+    });
+    skipped
+}
+
+/// Check a macro's name, trigger and script steps, which only parse when run.
+/// A macro that would fail part way is skipped as a whole.
+fn check_macro(m: &Macro, context: &str, skipped: &mut Vec<String>) -> bool {
+    if m.name.trim().is_empty() {
+        skipped.push(format!("{}: no name", context));
+        return false;
+    }
+    match m.validate() {
+        Ok(()) => true,
+        Err(e) => {
+            skipped.push(format!("{}: {}", context, e));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clicker::ClickMode;
+    use crate::focus::AutoProfileRule;
+    use crate::hotkeys::Hotkey;
+    use crate::keys::KeyId;
+    use crate::macros::MacroStep;
+    use crate::triggers::Trigger;
+
+    fn source_store() -> ProfileStore {
+        let mut store = ProfileStore::default();
+        store.active_mut().macro_config.macros.push(Macro {
+            name: "Loot".into(),
+            hotkey: Hotkey::parse("F9").ok(),
+            trigger: Trigger::Press,
+            steps: vec![MacroStep::KeyTap { key: KeyId::parse("KeyE").unwrap(), hold_ms: 30 }],
+        });
+        store.active_mut().clicker.cps = 15;
+        store.create("Aim").unwrap();
+        store
+    }
+
+    fn macro_names(store: &ProfileStore) -> Vec<String> {
+        store.active().macro_config.macros.iter().map(|m| m.name.clone()).collect()
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let text = export(&source_store(), &["Default".into(), "Aim".into()], &["Loot".into()]).unwrap();
+
+        let mut target = ProfileStore::with_profile(Profile::new("Mine"));
+        let report = import(&text, &mut target, Conflict::Skip).unwrap();
+        assert_eq!(report.profiles, vec!["Default", "Aim"]);
+        assert_eq!(report.macros, vec!["Loot"]);
+        assert!(report.warnings.is_empty() && report.skipped.is_empty(), "{:?}", report);
+        assert_eq!(target.names(), vec!["Mine", "Default", "Aim"]);
+        assert_eq!(target.get("Default").unwrap().clicker.cps, 15);
+        assert_eq!(macro_names(&target), vec!["Loot"]);
+    }
+
+    #[test]
+    fn export_rejects_unknown_names() {
+        assert!(export(&source_store(), &["Nope".into()], &[]).is_err());
+        assert!(export(&source_store(), &[], &["Nope".into()]).is_err());
+    }
+
+    #[test]
+    fn name_conflicts_follow_the_policy() {
+        let text = export(&source_store(), &["Aim".into()], &["Loot".into()]).unwrap();
+
+        let mut skip = source_store();
+        let report = import(&text, &mut skip, Conflict::Skip).unwrap();
+        assert!(report.profiles.is_empty() && report.macros.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+
+        // A renamed copy still needs a hotkey of its own
+        let mut rename = source_store();
+        let report = import(&text, &mut rename, Conflict::Rename).unwrap();
+        assert_eq!(report.profiles, vec!["Aim (2)"]);
+        assert!(report.skipped[0].contains("F9"), "{:?}", report.skipped);
+        let moved = text.replace(r#""hotkey": "F9""#, r#""hotkey": "F4""#);
+        let mut rename = source_store();
+        import(&moved, &mut rename, Conflict::Rename).unwrap();
+        assert_eq!(macro_names(&rename), vec!["Loot", "Loot (2)"]);
+
+        let mut replace = source_store();
+        let report = import(&text, &mut replace, Conflict::Replace).unwrap();
+        assert_eq!(report.warnings.len(), 2);
+        assert_eq!(replace.names(), vec!["Default", "Aim"]);
+        assert_eq!(macro_names(&replace), vec!["Loot"]);
+    }
+
+    #[test]
+    fn reports_unknown_keys_and_bad_entries() {
+        let text = r#"{
+            "format": "cliky-bundle",
+            "version": 1,
+            "profiles": [
+                {
+                    "name": "Shared",
+                    "clicker": {"running": true, "cps": 12, "randomness": 0, "humanization_enabled": true,
+                                "toggle_key": "Hyper7", "click_mode": "triple"},
+                    "macro_config": {
+                        "macros": [
+                            {"name": "Good", "hotkey": "F8", "steps": [{"type": "wait", "ms": 10}]},
+                            {"name": "Bad", "hotkey": "F7", "steps": [
+                                {"type": "repeat", "times": 2, "steps": [{"type": "keyTap", "key": "KeyNope"}]}
+                            ]}
+                        ],
+                        "panicKey": "Nowhere",
+                        "scripts": [{"file": "../evil.rhai", "hotkey": "F6"}]
+                    }
+                },
+                {"name": "Broken"}
+            ],
+            "macros": [
+                {"name": "Scripted", "hotkey": "Mouse9", "steps": [{"type": "script", "source": "tap NotAKey"}]},
+                {"name": "Fine", "hotkey": "Mouse9", "steps": []}
+            ]
+        }"#;
+        let mut store = ProfileStore::default();
+        let report = import(text, &mut store, Conflict::Skip).unwrap();
+
+        assert_eq!(report.profiles, vec!["Shared"]);
+        assert_eq!(report.macros, vec!["Fine"]);
+        let shared = store.get("Shared").unwrap();
+        assert!(!shared.clicker.running);
+        assert_eq!(shared.clicker.toggle_key, None);
+        assert_eq!(shared.clicker.click_mode, ClickMode::Left);
+        assert_eq!(shared.macro_config.panic_key, None);
+        assert_eq!(shared.macro_config.macros.len(), 1);
+        assert!(shared.macro_config.scripts.is_empty());
+
+        let text = format!("{:?} {:?}", report.warnings, report.skipped);
+        for expected in ["Hyper7", "triple", "Nowhere", "Mouse9", "step 1: step 1: unknown key 'KeyNope'", "evil.rhai", "profile #2", "macro 'Scripted'"] {
+            assert!(text.contains(expected), "missing {} in {}", expected, text);
+        }
+    }
+
+    #[test]
+    fn skips_profiles_with_settings_out_of_range() {
+        let text = r#"{
+            "format": "cliky-bundle",
+            "version": 1,
+            "profiles": [
+                {"name": "Fast", "clicker": {"running": false, "cps": 0, "randomness": 0, "humanization_enabled": true,
+                                             "toggle_key": "F6", "click_mode": "left"},
+                 "macro_config": {"macros": []}},
+                {"name": "Unlimited", "clicker": {"running": false, "cps": 10, "randomness": 0, "humanization_enabled": true,
+                                                  "toggle_key": "F6", "click_mode": "left"},
+                 "macro_config": {"macros": [], "scriptTimeLimitMs": 0}}
+            ]
+        }"#;
+        let mut store = ProfileStore::default();
+        let report = import(text, &mut store, Conflict::Skip).unwrap();
+        assert!(report.profiles.is_empty());
+        assert_eq!(store.names(), vec!["Default"]);
+        assert!(report.skipped[0].contains("profile 'Fast': clicks per second"), "{:?}", report.skipped);
+        assert!(report.skipped[1].contains("profile 'Unlimited': script time limit"), "{:?}", report.skipped);
+    }
+
+    #[test]
+    fn skips_entries_whose_hotkeys_clash() {
+        let text = r#"{
+            "format": "cliky-bundle",
+            "version": 1,
+            "profiles": [
+                {"name": "Clash", "clicker": {"running": false, "cps": 10, "randomness": 0, "humanization_enabled": true,
+                                              "toggle_key": "Insert", "click_mode": "left"},
+                 "macro_config": {"macros": []}},
+                {"name": "Fine", "clicker": {"running": false, "cps": 10, "randomness": 0, "humanization_enabled": true,
+                                             "toggle_key": "F6", "click_mode": "left"},
+                 "macro_config": {"macros": []}}
+            ],
+            "macros": [
+                {"name": "Toggle", "hotkey": "F6", "steps": []},
+                {"name": "Loot", "hotkey": "F9", "steps": []},
+                {"name": "Other", "hotkey": "F4", "steps": []}
+            ]
+        }"#;
+        let mut store = source_store();
+        let report = import(text, &mut store, Conflict::Rename).unwrap();
+        assert_eq!(report.profiles, vec!["Fine"]);
+        assert_eq!(report.macros, vec!["Other"]);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        let text = report.skipped.join("; ");
+        for expected in ["profile 'Clash': Insert", "macro 'Toggle': F6", "macro 'Loot': F9"] {
+            assert!(text.contains(expected), "missing {} in {}", expected, text);
+        }
+        assert_eq!(macro_names(&store), vec!["Loot", "Other"]);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_bundles() {
+        let mut store = ProfileStore::default();
+        assert!(import("{", &mut store, Conflict::Skip).unwrap_err().contains("not valid JSON"));
+        assert!(import(r#"{"version": 3}"#, &mut store, Conflict::Skip).unwrap_err().contains("not a cliky-bundle"));
+        let newer = r#"{"format": "cliky-bundle", "version": 2}"#;
+        assert!(import(newer, &mut store, Conflict::Skip).unwrap_err().contains("newer"));
+        let bad = r#"{"format": "cliky-bundle", "version": 1, "profiles": {}}"#;
+        assert!(import(bad, &mut store, Conflict::Skip).unwrap_err().contains("must be a list"));
+    }
+
+    #[test]
+    fn replacing_keeps_auto_rules_pointing_at_the_profile() {
+        let text = export(&source_store(), &["Aim".into()], &[]).unwrap();
+        let mut store = source_store();
+        let rule = AutoProfileRule { profile: "Aim".into(), class: "game".into(), title: String::new() };
+        store.set_auto_rules(vec![rule.clone()]).unwrap();
+        import(&text, &mut store, Conflict::Replace).unwrap();
+        assert_eq!(store.auto_rules(), &[rule]);
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CANCELLATION — cooperative stop signal for macros and scripts
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Shared flag that running macros and scripts poll between steps and while
+/// sleeping. Clones refer to the same flag.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Clear the flag before starting a new run
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
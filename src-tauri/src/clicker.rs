@@ -0,0 +1,208 @@
+use crate::actions::perform_click;
+use crate::input::InputBackend;
+use crate::hotkeys::Hotkey;
+use crate::triggers::Trigger;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Which button the clicker presses, and how
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClickMode {
+    Left,
+    Right,
+    /// Two quick left clicks per tick
+    Double,
+}
+
+/// Allowed click rate; 1000 / cps must leave room for a click
+pub const CPS_RANGE: std::ops::RangeInclusive<u64> = 1..=100;
+/// Largest timing jitter in ms
+pub const MAX_RANDOMNESS: u64 = 500;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClickerState {
+    pub running: bool,
+    pub cps: u64,
+    pub randomness: u64,
+    pub humanization_enabled: bool,
+    #[serde(with = "crate::hotkeys::binding")]
+    pub toggle_key: Option<Hotkey>,
+    /// Hold clicks only while the toggle key is down
+    #[serde(default)]
+    pub toggle_trigger: Trigger,
+    pub click_mode: ClickMode,
+}
+
+impl ClickerState {
+    /// Check values coming from the frontend before they are applied
+    pub fn validate(&self) -> Result<(), String> {
+        if !CPS_RANGE.contains(&self.cps) {
+            return Err(format!(
+                "clicks per second must be between {} and {}, got {}",
+                CPS_RANGE.start(), CPS_RANGE.end(), self.cps
+            ));
+        }
+        if self.randomness > MAX_RANDOMNESS {
+            return Err(format!("randomness must be at most {} ms, got {}", MAX_RANDOMNESS, self.randomness));
+        }
+        self.toggle_trigger.validate().map_err(|e| format!("toggle key: {}", e))
+    }
+}
+
+impl Default for ClickerState {
+    fn default() -> ClickerState {
+        ClickerState {
+            running: false,
+            cps: 10,
+            randomness: 0,
+            humanization_enabled: true,
+            toggle_key: Hotkey::parse("F6").ok(),
+            toggle_trigger: Trigger::Press,
+            click_mode: ClickMode::Left,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CLICK ENGINE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How long to hold the button and how long to wait afterwards, in ms
+pub fn click_timing(
+    cps: u64, randomness: u64, humanization_enabled: bool, rng: &mut impl Rng,
+) -> (u64, u64) {
+    let target_ms: u64 = 1000 / cps;
+    let mut hold_ms = target_ms / 2;
+    let gap_ms;
+
+    if humanization_enabled {
+        let hold_ratio = rng.gen_range(0.3..0.6);
+        hold_ms = (target_ms as f64 * hold_ratio) as u64;
+        if randomness > 0 {
+            let jitter = rng.gen_range(0..=randomness);
+            if rng.gen_bool(0.5) { hold_ms = hold_ms.saturating_add(jitter); }
+            else { hold_ms = hold_ms.saturating_sub(jitter); }
+        }
+        let min_hold = 2.max(target_ms / 4);
+        let max_hold = (target_ms * 3 / 4).max(min_hold);
+        hold_ms = hold_ms.clamp(min_hold, max_hold);
+        gap_ms = {
+            let mut g = target_ms.saturating_sub(hold_ms);
+            if randomness > 0 {
+                let jitter = rng.gen_range(0..=randomness);
+                if rng.gen_bool(0.5) { g = g.saturating_add(jitter); }
+                else { g = g.saturating_sub(jitter); }
+            }
+            g
+        };
+    } else {
+        hold_ms = hold_ms.max(20);
+        gap_ms = target_ms.saturating_sub(hold_ms);
+    }
+
+    (hold_ms, gap_ms)
+}
+
+/// One iteration of the engine thread: a click followed by its gap when the
+/// clicker is running, otherwise a short idle wait.
+pub fn tick(
+    input: &dyn InputBackend,
+    state: &Mutex<ClickerState>,
+    macro_running: &Mutex<bool>,
+    rng: &mut impl Rng,
+) {
+    let (running, cps, randomness, humanization_enabled, click_mode) = {
+        let s = state.lock().unwrap();
+        (s.running, s.cps, s.randomness, s.humanization_enabled, s.click_mode)
+    };
+
+    // Pause clicker while macro is running to avoid interference
+    let macro_active = *macro_running.lock().unwrap();
+
+    if running && !macro_active && cps > 0 {
+        let (hold_ms, gap_ms) = click_timing(cps, randomness, humanization_enabled, rng);
+        perform_click(input, click_mode, hold_ms);
+        input.sleep(Duration::from_millis(gap_ms));
+    } else {
+        input.sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent::*, MockBackend};
+    use crate::input::MouseButton::Left;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn state(running: bool, cps: u64, humanization_enabled: bool) -> Mutex<ClickerState> {
+        Mutex::new(ClickerState {
+            running,
+            cps,
+            randomness: 0,
+            humanization_enabled,
+            ..ClickerState::default()
+        })
+    }
+
+    #[test]
+    fn validation_rejects_out_of_range_settings() {
+        assert!(ClickerState::default().validate().is_ok());
+        let err = ClickerState { cps: 0, ..ClickerState::default() }.validate().unwrap_err();
+        assert!(err.contains("between 1 and 100"), "{}", err);
+        assert!(ClickerState { randomness: 501, ..ClickerState::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn fixed_timing_without_humanization() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(click_timing(10, 0, false, &mut rng), (50, 50));
+        // Hold never drops below 20ms even at high CPS
+        assert_eq!(click_timing(25, 0, false, &mut rng), (20, 20));
+    }
+
+    #[test]
+    fn humanized_timing_stays_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let (hold, gap) = click_timing(10, 15, true, &mut rng);
+            assert!((25..=75).contains(&hold), "hold {}", hold);
+            assert!(gap <= 100 - 25 + 15, "gap {}", gap);
+        }
+    }
+
+    #[test]
+    fn running_clicker_clicks_at_target_rate() {
+        let mock = MockBackend::new();
+        let state = state(true, 10, false);
+        let macro_running = Mutex::new(false);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..3 {
+            tick(&mock, &state, &macro_running, &mut rng);
+        }
+
+        assert_eq!(mock.timeline(), vec![
+            (0, MouseDown(Left)), (50, MouseUp(Left)),
+            (100, MouseDown(Left)), (150, MouseUp(Left)),
+            (200, MouseDown(Left)), (250, MouseUp(Left)),
+        ]);
+        assert_eq!(mock.now(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn clicker_idles_when_stopped_or_paused_by_macro() {
+        let mock = MockBackend::new();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        tick(&mock, &state(false, 10, true), &Mutex::new(false), &mut rng);
+        tick(&mock, &state(true, 10, true), &Mutex::new(true), &mut rng);
+        tick(&mock, &state(true, 0, true), &Mutex::new(false), &mut rng);
+
+        assert!(mock.events().is_empty());
+        assert_eq!(mock.now(), Duration::from_millis(30));
+    }
+}
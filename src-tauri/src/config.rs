@@ -0,0 +1,579 @@
+use crate::clicker::ClickMode;
+use crate::hotkeys;
+use crate::keys::KeyId;
+use crate::profiles::{ProfileStore, DEFAULT_PROFILE};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PERSISTENT CONFIG — settings.json schema and its migrations
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Historical formats:
+//   v0  unversioned; fixed snap-hook macro (part1Key, safePocketX, delayMs, …)
+//   v1  unversioned; `macro_config.macros` step lists
+//   v2  `version` field added
+//   v3  named profiles; v2's `clicker`/`macro_config` become the "Default" one
+//   v4  keys and click modes are checked; values v3 silently ignored are
+//       unbound or removed
+//
+// Adding a field with a serde default needs no new version. Renaming,
+// removing or reshaping one does: bump CURRENT_VERSION, add a step to
+// MIGRATIONS and a golden file under tests/fixtures/config. Fixtures of
+// older versions are what those versions wrote, so they are never edited;
+// new cases get new fixtures. Migrations build plain JSON of the version
+// they produce, never today's types.
+
+pub const CURRENT_VERSION: u32 = 4;
+
+#[derive(Serialize, Deserialize)]
+pub struct PersistentConfig {
+    pub version: u32,
+    #[serde(flatten)]
+    pub profiles: ProfileStore,
+}
+
+impl PersistentConfig {
+    pub fn new(profiles: ProfileStore) -> PersistentConfig {
+        PersistentConfig { version: CURRENT_VERSION, profiles }
+    }
+}
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4];
+
+fn detect_version(doc: &Value) -> Result<u32, String> {
+    match doc.get("version") {
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("invalid version {}", v)),
+        None if doc.pointer("/macro_config/macros").is_some() => Ok(1),
+        None => Ok(0),
+    }
+}
+
+/// Parse settings.json of any known version, upgrading it to the current one
+pub fn from_json(text: &str) -> Result<PersistentConfig, String> {
+    let mut doc: Value = serde_json::from_str(text).map_err(|e| format!("not valid JSON: {}", e))?;
+    let version = detect_version(&doc)?;
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "settings were written by a newer version (format {}, this build reads up to {})",
+            version, CURRENT_VERSION
+        ));
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        doc = migrate(doc).map_err(|e| format!("upgrading from format {}: {}", from, e))?;
+    }
+    let mut config: PersistentConfig =
+        serde_json::from_value(doc).map_err(|e| format!("format {}: {}", CURRENT_VERSION, e))?;
+    config.profiles.normalize();
+    Ok(config)
+}
+
+pub fn to_json(config: &PersistentConfig) -> String {
+    serde_json::to_string_pretty(config).expect("config is always serializable")
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SAVE / LOAD — atomic writes with a rolling backup
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// A save writes `settings.json.tmp`, flushes it to disk and renames it over
+// `settings.json`, so a crash leaves either the old or the new file, never a
+// half-written one. Before that, the current file is copied to
+// `settings.json.bak` if it still loads, so the backup is always good.
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+pub fn save(path: &Path, config: &PersistentConfig) -> Result<(), String> {
+    let fail = |what: &str, e: std::io::Error| format!("could not {} {}: {}", what, path.display(), e);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| fail("create the folder for", e))?;
+    }
+
+    let current_is_good = fs::read_to_string(path).is_ok_and(|text| from_json(&text).is_ok());
+    if current_is_good {
+        fs::copy(path, backup_path(path)).map_err(|e| fail("back up", e))?;
+    }
+
+    let tmp = sibling(path, ".tmp");
+    let mut file = File::create(&tmp).map_err(|e| fail("write", e))?;
+    file.write_all(to_json(config).as_bytes()).map_err(|e| fail("write", e))?;
+    file.sync_all().map_err(|e| fail("write", e))?;
+    drop(file);
+    fs::rename(&tmp, path).map_err(|e| fail("replace", e))
+}
+
+pub enum LoadOutcome {
+    /// No settings file yet
+    Missing,
+    Loaded(PersistentConfig),
+    /// The settings file was unreadable; the backup was used instead
+    Recovered { config: PersistentConfig, error: String },
+    /// Neither the settings file nor the backup could be used
+    Failed(String),
+}
+
+/// Load settings, falling back to the backup if the main file is damaged.
+/// A damaged file is kept as `settings.json.unreadable` for inspection.
+pub fn load(path: &Path) -> LoadOutcome {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return LoadOutcome::Missing,
+        Err(e) => return LoadOutcome::Failed(format!("could not read {}: {}", path.display(), e)),
+    };
+    let error = match from_json(&text) {
+        Ok(config) => return LoadOutcome::Loaded(config),
+        Err(e) => e,
+    };
+
+    let _ = fs::copy(path, sibling(path, ".unreadable"));
+    match fs::read_to_string(backup_path(path)).map_err(|e| e.to_string()).and_then(|t| from_json(&t)) {
+        Ok(config) => LoadOutcome::Recovered { config, error },
+        Err(backup_error) => LoadOutcome::Failed(format!("{} (backup: {})", error, backup_error)),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MIGRATIONS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// The fixed "Part 1" / "Part 2" snap-hook macros become editable step-list
+/// macros with the same keys, positions and timing. Written as v1 JSON, not
+/// with today's types, so later changes to those cannot alter this step.
+fn v0_to_v1(mut doc: Value) -> Result<Value, String> {
+    let old = doc.get("macro_config").cloned().ok_or("missing macro_config")?;
+    if !old.is_object() {
+        return Err("macro_config is not a JSON object".into());
+    }
+    let text = |name: &str, default: &str| -> String {
+        old.get(name).and_then(Value::as_str).unwrap_or(default).to_string()
+    };
+    let int = |name: &str| -> i32 {
+        old.get(name).and_then(Value::as_i64).and_then(|v| i32::try_from(v).ok()).unwrap_or(0)
+    };
+    let delay = old.get("delayMs").and_then(Value::as_u64).unwrap_or(50);
+    let safe_pocket = json!([int("safePocketX"), int("safePocketY")]);
+    let quick_use = json!([int("quickUseX"), int("quickUseY")]);
+
+    let tap = |key: &str| json!({ "type": "keyTap", "key": key, "holdMs": 30 });
+    let wait = |ms: u64| json!({ "type": "wait", "ms": ms });
+    let drag = |from: &Value, to: &Value| json!({ "type": "drag", "from": from, "to": to });
+    let part1 = json!([
+        tap("Tab"), wait(delay),
+        drag(&safe_pocket, &quick_use), wait(delay),
+        tap("Tab"), wait(delay),
+        { "type": "keyDown", "key": "KeyQ" }, wait(300),
+        tap("Digit6"), wait(50),
+        { "type": "keyUp", "key": "KeyQ" },
+    ]);
+    // An unknown dodge key is removed, with a note, by the v3 → v4 step
+    let part2 = json!([
+        tap("Digit1"), wait(delay),
+        tap(&text("dodgeKey", "AltLeft")),
+        wait(delay.max(150)),
+        tap("Tab"), wait(delay.max(100)),
+        drag(&quick_use, &safe_pocket), wait(delay),
+        tap("Tab"),
+    ]);
+
+    // The old macros refused to run until both positions were captured;
+    // unbinding them keeps them from dragging to (0, 0)
+    let positions_set = safe_pocket != json!([0, 0]) && quick_use != json!([0, 0]);
+    let hotkey = |name: &str, default: &str| if positions_set { text(name, default) } else { String::new() };
+
+    doc["macro_config"] = json!({
+        "macros": [
+            { "name": "Part 1: Safe Pocket → Quick Use", "hotkey": hotkey("part1Key", "F7"), "steps": part1 },
+            { "name": "Part 2: Quick Use → Safe Pocket", "hotkey": hotkey("part2Key", "F8"), "steps": part2 },
+        ]
+    });
+    Ok(doc)
+}
+
+fn v1_to_v2(mut doc: Value) -> Result<Value, String> {
+    doc.as_object_mut().ok_or("not a JSON object")?.insert("version".into(), json!(2));
+    Ok(doc)
+}
+
+/// The single clicker/macro configuration becomes the only profile
+fn v2_to_v3(mut doc: Value) -> Result<Value, String> {
+    let obj = doc.as_object_mut().ok_or("not a JSON object")?;
+    let clicker = obj.remove("clicker").ok_or("missing clicker")?;
+    let macro_config = obj.remove("macro_config").ok_or("missing macro_config")?;
+    obj.insert("version".into(), json!(3));
+    obj.insert("active_profile".into(), json!(DEFAULT_PROFILE));
+    obj.insert("profile_cycle_key".into(), json!(""));
+    obj.insert(
+        "profiles".into(),
+        json!([{ "name": DEFAULT_PROFILE, "clicker": clicker, "macro_config": macro_config }]),
+    );
+    Ok(doc)
+}
+
+/// Keys and click modes used to be free strings; unknown ones did nothing.
+/// They are now unbound, reset or removed so the typed settings load.
+fn v3_to_v4(mut doc: Value) -> Result<Value, String> {
+    let obj = doc.as_object_mut().ok_or("not a JSON object")?;
+    let mut notes = Vec::new();
+    if let Some(key) = obj.get_mut("profile_cycle_key") {
+        fix_hotkey(key, "next profile key", &mut notes, &V4);
+    }
+    for profile in obj.get_mut("profiles").and_then(Value::as_array_mut).into_iter().flatten() {
+        let name = profile.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+        notes.extend(sanitize_profile(profile, &V4).into_iter().map(|n| format!("profile '{}': {}", name, n)));
+    }
+    for note in notes {
+        println!("Settings upgrade: {}", note);
+    }
+    obj.insert("version".into(), json!(4));
+    Ok(doc)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SANITIZING — values a format cannot use, in untyped documents
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Shared by the v3 → v4 upgrade, which checks against format 4 as it was
+// introduced, and bundle imports, which check against this build. Values of
+// the wrong JSON type are left alone for serde to report.
+
+/// What keys, hotkeys and click modes a document may use
+pub struct Rules {
+    key: fn(&str) -> Result<(), String>,
+    /// Checks a whole hotkey setting; "" is unbound and always allowed
+    hotkey: fn(&str) -> Result<(), String>,
+    click_mode: fn(&str) -> bool,
+}
+
+/// This build's keys, chords and click modes
+pub const CURRENT: Rules = Rules {
+    key: |name| KeyId::parse(name).map(|_| ()),
+    hotkey: |text| hotkeys::parse_binding(text).map(|_| ()),
+    click_mode: |mode| serde_json::from_value::<ClickMode>(json!(mode)).is_ok(),
+};
+
+/// Format 4 as the v3 upgrade first wrote it: hotkeys are single keys from
+/// a fixed list. Spelled out so later changes to the key table or the
+/// hotkey syntax cannot change what old files upgrade to.
+const V4: Rules = Rules { key: v4_key, hotkey: v4_hotkey, click_mode: |mode| V4_CLICK_MODES.contains(&mode) };
+
+const V4_KEYS: &[&str] = &[
+    "KeyA", "KeyB", "KeyC", "KeyD", "KeyE", "KeyF", "KeyG", "KeyH", "KeyI", "KeyJ", "KeyK", "KeyL",
+    "KeyM", "KeyN", "KeyO", "KeyP", "KeyQ", "KeyR", "KeyS", "KeyT", "KeyU", "KeyV", "KeyW", "KeyX",
+    "KeyY", "KeyZ", "Digit0", "Digit1", "Digit2", "Digit3", "Digit4", "Digit5", "Digit6", "Digit7",
+    "Digit8", "Digit9", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12",
+    "ShiftLeft", "ShiftRight", "ControlLeft", "ControlRight", "AltLeft", "AltRight", "MetaLeft",
+    "MetaRight", "Space", "Enter", "Escape", "Backspace", "Tab", "CapsLock", "Insert", "Delete",
+    "Home", "End", "PageUp", "PageDown", "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight",
+    "Numpad0", "Numpad1", "Numpad2", "Numpad3", "Numpad4", "Numpad5", "Numpad6", "Numpad7",
+    "Numpad8", "Numpad9", "NumpadMultiply", "NumpadAdd", "NumpadSubtract", "NumpadDecimal",
+    "NumpadDivide", "NumpadEnter", "NumLock", "Backquote", "Minus", "Equal", "BracketLeft",
+    "BracketRight", "Backslash", "Semicolon", "Quote", "Comma", "Period", "Slash", "PrintScreen",
+    "ScrollLock", "Pause", "ContextMenu", "AudioVolumeMute", "AudioVolumeDown", "AudioVolumeUp",
+    "MediaTrackNext", "MediaTrackPrevious", "MediaStop", "MediaPlayPause", "Mouse3", "Mouse4",
+    "Mouse5",
+];
+
+/// Keys of `V4_KEYS` that cannot be hotkeys
+const V4_UNDETECTABLE: &[&str] = &[
+    "NumLock", "PrintScreen", "ScrollLock", "Pause", "ContextMenu", "AudioVolumeMute",
+    "AudioVolumeDown", "AudioVolumeUp", "MediaTrackNext", "MediaTrackPrevious", "MediaStop",
+    "MediaPlayPause",
+];
+
+const V4_CLICK_MODES: &[&str] = &["left", "right", "double"];
+
+fn v4_key(name: &str) -> Result<(), String> {
+    if !V4_KEYS.contains(&name) {
+        return Err(format!("unknown key '{}'", name));
+    }
+    Ok(())
+}
+
+fn v4_hotkey(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Ok(());
+    }
+    v4_key(name)?;
+    if V4_UNDETECTABLE.contains(&name) {
+        return Err(format!("key '{}' cannot be detected as a hotkey", name));
+    }
+    Ok(())
+}
+
+/// Unbind an unknown hotkey
+fn fix_hotkey(key: &mut Value, what: &str, notes: &mut Vec<String>, rules: &Rules) {
+    if let Some(name) = key.as_str() {
+        if let Err(e) = (rules.hotkey)(name) {
+            notes.push(format!("{}: {}, left unbound", what, e));
+            *key = json!("");
+        }
+    }
+}
+
+/// Remove steps, also inside repeats, that use a key `rules` do not know,
+/// returning where each one was
+pub fn remove_unknown_key_steps(steps: &mut Value, rules: &Rules) -> Vec<String> {
+    let mut removed = Vec::new();
+    let Some(list) = steps.as_array_mut() else { return removed };
+    let mut n = 0;
+    list.retain_mut(|step| {
+        n += 1;
+        if let Some(nested) = step.get_mut("steps") {
+            removed.extend(remove_unknown_key_steps(nested, rules).into_iter().map(|r| format!("step {}: {}", n, r)));
+        }
+        match step.get("key").and_then(Value::as_str).map(rules.key) {
+            Some(Err(e)) => {
+                removed.push(format!("step {}: {}", n, e));
+                false
+            }
+            _ => true,
+        }
+    });
+    removed
+}
+
+/// Unbind a macro's unknown hotkey and remove its unknown-key steps
+pub fn sanitize_macro(m: &mut Value, rules: &Rules) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(key) = m.get_mut("hotkey") {
+        fix_hotkey(key, "hotkey", &mut notes, rules);
+    }
+    if let Some(steps) = m.get_mut("steps") {
+        notes.extend(remove_unknown_key_steps(steps, rules).into_iter().map(|r| format!("{}, step removed", r)));
+    }
+    notes
+}
+
+/// Make a profile document loadable: unknown click modes become "left",
+/// unknown hotkeys are unbound and steps with unknown keys are removed
+pub fn sanitize_profile(profile: &mut Value, rules: &Rules) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(clicker) = profile.get_mut("clicker") {
+        if let Some(key) = clicker.get_mut("toggle_key") {
+            fix_hotkey(key, "toggle key", &mut notes, rules);
+        }
+        if let Some(mode) = clicker.get_mut("click_mode") {
+            if let Some(name) = mode.as_str().filter(|&m| !(rules.click_mode)(m)) {
+                notes.push(format!("unknown click mode '{}', using left", name));
+                *mode = json!("left");
+            }
+        }
+    }
+    let Some(config) = profile.get_mut("macro_config") else { return notes };
+    for (field, what) in [("recordKey", "record key"), ("panicKey", "panic key")] {
+        if let Some(key) = config.get_mut(field) {
+            fix_hotkey(key, what, &mut notes, rules);
+        }
+    }
+    for m in config.get_mut("macros").and_then(Value::as_array_mut).into_iter().flatten() {
+        let name = m.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+        notes.extend(sanitize_macro(m, rules).into_iter().map(|n| format!("macro '{}': {}", name, n)));
+    }
+    for binding in config.get_mut("scripts").and_then(Value::as_array_mut).into_iter().flatten() {
+        let file = binding.get("file").and_then(Value::as_str).unwrap_or_default().to_string();
+        if let Some(key) = binding.get_mut("hotkey") {
+            let mut fixed = Vec::new();
+            fix_hotkey(key, "hotkey", &mut fixed, rules);
+            notes.extend(fixed.into_iter().map(|n| format!("script '{}': {}", file, n)));
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clicker::ClickerState;
+    use crate::macros::MacroStep;
+
+    /// Load a fixture and compare the upgraded config with its golden file
+    fn check_golden(input: &str, golden: &str) {
+        let upgraded = to_json(&from_json(input).unwrap());
+        assert_eq!(upgraded.trim(), golden.trim());
+    }
+
+    #[test]
+    fn upgrades_v0_snap_hook_config() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v0.json"),
+            include_str!("../tests/fixtures/config/v0.golden.json"),
+        );
+    }
+
+    #[test]
+    fn upgrades_v0_without_positions_to_unbound_macros() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v0_no_positions.json"),
+            include_str!("../tests/fixtures/config/v0_no_positions.golden.json"),
+        );
+    }
+
+    #[test]
+    fn upgrades_v1_step_list_config() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v1.json"),
+            include_str!("../tests/fixtures/config/v1.golden.json"),
+        );
+    }
+
+    #[test]
+    fn upgrades_v2_to_a_single_default_profile() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v2.json"),
+            include_str!("../tests/fixtures/config/v2.golden.json"),
+        );
+    }
+
+    #[test]
+    fn upgrades_v3_profiles() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v3.json"),
+            include_str!("../tests/fixtures/config/v3.golden.json"),
+        );
+    }
+
+    #[test]
+    fn upgrades_v3_with_unknown_keys_and_click_mode() {
+        check_golden(
+            include_str!("../tests/fixtures/config/v3_unknown_keys.json"),
+            include_str!("../tests/fixtures/config/v3_unknown_keys.golden.json"),
+        );
+    }
+
+    #[test]
+    fn v3_hotkeys_are_single_keys() {
+        // Chords came with format 4; in a v3 file this was an unknown key
+        let text = include_str!("../tests/fixtures/config/v3.json").replace("\"F8\"", "\"Ctrl+F8\"");
+        let cfg = from_json(&text).unwrap();
+        assert_eq!(cfg.profiles.cycle_key, None);
+    }
+
+    #[test]
+    fn current_format_round_trips() {
+        let current = include_str!("../tests/fixtures/config/v4.json");
+        check_golden(current, current);
+    }
+
+    #[test]
+    fn current_format_rejects_unknown_keys() {
+        let text = include_str!("../tests/fixtures/config/v4.json").replace("\"F8\"", "\"Hyper7\"");
+        assert!(load_error(&text).contains("unknown key 'Hyper7'"));
+    }
+
+    #[test]
+    fn migrated_v0_macro_matches_the_old_part1_sequence() {
+        let cfg = from_json(include_str!("../tests/fixtures/config/v0.json")).unwrap();
+        let part1 = &cfg.profiles.active().macro_config.macros[0];
+        assert_eq!(hotkeys::binding_name(part1.hotkey), "F7");
+        assert_eq!(part1.steps[2], MacroStep::Drag { from: (812, 640), to: (1210, 955) });
+        assert_eq!(part1.steps[1], MacroStep::Wait { ms: 80 });
+    }
+
+    /// Fresh directory under the system temp dir for one test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cliky-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn sample(cps: u64) -> PersistentConfig {
+        let mut cfg = from_json(include_str!("../tests/fixtures/config/v4.json")).unwrap();
+        let active = cfg.profiles.active().clone();
+        let clicker = ClickerState { cps, ..active.clicker };
+        cfg.profiles.store_active(&clicker, &active.macro_config);
+        cfg
+    }
+
+    fn cps(cfg: &PersistentConfig) -> u64 {
+        cfg.profiles.active().clicker.cps
+    }
+
+    fn loaded_cps(path: &Path) -> Result<u64, String> {
+        match load(path) {
+            LoadOutcome::Loaded(cfg) => Ok(cps(&cfg)),
+            LoadOutcome::Recovered { config, .. } => Err(format!("recovered {}", cps(&config))),
+            LoadOutcome::Missing => Err("missing".into()),
+            LoadOutcome::Failed(e) => Err(e),
+        }
+    }
+
+    #[test]
+    fn save_replaces_file_and_keeps_previous_as_backup() {
+        let path = temp_dir("save").join("settings.json");
+        assert_eq!(loaded_cps(&path), Err("missing".into()));
+
+        save(&path, &sample(11)).unwrap();
+        save(&path, &sample(12)).unwrap();
+
+        assert_eq!(loaded_cps(&path), Ok(12));
+        assert_eq!(loaded_cps(&backup_path(&path)), Ok(11));
+        assert!(!sibling(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn damaged_file_is_recovered_from_backup() {
+        let path = temp_dir("recover").join("settings.json");
+        save(&path, &sample(11)).unwrap();
+        save(&path, &sample(12)).unwrap();
+        // Simulate a torn write from an older build
+        fs::write(&path, "{\"version\": 2, \"clicker\": {\"cps\": 1").unwrap();
+
+        assert_eq!(loaded_cps(&path), Err("recovered 11".into()));
+        assert!(sibling(&path, ".unreadable").exists());
+
+        // Saving over a damaged file must not replace the good backup
+        save(&path, &sample(13)).unwrap();
+        assert_eq!(loaded_cps(&backup_path(&path)), Ok(11));
+        assert_eq!(loaded_cps(&path), Ok(13));
+    }
+
+    #[test]
+    fn damaged_file_without_backup_fails_with_reason() {
+        let path = temp_dir("failed").join("settings.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "not json").unwrap();
+
+        let err = loaded_cps(&path).unwrap_err();
+        assert!(err.contains("not valid JSON") && err.contains("backup"), "{}", err);
+    }
+
+    #[test]
+    fn save_reports_errors() {
+        // A directory where the file should be cannot be replaced
+        let path = temp_dir("error").join("settings.json");
+        fs::create_dir_all(&path).unwrap();
+        assert!(save(&path, &sample(10)).is_err());
+    }
+
+    fn load_error(text: &str) -> String {
+        match from_json(text) {
+            Ok(_) => panic!("expected {} to be rejected", text),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn rejects_newer_and_broken_files() {
+        assert!(load_error(r#"{"version": 99}"#).contains("newer version"));
+        assert!(load_error("{").contains("not valid JSON"));
+        assert!(load_error(r#"{"version": "two"}"#).contains("invalid version"));
+        assert!(load_error(r#"{"clicker": {}}"#).contains("missing macro_config"));
+    }
+}
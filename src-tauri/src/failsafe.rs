@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// FAIL-SAFE — slam the cursor into a screen corner to stop everything
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+fn default_margin() -> i32 {
+    2
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailsafeConfig {
+    pub enabled: bool,
+    pub corner: Corner,
+    /// How close to the corner (in pixels) counts as "in" it
+    #[serde(default = "default_margin")]
+    pub margin: i32,
+}
+
+impl Default for FailsafeConfig {
+    fn default() -> FailsafeConfig {
+        FailsafeConfig { enabled: true, corner: Corner::TopLeft, margin: default_margin() }
+    }
+}
+
+/// Screen rectangle in physical pixels; `right`/`bottom` are exclusive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+pub fn in_corner(pos: (i32, i32), bounds: Bounds, corner: Corner, margin: i32) -> bool {
+    let near_left = pos.0 <= bounds.left + margin;
+    let near_right = pos.0 >= bounds.right - 1 - margin;
+    let near_top = pos.1 <= bounds.top + margin;
+    let near_bottom = pos.1 >= bounds.bottom - 1 - margin;
+    match corner {
+        Corner::TopLeft => near_left && near_top,
+        Corner::TopRight => near_right && near_top,
+        Corner::BottomLeft => near_left && near_bottom,
+        Corner::BottomRight => near_right && near_bottom,
+    }
+}
+
+/// Edge-triggered corner detector: fires once when the cursor enters the
+/// corner, and again only after it has left. A cursor our own backend put
+/// in the corner, like a macro moving to (0, 0), does not fire.
+#[derive(Default)]
+pub struct FailsafeWatcher {
+    in_corner: bool,
+}
+
+impl FailsafeWatcher {
+    pub fn new() -> FailsafeWatcher {
+        FailsafeWatcher::default()
+    }
+
+    /// Returns true when the fail-safe should trip for this cursor sample.
+    /// `ours` is whether the backend moved the cursor there.
+    pub fn check(&mut self, pos: (i32, i32), ours: bool, bounds: Bounds, config: &FailsafeConfig) -> bool {
+        let now = config.enabled && in_corner(pos, bounds, config.corner, config.margin);
+        let tripped = now && !self.in_corner && !ours;
+        self.in_corner = now;
+        tripped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Bounds = Bounds { left: 0, top: 0, right: 1920, bottom: 1080 };
+
+    fn config(corner: Corner) -> FailsafeConfig {
+        FailsafeConfig { enabled: true, corner, margin: 2 }
+    }
+
+    #[test]
+    fn detects_each_corner_within_margin() {
+        assert!(in_corner((0, 0), SCREEN, Corner::TopLeft, 2));
+        assert!(in_corner((2, 1), SCREEN, Corner::TopLeft, 2));
+        assert!(!in_corner((3, 0), SCREEN, Corner::TopLeft, 2));
+        assert!(in_corner((1919, 0), SCREEN, Corner::TopRight, 0));
+        assert!(!in_corner((1918, 0), SCREEN, Corner::TopRight, 0));
+        assert!(in_corner((0, 1079), SCREEN, Corner::BottomLeft, 0));
+        assert!(in_corner((1917, 1077), SCREEN, Corner::BottomRight, 2));
+        assert!(!in_corner((0, 0), SCREEN, Corner::BottomRight, 2));
+    }
+
+    #[test]
+    fn handles_screens_not_at_the_origin() {
+        let left_monitor = Bounds { left: -1280, top: -200, right: 0, bottom: 824 };
+        assert!(in_corner((-1280, -200), left_monitor, Corner::TopLeft, 0));
+        assert!(in_corner((-1, 823), left_monitor, Corner::BottomRight, 0));
+        assert!(!in_corner((0, 0), left_monitor, Corner::TopLeft, 2));
+    }
+
+    #[test]
+    fn trips_once_per_entry() {
+        let mut watcher = FailsafeWatcher::new();
+        let cfg = config(Corner::TopLeft);
+        assert!(!watcher.check((500, 500), false, SCREEN, &cfg));
+        assert!(watcher.check((0, 0), false, SCREEN, &cfg));
+        assert!(!watcher.check((1, 0), false, SCREEN, &cfg));
+        assert!(!watcher.check((400, 300), false, SCREEN, &cfg));
+        assert!(watcher.check((0, 1), false, SCREEN, &cfg));
+    }
+
+    #[test]
+    fn ignores_the_cursor_a_macro_moved() {
+        use crate::input::mock::MockBackend;
+        use crate::input::{Injected, InputBackend, TrackedBackend};
+        use crate::macros::{run_macro, Macro, MacroStep};
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let injected = Arc::new(Injected::default());
+        let backend: Arc<dyn InputBackend> =
+            Arc::new(TrackedBackend::new(Arc::new(MockBackend::new()), injected.clone()));
+        let m = Macro {
+            name: "Park".into(),
+            hotkey: None,
+            trigger: Default::default(),
+            steps: vec![MacroStep::Move { x: 0, y: 0, relative: false }],
+        };
+        run_macro(backend, &m, &crate::cancel::CancelToken::new());
+
+        let mut watcher = FailsafeWatcher::new();
+        let cfg = config(Corner::TopLeft);
+        let mut sample = |pos, at| {
+            let ours = injected.cursor_is_ours(pos, at);
+            watcher.check(pos, ours, SCREEN, &cfg)
+        };
+        let now = Instant::now();
+        assert!(!sample((0, 0), now));
+        // The user takes the cursor away and slams it back
+        let later = now + Duration::from_secs(1);
+        assert!(!sample((700, 400), later));
+        assert!(sample((0, 0), later));
+    }
+
+    #[test]
+    fn disabled_never_trips() {
+        let mut watcher = FailsafeWatcher::new();
+        let cfg = FailsafeConfig { enabled: false, ..config(Corner::TopLeft) };
+        assert!(!watcher.check((0, 0), false, SCREEN, &cfg));
+    }
+
+    #[test]
+    fn config_defaults_margin() {
+        let cfg: FailsafeConfig = serde_json::from_str(r#"{"enabled": true, "corner": "bottomRight"}"#).unwrap();
+        assert_eq!(cfg, FailsafeConfig { enabled: true, corner: Corner::BottomRight, margin: 2 });
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_os = "linux")]
+mod x11;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// FOCUS — which window is in front, and which profile it asks for
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowInfo {
+    /// WM_CLASS class part on X11, e.g. "firefox"
+    pub class: String,
+    pub title: String,
+}
+
+/// Source of the currently focused window
+pub trait FocusSource: Send {
+    fn focused_window(&self) -> Option<WindowInfo>;
+}
+
+/// The platform's focus source, if there is one
+pub fn default_source() -> Option<Box<dyn FocusSource>> {
+    #[cfg(target_os = "linux")]
+    {
+        match x11::X11Focus::open(None) {
+            Ok(source) => Some(Box::new(source)),
+            Err(e) => {
+                println!("Focus: X11 unavailable ({}), automatic profiles are disabled", e);
+                None
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        println!("Focus: not supported on this platform, automatic profiles are disabled");
+        None
+    }
+}
+
+/// Activate `profile` when the focused window's class and title contain the
+/// given text (case-insensitive). An empty field matches anything, but a
+/// rule needs at least one of them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoProfileRule {
+    pub profile: String,
+    #[serde(default)]
+    pub class: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+impl AutoProfileRule {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        let contains = |hay: &str, needle: &str| hay.to_lowercase().contains(&needle.to_lowercase());
+        (!self.class.is_empty() || !self.title.is_empty())
+            && contains(&window.class, &self.class)
+            && contains(&window.title, &self.title)
+    }
+}
+
+/// Picks a profile when focus moves to another window. Only a change of
+/// window triggers a switch, so a profile chosen by hand sticks until the
+/// user switches windows.
+#[derive(Default)]
+pub struct AutoSwitcher {
+    last: Option<WindowInfo>,
+}
+
+impl AutoSwitcher {
+    pub fn new() -> AutoSwitcher {
+        AutoSwitcher::default()
+    }
+
+    /// Profile to activate for this focus sample, first matching rule wins
+    pub fn check(&mut self, window: Option<WindowInfo>, rules: &[AutoProfileRule]) -> Option<String> {
+        if window == self.last {
+            return None;
+        }
+        self.last = window;
+        let window = self.last.as_ref()?;
+        rules.iter().find(|r| r.matches(window)).map(|r| r.profile.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Focus source that replays a fixed sequence of windows
+    struct ScriptedFocus(Mutex<Vec<Option<WindowInfo>>>);
+
+    impl FocusSource for ScriptedFocus {
+        fn focused_window(&self) -> Option<WindowInfo> {
+            self.0.lock().unwrap().remove(0)
+        }
+    }
+
+    fn window(class: &str, title: &str) -> Option<WindowInfo> {
+        Some(WindowInfo { class: class.into(), title: title.into() })
+    }
+
+    fn rule(profile: &str, class: &str, title: &str) -> AutoProfileRule {
+        AutoProfileRule { profile: profile.into(), class: class.into(), title: title.into() }
+    }
+
+    #[test]
+    fn rules_match_class_and_title_case_insensitively() {
+        let game = window("steam_app_730", "Counter-Strike 2").unwrap();
+        assert!(rule("CS", "STEAM_APP", "").matches(&game));
+        assert!(rule("CS", "", "counter-strike").matches(&game));
+        assert!(rule("CS", "steam_app_730", "Counter").matches(&game));
+        assert!(!rule("CS", "steam_app_730", "Dota").matches(&game));
+        assert!(!rule("Empty", "", "").matches(&game));
+    }
+
+    #[test]
+    fn switches_only_when_focus_changes() {
+        let rules = [rule("Browser", "firefox", ""), rule("Game", "", "Minecraft"), rule("Any", "", "a")];
+        let focus = ScriptedFocus(Mutex::new(vec![
+            window("firefox", "Docs"),
+            window("firefox", "Docs"),
+            window("java", "Minecraft 1.21"),
+            None,
+            window("xterm", "bash"),
+            window("firefox", "Docs"),
+        ]));
+        let mut switcher = AutoSwitcher::new();
+        let picks: Vec<Option<String>> = (0..6).map(|_| switcher.check(focus.focused_window(), &rules)).collect();
+        assert_eq!(
+            picks,
+            vec![Some("Browser".into()), None, Some("Game".into()), None, Some("Any".into()), Some("Browser".into())]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Focused-window lookup on X11 through the EWMH `_NET_ACTIVE_WINDOW`
+//! property that window managers keep on the root window.
+//!
+//! Xlib is loaded at runtime, like the XTEST input backend, so the binary
+//! still starts without it.
+
+use super::{FocusSource, WindowInfo};
+use crate::x11::Connection;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uchar, c_ulong};
+use std::ptr;
+use std::sync::Mutex;
+use x11_dl::xlib::{self, Display, Xlib};
+
+pub struct X11Focus {
+    /// Before `xlib`, so it is closed while Xlib is still loaded
+    conn: Mutex<Connection>,
+    xlib: Xlib,
+    net_active_window: c_ulong,
+    net_wm_name: c_ulong,
+    utf8_string: c_ulong,
+}
+
+/// The active window can be destroyed between reading its id and querying
+/// it. Xlib's default handler exits the process on the resulting BadWindow,
+/// so errors are ignored and show up as failed queries instead.
+unsafe extern "C" fn ignore_errors(_display: *mut Display, _event: *mut xlib::XErrorEvent) -> c_int {
+    0
+}
+
+impl X11Focus {
+    /// Connect to `display`, or to `$DISPLAY` when `None`.
+    pub fn open(display: Option<&str>) -> Result<X11Focus, String> {
+        let xlib = Xlib::open().map_err(|e| e.to_string())?;
+        let conn = Connection::open(&xlib, display)?;
+        let display = conn.display;
+        let atom = |name: &CStr| unsafe { (xlib.XInternAtom)(display, name.as_ptr(), xlib::False) };
+        let net_active_window = atom(c"_NET_ACTIVE_WINDOW");
+        let net_wm_name = atom(c"_NET_WM_NAME");
+        let utf8_string = atom(c"UTF8_STRING");
+        unsafe { (xlib.XSetErrorHandler)(Some(ignore_errors)) };
+        Ok(X11Focus { conn: Mutex::new(conn), xlib, net_active_window, net_wm_name, utf8_string })
+    }
+
+    /// Read a whole window property, or `None` if it is missing or of
+    /// another type.
+    unsafe fn property(&self, display: *mut Display, window: c_ulong, property: c_ulong, kind: c_ulong) -> Option<(Vec<u8>, c_int)> {
+        let (mut actual_type, mut format, mut items, mut after) = (0, 0, 0, 0);
+        let mut data: *mut c_uchar = ptr::null_mut();
+        let status = (self.xlib.XGetWindowProperty)(
+            display, window, property, 0, 1024, xlib::False, kind,
+            &mut actual_type, &mut format, &mut items, &mut after, &mut data,
+        );
+        if status != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+        // 32-bit items are stored as C longs
+        let item_size = match format {
+            8 => 1,
+            16 => 2,
+            32 => std::mem::size_of::<c_ulong>(),
+            _ => 0,
+        };
+        let bytes = std::slice::from_raw_parts(data, items as usize * item_size).to_vec();
+        (self.xlib.XFree)(data.cast());
+        (actual_type == kind && item_size > 0).then_some((bytes, format))
+    }
+
+    unsafe fn active_window(&self, display: *mut Display) -> Option<c_ulong> {
+        let root = (self.xlib.XDefaultRootWindow)(display);
+        let (bytes, _) = self.property(display, root, self.net_active_window, xlib::XA_WINDOW)?;
+        let id = c_ulong::from_ne_bytes(bytes.get(..std::mem::size_of::<c_ulong>())?.try_into().ok()?);
+        (id != 0).then_some(id)
+    }
+
+    unsafe fn class(&self, display: *mut Display, window: c_ulong) -> String {
+        let mut hint = xlib::XClassHint { res_name: ptr::null_mut(), res_class: ptr::null_mut() };
+        if (self.xlib.XGetClassHint)(display, window, &mut hint) == 0 {
+            return String::new();
+        }
+        let class = c_string(hint.res_class);
+        for p in [hint.res_name, hint.res_class] {
+            if !p.is_null() {
+                (self.xlib.XFree)(p.cast());
+            }
+        }
+        class
+    }
+
+    unsafe fn title(&self, display: *mut Display, window: c_ulong) -> String {
+        if let Some((bytes, 8)) = self.property(display, window, self.net_wm_name, self.utf8_string) {
+            return String::from_utf8_lossy(&bytes).into_owned();
+        }
+        // Older clients only set the Latin-1 WM_NAME
+        let mut name: *mut c_char = ptr::null_mut();
+        if (self.xlib.XFetchName)(display, window, &mut name) == 0 || name.is_null() {
+            return String::new();
+        }
+        let title = c_string(name);
+        (self.xlib.XFree)(name.cast());
+        title
+    }
+}
+
+unsafe fn c_string(p: *const c_char) -> String {
+    if p.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(p).to_string_lossy().into_owned()
+}
+
+impl FocusSource for X11Focus {
+    fn focused_window(&self) -> Option<WindowInfo> {
+        let conn = self.conn.lock().unwrap();
+        unsafe {
+            let window = self.active_window(conn.display)?;
+            Some(WindowInfo { class: self.class(conn.display, window), title: self.title(conn.display, window) })
+        }
+    }
+}
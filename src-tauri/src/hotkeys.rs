@@ -0,0 +1,202 @@
+use crate::keys::{self, KeyId};
+use std::fmt;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// HOTKEYS — a key or mouse button plus the modifiers held with it
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Written "Ctrl+Shift+F6": modifiers (printed in this order), then one key
+// name from the key table. Either side's modifier key counts, so "Ctrl" is ControlLeft
+// or ControlRight.
+
+/// Modifier groups in display order, with the keys of each
+const MODIFIERS: [(&str, [&str; 2]); 4] = [
+    ("Ctrl", ["ControlLeft", "ControlRight"]),
+    ("Shift", ["ShiftLeft", "ShiftRight"]),
+    ("Alt", ["AltLeft", "AltRight"]),
+    ("Meta", ["MetaLeft", "MetaRight"]),
+];
+
+fn modifier_group(key: KeyId) -> Option<usize> {
+    MODIFIERS.iter().position(|(_, names)| names.contains(&key.name()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    /// Held modifier groups, indexed like `MODIFIERS`
+    modifiers: [bool; 4],
+    pub key: KeyId,
+}
+
+impl Hotkey {
+    pub fn parse(text: &str) -> Result<Hotkey, String> {
+        let (prefix, name) = match text.rsplit_once('+') {
+            Some((prefix, name)) => (Some(prefix), name),
+            None => (None, text),
+        };
+        let key = keys::parse_hotkey(name)?.ok_or_else(|| format!("'{}' has no key after the modifiers", text))?;
+        let mut modifiers = [false; 4];
+        for token in prefix.into_iter().flat_map(|p| p.split('+')) {
+            let group = MODIFIERS
+                .iter()
+                .position(|(name, _)| *name == token)
+                .ok_or_else(|| format!("unknown modifier '{}' in '{}'", token, text))?;
+            if modifiers[group] {
+                return Err(format!("modifier '{}' repeated in '{}'", token, text));
+            }
+            if modifier_group(key) == Some(group) {
+                return Err(format!("'{}' is both the key and a modifier in '{}'", token, text));
+            }
+            modifiers[group] = true;
+        }
+        Ok(Hotkey { modifiers, key })
+    }
+
+    /// Exact match: the key is down and the held modifier groups are exactly
+    /// the hotkey's, so "F6" does not fire on Ctrl+F6 and the other way round.
+    /// Other keys held at the same time don't matter. A modifier used as the
+    /// key itself is not counted as held.
+    pub fn is_active(&self, down: &[KeyId]) -> bool {
+        if !down.contains(&self.key) {
+            return false;
+        }
+        let own_group = modifier_group(self.key);
+        MODIFIERS.iter().enumerate().all(|(group, (_, names))| {
+            let held = Some(group) != own_group && down.iter().any(|k| names.contains(&k.name()));
+            held == self.modifiers[group]
+        })
+    }
+
+    /// Whether this hotkey can go active while `other` is being pressed:
+    /// its modifiers are a subset of `other`'s and its key is `other`'s key
+    /// or one of `other`'s modifiers. "F6" is part of "Ctrl+F6" (pressed F6
+    /// first), and so is "ControlLeft".
+    pub fn is_part_of(&self, other: &Hotkey) -> bool {
+        let subset = self.modifiers.iter().zip(other.modifiers).all(|(&mine, theirs)| !mine || theirs);
+        let key_in_other = self.key == other.key || modifier_group(self.key).is_some_and(|g| other.modifiers[g]);
+        self != other && subset && key_in_other
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ((name, _), held) in MODIFIERS.iter().zip(self.modifiers) {
+            if held {
+                write!(f, "{}+", name)?;
+            }
+        }
+        f.write_str(self.key.name())
+    }
+}
+
+/// Hotkey setting from its text, `None` for "" (unbound)
+pub fn parse_binding(text: &str) -> Result<Option<Hotkey>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    Hotkey::parse(text).map(Some)
+}
+
+/// Text of a hotkey setting, "" when unbound
+pub fn binding_name(hotkey: Option<Hotkey>) -> String {
+    hotkey.map_or_else(String::new, |h| h.to_string())
+}
+
+/// Serde format for `Option<Hotkey>` settings: unbound is "" so the settings
+/// file keeps its plain string values
+pub mod binding {
+    use super::Hotkey;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hotkey: &Option<Hotkey>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::binding_name(*hotkey))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Hotkey>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::parse_binding(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn down(names: &[&str]) -> Vec<KeyId> {
+        names.iter().map(|n| KeyId::parse(n).unwrap()).collect()
+    }
+
+    #[test]
+    fn parses_and_prints_chords() {
+        for text in ["F6", "Ctrl+Shift+F6", "Alt+Mouse4", "Ctrl+Meta+KeyQ", "ShiftLeft", "Ctrl+AltRight"] {
+            assert_eq!(Hotkey::parse(text).unwrap().to_string(), text);
+        }
+        assert_eq!(parse_binding(""), Ok(None));
+        assert_eq!(binding_name(None), "");
+
+        // Modifiers may come in any order but print in the standard one
+        assert_eq!(Hotkey::parse("Shift+Ctrl+F6").unwrap().to_string(), "Ctrl+Shift+F6");
+        assert_eq!(Hotkey::parse("Hyper+F6"), Err("unknown modifier 'Hyper' in 'Hyper+F6'".into()));
+        assert_eq!(Hotkey::parse("Ctrl+Ctrl+F6"), Err("modifier 'Ctrl' repeated in 'Ctrl+Ctrl+F6'".into()));
+        assert_eq!(Hotkey::parse("Shift+ShiftLeft"), Err("'Shift' is both the key and a modifier in 'Shift+ShiftLeft'".into()));
+        assert_eq!(Hotkey::parse("Ctrl+"), Err("'Ctrl+' has no key after the modifiers".into()));
+        assert_eq!(Hotkey::parse("Ctrl+F13"), Err("unknown key 'F13'".into()));
+    }
+
+    #[test]
+    fn matches_modifiers_exactly() {
+        let plain = Hotkey::parse("F6").unwrap();
+        let chord = Hotkey::parse("Ctrl+Shift+F6").unwrap();
+
+        assert!(plain.is_active(&down(&["F6"])));
+        assert!(!plain.is_active(&down(&["ControlLeft", "F6"])));
+        assert!(chord.is_active(&down(&["ControlRight", "ShiftLeft", "F6"])));
+        assert!(!chord.is_active(&down(&["ControlLeft", "F6"])));
+        assert!(!chord.is_active(&down(&["ControlLeft", "ShiftLeft", "AltLeft", "F6"])));
+        assert!(!chord.is_active(&down(&["ControlLeft", "ShiftLeft"])));
+        // Other keys held alongside don't break the match
+        assert!(chord.is_active(&down(&["ControlLeft", "ShiftLeft", "KeyW", "F6"])));
+    }
+
+    #[test]
+    fn matches_mouse_buttons_and_modifier_keys() {
+        let mouse = Hotkey::parse("Alt+Mouse4").unwrap();
+        assert!(mouse.is_active(&down(&["AltLeft", "Mouse4"])));
+        assert!(!mouse.is_active(&down(&["Mouse4"])));
+
+        let shift = Hotkey::parse("ShiftLeft").unwrap();
+        assert!(shift.is_active(&down(&["ShiftLeft"])));
+        assert!(shift.is_active(&down(&["ShiftLeft", "ShiftRight"])));
+        assert!(!shift.is_active(&down(&["ShiftRight"])));
+        assert!(!shift.is_active(&down(&["ControlLeft", "ShiftLeft"])));
+    }
+
+    #[test]
+    fn finds_hotkeys_pressed_on_the_way_to_a_chord() {
+        let part_of = |a: &str, b: &str| Hotkey::parse(a).unwrap().is_part_of(&Hotkey::parse(b).unwrap());
+        assert!(part_of("F6", "Ctrl+F6"));
+        assert!(part_of("Ctrl+F6", "Ctrl+Shift+F6"));
+        assert!(part_of("ControlLeft", "Ctrl+F6"));
+        assert!(part_of("Shift+AltLeft", "Shift+Alt+Mouse4"));
+
+        assert!(!part_of("Ctrl+F6", "F6"));
+        assert!(!part_of("F6", "F6"));
+        assert!(!part_of("Ctrl+F6", "Shift+F6"));
+        assert!(!part_of("F7", "Ctrl+F6"));
+        assert!(!part_of("ShiftLeft", "Ctrl+F6"));
+    }
+
+    #[test]
+    fn serializes_as_text() {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct Settings {
+            #[serde(with = "binding")]
+            key: Option<Hotkey>,
+        }
+        let s: Settings = serde_json::from_str(r#"{"key":"Ctrl+Alt+Delete"}"#).unwrap();
+        assert_eq!(serde_json::to_string(&s).unwrap(), r#"{"key":"Ctrl+Alt+Delete"}"#);
+        let s: Settings = serde_json::from_str(r#"{"key":""}"#).unwrap();
+        assert_eq!(s.key, None);
+        assert!(serde_json::from_str::<Settings>(r#"{"key":"Ctrl+AudioVolumeUp"}"#).is_err());
+    }
+}
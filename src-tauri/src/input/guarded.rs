@@ -0,0 +1,118 @@
+use super::{Held, InputBackend, MouseButton};
+use crate::cancel::CancelToken;
+use crate::keys::KeyId;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest uninterrupted sleep, so cancellation is noticed quickly
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+/// Wraps a backend for one macro or script run. Once the token is cancelled,
+/// sleeps return early and new presses and moves are dropped, while releases
+/// still go through. Past the deadline, if there is one, sleeps return early
+/// too. `release_all` lets go of anything left held.
+pub struct GuardedInput {
+    inner: Arc<dyn InputBackend>,
+    cancel: CancelToken,
+    deadline: Option<Instant>,
+    held: Mutex<Vec<Held>>,
+}
+
+impl GuardedInput {
+    pub fn new(inner: Arc<dyn InputBackend>, cancel: CancelToken) -> GuardedInput {
+        GuardedInput { inner, cancel, deadline: None, held: Mutex::new(Vec::new()) }
+    }
+
+    /// Also cut sleeps short once `deadline` has passed
+    pub fn with_deadline(inner: Arc<dyn InputBackend>, cancel: CancelToken, deadline: Instant) -> GuardedInput {
+        GuardedInput { inner, cancel, deadline: Some(deadline), held: Mutex::new(Vec::new()) }
+    }
+
+    fn stopped(&self) -> bool {
+        self.cancel.is_cancelled() || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    fn press(&self, held: Held) -> bool {
+        if self.cancel.is_cancelled() {
+            return false;
+        }
+        let mut all = self.held.lock().unwrap();
+        if !all.contains(&held) {
+            all.push(held);
+        }
+        true
+    }
+
+    fn release(&self, held: Held) {
+        self.held.lock().unwrap().retain(|h| *h != held);
+    }
+
+    /// Release everything still held, most recent first
+    pub fn release_all(&self) {
+        let held: Vec<Held> = self.held.lock().unwrap().drain(..).rev().collect();
+        for h in held {
+            h.release(&*self.inner);
+        }
+    }
+}
+
+impl InputBackend for GuardedInput {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn mouse_down(&self, button: MouseButton) {
+        if self.press(Held::Button(button)) {
+            self.inner.mouse_down(button);
+        }
+    }
+
+    fn mouse_up(&self, button: MouseButton) {
+        self.release(Held::Button(button));
+        self.inner.mouse_up(button);
+    }
+
+    fn move_absolute(&self, x: i32, y: i32) {
+        if !self.cancel.is_cancelled() {
+            self.inner.move_absolute(x, y);
+        }
+    }
+
+    fn move_relative(&self, dx: i32, dy: i32) {
+        if !self.cancel.is_cancelled() {
+            self.inner.move_relative(dx, dy);
+        }
+    }
+
+    fn key_down(&self, key: KeyId) {
+        if self.press(Held::Key(key)) {
+            self.inner.key_down(key);
+        }
+    }
+
+    fn key_up(&self, key: KeyId) {
+        self.release(Held::Key(key));
+        self.inner.key_up(key);
+    }
+
+    fn scroll(&self, notches: i32) {
+        if !self.cancel.is_cancelled() {
+            self.inner.scroll(notches);
+        }
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut left = duration;
+        while !left.is_zero() && !self.stopped() {
+            let slice = left.min(SLEEP_SLICE);
+            self.inner.sleep(slice);
+            left -= slice;
+        }
+    }
+}
+
+impl Drop for GuardedInput {
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
@@ -0,0 +1,77 @@
+//! Recording backend for tests: nothing is injected, every call is appended
+//! to an event log stamped with a virtual clock that only `sleep` advances.
+
+use super::{InputBackend, MouseButton};
+use crate::keys::KeyId;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    MoveAbsolute(i32, i32),
+    MoveRelative(i32, i32),
+    KeyDown(u16),
+    KeyUp(u16),
+    Scroll(i32),
+}
+
+/// Keys are logged by their Windows virtual-key code
+fn vk(key: KeyId) -> u16 {
+    key.codes().expect("only keyboard keys reach the backend").vk
+}
+
+#[derive(Default)]
+struct Recording {
+    now: Duration,
+    events: Vec<(Duration, InputEvent)>,
+}
+
+#[derive(Default)]
+pub struct MockBackend {
+    recording: Mutex<Recording>,
+}
+
+impl MockBackend {
+    pub fn new() -> MockBackend {
+        MockBackend::default()
+    }
+
+    /// Virtual time elapsed since creation.
+    pub fn now(&self) -> Duration {
+        self.recording.lock().unwrap().now
+    }
+
+    /// Every recorded event with its virtual timestamp in milliseconds.
+    pub fn timeline(&self) -> Vec<(u64, InputEvent)> {
+        let rec = self.recording.lock().unwrap();
+        rec.events.iter().map(|(t, e)| (t.as_millis() as u64, *e)).collect()
+    }
+
+    /// Recorded events without timestamps.
+    pub fn events(&self) -> Vec<InputEvent> {
+        self.recording.lock().unwrap().events.iter().map(|(_, e)| *e).collect()
+    }
+
+    fn record(&self, event: InputEvent) {
+        let mut rec = self.recording.lock().unwrap();
+        let now = rec.now;
+        rec.events.push((now, event));
+    }
+}
+
+impl InputBackend for MockBackend {
+    fn name(&self) -> &'static str { "mock" }
+    fn mouse_down(&self, button: MouseButton) { self.record(InputEvent::MouseDown(button)); }
+    fn mouse_up(&self, button: MouseButton) { self.record(InputEvent::MouseUp(button)); }
+    fn move_absolute(&self, x: i32, y: i32) { self.record(InputEvent::MoveAbsolute(x, y)); }
+    fn move_relative(&self, dx: i32, dy: i32) { self.record(InputEvent::MoveRelative(dx, dy)); }
+    fn key_down(&self, key: KeyId) { self.record(InputEvent::KeyDown(vk(key))); }
+    fn key_up(&self, key: KeyId) { self.record(InputEvent::KeyUp(vk(key))); }
+    fn scroll(&self, notches: i32) { self.record(InputEvent::Scroll(notches)); }
+
+    fn sleep(&self, duration: Duration) {
+        self.recording.lock().unwrap().now += duration;
+    }
+}
@@ -0,0 +1,180 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// INPUT BACKENDS — platform-specific synthesis of mouse and keyboard events
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(windows)]
+mod win32;
+#[cfg(target_os = "linux")]
+mod uinput;
+#[cfg(target_os = "linux")]
+mod xtest;
+#[cfg(test)]
+pub mod mock;
+mod guarded;
+mod tracked;
+
+pub use guarded::GuardedInput;
+pub use tracked::{Injected, TrackedBackend};
+
+use crate::keys::KeyId;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread;
+use std::time::Duration;
+
+/// Mouse buttons that can be pressed by a backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// XBUTTON1 ("Mouse4", usually "back")
+    X1,
+    /// XBUTTON2 ("Mouse5", usually "forward")
+    X2,
+}
+
+/// A key or button pressed through a backend and not released yet
+#[derive(Clone, Copy, PartialEq)]
+enum Held {
+    Key(KeyId),
+    Button(MouseButton),
+}
+
+impl Held {
+    fn release(self, backend: &dyn InputBackend) {
+        match self {
+            Held::Key(key) => backend.key_up(key),
+            Held::Button(button) => backend.mouse_up(button),
+        }
+    }
+
+    fn try_release(self, backend: &dyn InputBackend) -> bool {
+        match self {
+            Held::Key(key) => backend.try_key_up(key),
+            Held::Button(button) => backend.try_mouse_up(button),
+        }
+    }
+}
+
+/// Lock without waiting, for the panic hook: `None` while another thread, or
+/// the panicking one, holds the lock. A poisoned lock is used anyway.
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Name prefix of our uinput devices, which the hotkey listener skips
+#[cfg(target_os = "linux")]
+pub const VIRTUAL_DEVICE_PREFIX: &str = "CliKy virtual";
+
+/// `ioctl` on a uinput or evdev device; pointers are passed as `arg` too
+#[cfg(target_os = "linux")]
+pub fn ioctl(file: &std::fs::File, request: libc::c_ulong, arg: libc::c_ulong) -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    // The request parameter type differs between libc targets (c_ulong vs c_int)
+    if unsafe { libc::ioctl(file.as_raw_fd(), request as _, arg) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+impl MouseButton {
+    /// Linux input event code (BTN_*)
+    pub fn evdev(self) -> u16 {
+        match self {
+            MouseButton::Left => 0x110,
+            MouseButton::Right => 0x111,
+            MouseButton::Middle => 0x112,
+            MouseButton::X1 => 0x113,
+            MouseButton::X2 => 0x114,
+        }
+    }
+}
+
+/// Everything the clicker engine and macros need to drive the OS.
+///
+/// Keys come from the key table; each backend uses its own column of it.
+/// The mouse-button keys are pressed through `mouse_down` instead.
+pub trait InputBackend: Send + Sync {
+    /// Short human-readable name, used in logs.
+    fn name(&self) -> &'static str;
+
+    fn mouse_down(&self, button: MouseButton);
+    fn mouse_up(&self, button: MouseButton);
+
+    /// Move the cursor to a screen position in pixels.
+    fn move_absolute(&self, x: i32, y: i32);
+    /// Move the cursor by a pixel offset from its current position.
+    fn move_relative(&self, dx: i32, dy: i32);
+
+    fn key_down(&self, key: KeyId);
+    fn key_up(&self, key: KeyId);
+
+    /// `key_up` and `mouse_up` for the panic hook, which must not wait on a
+    /// lock: false if the release could not be sent right away. Backends
+    /// without locks can keep these defaults.
+    fn try_key_up(&self, key: KeyId) -> bool {
+        self.key_up(key);
+        true
+    }
+    fn try_mouse_up(&self, button: MouseButton) -> bool {
+        self.mouse_up(button);
+        true
+    }
+
+    /// Scroll the wheel by whole notches (positive = away from the user).
+    fn scroll(&self, notches: i32);
+
+    /// Wait between two events. Every delay in the clicker and macros goes
+    /// through here so that the mock backend can run them on a virtual clock.
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Backend used on platforms without an implementation: logs and drops events.
+pub struct NullBackend;
+
+impl InputBackend for NullBackend {
+    fn name(&self) -> &'static str { "null" }
+    fn mouse_down(&self, _button: MouseButton) {}
+    fn mouse_up(&self, _button: MouseButton) {}
+    fn move_absolute(&self, _x: i32, _y: i32) {}
+    fn move_relative(&self, _dx: i32, _dy: i32) {}
+    fn key_down(&self, _key: KeyId) {}
+    fn key_up(&self, _key: KeyId) {}
+    fn scroll(&self, _notches: i32) {}
+}
+
+/// Pick the best available backend for the current platform.
+pub fn default_backend() -> Arc<dyn InputBackend> {
+    #[cfg(windows)]
+    {
+        Arc::new(win32::Win32Backend)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let uinput_err = match uinput::UinputBackend::new() {
+            Ok(backend) => return Arc::new(backend),
+            Err(e) => e,
+        };
+        println!("Input: cannot open /dev/uinput ({}), trying XTest", uinput_err);
+        match xtest::XTestBackend::open(None) {
+            Ok(backend) => Arc::new(backend),
+            Err(e) => {
+                println!("Input: XTest unavailable ({}), synthesized input is disabled", e);
+                Arc::new(NullBackend)
+            }
+        }
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        println!("Input: no backend for this platform, synthesized input is disabled");
+        Arc::new(NullBackend)
+    }
+}
@@ -0,0 +1,280 @@
+use super::{try_lock, Held, InputBackend, MouseButton};
+use crate::keys::KeyId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long a released key still counts as ours. Polled key state can lag
+/// behind what was injected, so a quick tap may be seen after its release.
+const RELEASE_GRACE: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
+struct Sent {
+    /// Pressed and not released yet, oldest first
+    held: Vec<Held>,
+    /// Keys released lately, and when
+    released: HashMap<KeyId, Instant>,
+    /// Where the cursor was last moved to, and when
+    moved: Option<((i32, i32), Instant)>,
+}
+
+impl Sent {
+    fn released(&mut self, held: Held) {
+        self.held.retain(|h| *h != held);
+        if let Some(key) = key_of(held) {
+            self.released.insert(key, Instant::now());
+        }
+    }
+}
+
+/// The keys and buttons our own backend holds or just released, so that the
+/// hotkey listener can leave them out of what the user presses, and where it
+/// last put the cursor, for the fail-safe
+#[derive(Default)]
+pub struct Injected {
+    sent: Mutex<Sent>,
+}
+
+/// The key a held key or button shows up as, if any
+fn key_of(held: Held) -> Option<KeyId> {
+    match held {
+        Held::Key(key) => Some(key),
+        Held::Button(button) => KeyId::from_button(button),
+    }
+}
+
+impl Injected {
+    /// Also used from the panic hook, so a lock poisoned by the panicking
+    /// thread must not stop the release
+    fn sent(&self) -> MutexGuard<'_, Sent> {
+        self.sent.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn pressed(&self, held: Held) {
+        let mut sent = self.sent();
+        if !sent.held.contains(&held) {
+            sent.held.push(held);
+        }
+    }
+
+    fn released(&self, held: Held) {
+        self.sent().released(held);
+    }
+
+    /// `released`, unless another thread is noting something right now
+    fn try_released(&self, held: Held) {
+        if let Some(mut sent) = try_lock(&self.sent) {
+            sent.released(held);
+        }
+    }
+
+    /// Whether the cursor at `pos` is where our backend moved it, rather than
+    /// where the user did. Only absolute moves are followed.
+    pub fn cursor_is_ours(&self, pos: (i32, i32), now: Instant) -> bool {
+        let mut sent = self.sent();
+        let Some((to, at)) = sent.moved else { return false };
+        // Backends may round the position by a pixel
+        if (pos.0 - to.0).abs() <= 1 && (pos.1 - to.1).abs() <= 1 {
+            return true;
+        }
+        // Seen elsewhere once the move had time to land: the user has it now
+        if now.saturating_duration_since(at) >= RELEASE_GRACE {
+            sent.moved = None;
+        }
+        false
+    }
+
+    /// The keys in `down` the user pressed, as of `now`
+    pub fn physical(&self, down: Vec<KeyId>, now: Instant) -> Vec<KeyId> {
+        let mut sent = self.sent();
+        sent.released.retain(|_, t| now.saturating_duration_since(*t) < RELEASE_GRACE);
+        let ours: Vec<KeyId> = sent.held.iter().filter_map(|&h| key_of(h)).chain(sent.released.keys().copied()).collect();
+        down.into_iter().filter(|key| !ours.contains(key)).collect()
+    }
+}
+
+/// Passes everything on to the real backend, noting in `Injected` what it
+/// presses. Presses are noted before they are sent and releases after, so
+/// the listener never sees one of ours unmarked.
+pub struct TrackedBackend {
+    inner: Arc<dyn InputBackend>,
+    injected: Arc<Injected>,
+}
+
+impl TrackedBackend {
+    pub fn new(inner: Arc<dyn InputBackend>, injected: Arc<Injected>) -> TrackedBackend {
+        TrackedBackend { inner, injected }
+    }
+
+    /// Let go of every key and button still held, most recent first: for
+    /// when whatever pressed them cannot, after an emergency stop, a panic,
+    /// or on exit
+    pub fn release_all(&self) {
+        let held = std::mem::take(&mut self.injected.sent().held);
+        if !held.is_empty() {
+            println!("Input: releasing {} held key(s) and button(s)", held.len());
+        }
+        for h in held.into_iter().rev() {
+            h.release(self);
+        }
+    }
+
+    /// `release_all` for the panic hook, which must never block: the
+    /// panicking thread may hold any of our locks. Whatever cannot be
+    /// released right away stays noted, for the release on exit.
+    pub fn try_release_all(&self) {
+        let Some(mut sent) = try_lock(&self.injected.sent) else { return };
+        let mut kept = Vec::new();
+        for h in std::mem::take(&mut sent.held).into_iter().rev() {
+            // Straight to the real backend: going through `self` would lock
+            // `sent` again
+            if h.try_release(&*self.inner) {
+                sent.released(h);
+            } else {
+                kept.push(h);
+            }
+        }
+        kept.reverse();
+        sent.held = kept;
+    }
+}
+
+impl InputBackend for TrackedBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn mouse_down(&self, button: MouseButton) {
+        self.injected.pressed(Held::Button(button));
+        self.inner.mouse_down(button);
+    }
+
+    fn mouse_up(&self, button: MouseButton) {
+        self.inner.mouse_up(button);
+        self.injected.released(Held::Button(button));
+    }
+
+    fn move_absolute(&self, x: i32, y: i32) {
+        self.injected.sent().moved = Some(((x, y), Instant::now()));
+        self.inner.move_absolute(x, y);
+    }
+
+    fn move_relative(&self, dx: i32, dy: i32) {
+        self.inner.move_relative(dx, dy);
+    }
+
+    fn key_down(&self, key: KeyId) {
+        self.injected.pressed(Held::Key(key));
+        self.inner.key_down(key);
+    }
+
+    fn key_up(&self, key: KeyId) {
+        self.inner.key_up(key);
+        self.injected.released(Held::Key(key));
+    }
+
+    fn try_key_up(&self, key: KeyId) -> bool {
+        let sent = self.inner.try_key_up(key);
+        if sent {
+            self.injected.try_released(Held::Key(key));
+        }
+        sent
+    }
+
+    fn try_mouse_up(&self, button: MouseButton) -> bool {
+        let sent = self.inner.try_mouse_up(button);
+        if sent {
+            self.injected.try_released(Held::Button(button));
+        }
+        sent
+    }
+
+    fn scroll(&self, notches: i32) {
+        self.inner.scroll(notches);
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.inner.sleep(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent, MockBackend};
+
+    fn key(name: &str) -> KeyId {
+        KeyId::parse(name).unwrap()
+    }
+
+    #[test]
+    fn leaves_out_our_own_keys() {
+        let mock = Arc::new(MockBackend::new());
+        let injected = Arc::new(Injected::default());
+        let backend = TrackedBackend::new(mock.clone(), injected.clone());
+
+        backend.key_down(key("KeyQ"));
+        backend.mouse_down(MouseButton::X2);
+        // Left has no key of its own, so no hotkey can see it
+        backend.mouse_down(MouseButton::Left);
+        let down = vec![key("KeyQ"), key("Mouse5"), key("F6")];
+        assert_eq!(injected.physical(down.clone(), Instant::now()), vec![key("F6")]);
+
+        backend.key_up(key("KeyQ"));
+        backend.mouse_up(MouseButton::X2);
+        let released = Instant::now();
+        assert_eq!(injected.physical(down.clone(), released), vec![key("F6")]);
+        // Once the grace period is over a press is the user's again
+        assert_eq!(injected.physical(down.clone(), released + RELEASE_GRACE), down);
+
+        assert_eq!(mock.events()[..2], [InputEvent::KeyDown(0x51), InputEvent::MouseDown(MouseButton::X2)]);
+    }
+
+    #[test]
+    fn knows_where_it_put_the_cursor() {
+        let injected = Arc::new(Injected::default());
+        let backend = TrackedBackend::new(Arc::new(MockBackend::new()), injected.clone());
+        backend.move_absolute(0, 0);
+        let moved = Instant::now();
+        // Not there yet, then there
+        assert!(!injected.cursor_is_ours((500, 500), moved));
+        assert!(injected.cursor_is_ours((0, 1), moved));
+        assert!(injected.cursor_is_ours((0, 0), moved + RELEASE_GRACE * 10));
+        // Once the user moves it away, the spot is no longer ours
+        assert!(!injected.cursor_is_ours((300, 200), moved + RELEASE_GRACE));
+        assert!(!injected.cursor_is_ours((0, 0), moved + RELEASE_GRACE));
+    }
+
+    #[test]
+    fn releases_everything_still_held() {
+        let mock = Arc::new(MockBackend::new());
+        let backend = TrackedBackend::new(mock.clone(), Arc::new(Injected::default()));
+        backend.key_down(key("KeyQ"));
+        backend.mouse_down(MouseButton::Left);
+        backend.key_down(key("ShiftLeft"));
+        backend.key_up(key("ShiftLeft"));
+
+        backend.release_all();
+        backend.release_all();
+        assert_eq!(mock.events()[4..], [InputEvent::MouseUp(MouseButton::Left), InputEvent::KeyUp(0x51)]);
+    }
+
+    #[test]
+    fn panic_release_skips_what_is_locked() {
+        let mock = Arc::new(MockBackend::new());
+        let injected = Arc::new(Injected::default());
+        let backend = TrackedBackend::new(mock.clone(), injected.clone());
+        backend.key_down(key("KeyQ"));
+
+        // As if the panicking thread were noting a press
+        let sent = injected.sent();
+        backend.try_release_all();
+        drop(sent);
+        assert_eq!(mock.events(), [InputEvent::KeyDown(0x51)]);
+
+        backend.try_release_all();
+        assert_eq!(mock.events(), [InputEvent::KeyDown(0x51), InputEvent::KeyUp(0x51)]);
+        backend.release_all();
+        assert_eq!(mock.events().len(), 2);
+    }
+}
@@ -0,0 +1,247 @@
+//! Virtual input devices created through `/dev/uinput`.
+//!
+//! Two devices are registered: a keyboard/relative mouse that carries key,
+//! button, wheel and relative motion events, and an absolute pointer (shaped
+//! like a VM "tablet") whose axes span the screen so that `move_absolute`
+//! lands exactly on the requested pixel.
+
+use super::{ioctl, try_lock, InputBackend, MouseButton, VIRTUAL_DEVICE_PREFIX};
+use crate::keys::KeyId;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::Mutex;
+
+// linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_ABS: u16 = 0x03;
+const SYN_REPORT: u16 = 0;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const REL_WHEEL: u16 = 0x08;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+const BTN_EXTRA: u16 = 0x114;
+const KEY_MAX_KEYBOARD: u16 = 0xF8;
+
+// linux/uinput.h
+const UI_DEV_CREATE: libc::c_ulong = 0x5501;
+const UI_DEV_DESTROY: libc::c_ulong = 0x5502;
+const UI_SET_EVBIT: libc::c_ulong = 0x4004_5564;
+const UI_SET_KEYBIT: libc::c_ulong = 0x4004_5565;
+const UI_SET_RELBIT: libc::c_ulong = 0x4004_5566;
+const UI_SET_ABSBIT: libc::c_ulong = 0x4004_5567;
+const UINPUT_MAX_NAME_SIZE: usize = 80;
+const ABS_CNT: usize = 64;
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// Legacy `struct uinput_user_dev`, written to the fd before `UI_DEV_CREATE`.
+#[repr(C)]
+struct UinputUserDev {
+    name: [u8; UINPUT_MAX_NAME_SIZE],
+    bustype: u16,
+    vendor: u16,
+    product: u16,
+    version: u16,
+    ff_effects_max: u32,
+    absmax: [i32; ABS_CNT],
+    absmin: [i32; ABS_CNT],
+    absfuzz: [i32; ABS_CNT],
+    absflat: [i32; ABS_CNT],
+}
+
+/// One registered uinput device.
+struct Device {
+    file: File,
+}
+
+impl Device {
+    fn create(
+        name: &str,
+        product: u16,
+        configure: impl FnOnce(&File) -> io::Result<()>,
+        abs_max: Option<(i32, i32)>,
+    ) -> io::Result<Device> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")?;
+        configure(&file)?;
+
+        let mut dev = UinputUserDev {
+            name: [0; UINPUT_MAX_NAME_SIZE],
+            bustype: BUS_VIRTUAL,
+            vendor: 0x1209,
+            product,
+            version: 1,
+            ff_effects_max: 0,
+            absmax: [0; ABS_CNT],
+            absmin: [0; ABS_CNT],
+            absfuzz: [0; ABS_CNT],
+            absflat: [0; ABS_CNT],
+        };
+        let len = name.len().min(UINPUT_MAX_NAME_SIZE - 1);
+        dev.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        if let Some((max_x, max_y)) = abs_max {
+            dev.absmax[ABS_X as usize] = max_x;
+            dev.absmax[ABS_Y as usize] = max_y;
+        }
+        file.write_all(as_bytes(&dev))?;
+        ioctl(&file, UI_DEV_CREATE, 0)?;
+        Ok(Device { file })
+    }
+
+    /// Write a batch of events followed by a SYN_REPORT.
+    fn emit(&mut self, events: &[(u16, u16, i32)]) {
+        for &(kind, code, value) in events.iter().chain(&[(EV_SYN, SYN_REPORT, 0)]) {
+            let ev = libc::input_event {
+                time: libc::timeval { tv_sec: 0, tv_usec: 0 },
+                type_: kind,
+                code,
+                value,
+            };
+            if let Err(e) = self.file.write_all(as_bytes(&ev)) {
+                println!("uinput: write failed: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        let _ = ioctl(&self.file, UI_DEV_DESTROY, 0);
+    }
+}
+
+fn as_bytes<T>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, std::mem::size_of::<T>()) }
+}
+
+/// `/dev/uinput` backend.
+pub struct UinputBackend {
+    keyboard: Mutex<Device>,
+    pointer: Mutex<Device>,
+}
+
+impl UinputBackend {
+    pub fn new() -> io::Result<UinputBackend> {
+        let (width, height) = screen_size();
+
+        let keyboard = Device::create(&format!("{} input", VIRTUAL_DEVICE_PREFIX), 0x0001, |f| {
+            for ev in [EV_KEY, EV_REL, EV_SYN] {
+                ioctl(f, UI_SET_EVBIT, ev as _)?;
+            }
+            for code in (1..KEY_MAX_KEYBOARD).chain(BTN_LEFT..=BTN_EXTRA) {
+                ioctl(f, UI_SET_KEYBIT, code as _)?;
+            }
+            for rel in [REL_X, REL_Y, REL_WHEEL] {
+                ioctl(f, UI_SET_RELBIT, rel as _)?;
+            }
+            Ok(())
+        }, None)?;
+
+        let pointer = Device::create(&format!("{} pointer", VIRTUAL_DEVICE_PREFIX), 0x0002, |f| {
+            for ev in [EV_KEY, EV_ABS, EV_SYN] {
+                ioctl(f, UI_SET_EVBIT, ev as _)?;
+            }
+            // Buttons are what get an absolute device classified as a mouse
+            for code in [BTN_LEFT, BTN_RIGHT, BTN_MIDDLE] {
+                ioctl(f, UI_SET_KEYBIT, code as _)?;
+            }
+            for abs in [ABS_X, ABS_Y] {
+                ioctl(f, UI_SET_ABSBIT, abs as _)?;
+            }
+            Ok(())
+        }, Some((width - 1, height - 1)))?;
+
+        println!("uinput: devices created for a {}x{} screen", width, height);
+        Ok(UinputBackend { keyboard: Mutex::new(keyboard), pointer: Mutex::new(pointer) })
+    }
+}
+
+impl InputBackend for UinputBackend {
+    fn name(&self) -> &'static str { "uinput" }
+
+    fn mouse_down(&self, button: MouseButton) {
+        self.keyboard.lock().unwrap().emit(&[(EV_KEY, button.evdev(), 1)]);
+    }
+
+    fn mouse_up(&self, button: MouseButton) {
+        self.keyboard.lock().unwrap().emit(&[(EV_KEY, button.evdev(), 0)]);
+    }
+
+    fn move_absolute(&self, x: i32, y: i32) {
+        self.pointer.lock().unwrap().emit(&[(EV_ABS, ABS_X, x), (EV_ABS, ABS_Y, y)]);
+    }
+
+    fn move_relative(&self, dx: i32, dy: i32) {
+        self.keyboard.lock().unwrap().emit(&[(EV_REL, REL_X, dx), (EV_REL, REL_Y, dy)]);
+    }
+
+    fn key_down(&self, key: KeyId) {
+        if let Some(codes) = key.codes() {
+            self.keyboard.lock().unwrap().emit(&[(EV_KEY, codes.evdev, 1)]);
+        }
+    }
+
+    fn key_up(&self, key: KeyId) {
+        if let Some(codes) = key.codes() {
+            self.keyboard.lock().unwrap().emit(&[(EV_KEY, codes.evdev, 0)]);
+        }
+    }
+
+    fn try_key_up(&self, key: KeyId) -> bool {
+        let Some(codes) = key.codes() else { return true };
+        try_lock(&self.keyboard).map(|mut device| device.emit(&[(EV_KEY, codes.evdev, 0)])).is_some()
+    }
+
+    fn try_mouse_up(&self, button: MouseButton) -> bool {
+        try_lock(&self.keyboard).map(|mut device| device.emit(&[(EV_KEY, button.evdev(), 0)])).is_some()
+    }
+
+    fn scroll(&self, notches: i32) {
+        self.keyboard.lock().unwrap().emit(&[(EV_REL, REL_WHEEL, notches)]);
+    }
+}
+
+/// Size of the screen the absolute pointer is mapped onto.
+///
+/// `CLIKY_SCREEN_SIZE=WIDTHxHEIGHT` overrides detection; otherwise the
+/// preferred mode of the first connected DRM output is used.
+fn screen_size() -> (i32, i32) {
+    if let Some(size) = std::env::var("CLIKY_SCREEN_SIZE").ok().and_then(|s| parse_mode(&s)) {
+        return size;
+    }
+    if let Ok(entries) = fs::read_dir("/sys/class/drm") {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let connected = fs::read_to_string(path.join("status"))
+                .map(|s| s.trim() == "connected")
+                .unwrap_or(false);
+            if !connected {
+                continue;
+            }
+            let mode = fs::read_to_string(path.join("modes")).unwrap_or_default();
+            if let Some(size) = mode.lines().next().and_then(parse_mode) {
+                return size;
+            }
+        }
+    }
+    (1920, 1080)
+}
+
+fn parse_mode(s: &str) -> Option<(i32, i32)> {
+    let (w, h) = s.trim().split_once('x')?;
+    // DRM modes may carry a suffix such as "1920x1080i"
+    let h: String = h.chars().take_while(|c| c.is_ascii_digit()).collect();
+    match (w.parse().ok()?, h.parse().ok()?) {
+        (w, h) if w > 0 && h > 0 => Some((w, h)),
+        _ => None,
+    }
+}
@@ -0,0 +1,103 @@
+use super::{InputBackend, MouseButton};
+use crate::keys::KeyId;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+    MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN,
+    MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_WHEEL, MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, MOUSEINPUT,
+    MOUSE_EVENT_FLAGS, VIRTUAL_KEY,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+const XBUTTON1: u32 = 0x0001;
+const XBUTTON2: u32 = 0x0002;
+const WHEEL_DELTA: i32 = 120;
+
+/// `SendInput`-based backend.
+pub struct Win32Backend;
+
+fn send_mouse(flags: MOUSE_EVENT_FLAGS, dx: i32, dy: i32, data: u32) {
+    unsafe {
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT { dx, dy, mouseData: data, dwFlags: flags, ..Default::default() },
+            },
+        };
+        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+fn send_key(key: KeyId, mut flags: KEYBD_EVENT_FLAGS) {
+    let Some(codes) = key.codes() else { return };
+    if codes.extended {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    let vk = codes.vk;
+    unsafe {
+        let input = INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT { wVk: VIRTUAL_KEY(vk), dwFlags: flags, ..Default::default() },
+            },
+        };
+        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// (flags, mouseData) for a button transition
+fn button_flags(button: MouseButton, down: bool) -> (MOUSE_EVENT_FLAGS, u32) {
+    match (button, down) {
+        (MouseButton::Left, true) => (MOUSEEVENTF_LEFTDOWN, 0),
+        (MouseButton::Left, false) => (MOUSEEVENTF_LEFTUP, 0),
+        (MouseButton::Right, true) => (MOUSEEVENTF_RIGHTDOWN, 0),
+        (MouseButton::Right, false) => (MOUSEEVENTF_RIGHTUP, 0),
+        (MouseButton::Middle, true) => (MOUSEEVENTF_MIDDLEDOWN, 0),
+        (MouseButton::Middle, false) => (MOUSEEVENTF_MIDDLEUP, 0),
+        (MouseButton::X1, true) => (MOUSEEVENTF_XDOWN, XBUTTON1),
+        (MouseButton::X1, false) => (MOUSEEVENTF_XUP, XBUTTON1),
+        (MouseButton::X2, true) => (MOUSEEVENTF_XDOWN, XBUTTON2),
+        (MouseButton::X2, false) => (MOUSEEVENTF_XUP, XBUTTON2),
+    }
+}
+
+impl InputBackend for Win32Backend {
+    fn name(&self) -> &'static str { "win32" }
+
+    fn mouse_down(&self, button: MouseButton) {
+        let (flags, data) = button_flags(button, true);
+        send_mouse(flags, 0, 0, data);
+    }
+
+    fn mouse_up(&self, button: MouseButton) {
+        let (flags, data) = button_flags(button, false);
+        send_mouse(flags, 0, 0, data);
+    }
+
+    fn move_absolute(&self, x: i32, y: i32) {
+        // SendInput expects absolute coordinates normalized to 0..=65535
+        let (sw, sh) = unsafe {
+            (GetSystemMetrics(SM_CXSCREEN) as i64, GetSystemMetrics(SM_CYSCREEN) as i64)
+        };
+        let norm_x = ((x as i64) * 65535 / sw.max(1)) as i32;
+        let norm_y = ((y as i64) * 65535 / sh.max(1)) as i32;
+        send_mouse(MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE, norm_x, norm_y, 0);
+    }
+
+    fn move_relative(&self, dx: i32, dy: i32) {
+        send_mouse(MOUSEEVENTF_MOVE, dx, dy, 0);
+    }
+
+    fn key_down(&self, key: KeyId) {
+        send_key(key, KEYBD_EVENT_FLAGS(0));
+    }
+
+    fn key_up(&self, key: KeyId) {
+        send_key(key, KEYEVENTF_KEYUP);
+    }
+
+    fn scroll(&self, notches: i32) {
+        // mouseData is a signed wheel delta stored in a DWORD
+        send_mouse(MOUSEEVENTF_WHEEL, 0, 0, (notches * WHEEL_DELTA) as u32);
+    }
+}
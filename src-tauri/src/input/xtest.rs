@@ -0,0 +1,274 @@
+//! X11 backend using the XTEST extension.
+//!
+//! Needs no special permissions, only a reachable X server, which makes it
+//! the fallback when `/dev/uinput` is not writable. Xlib and libXtst are
+//! loaded at runtime so the binary still starts on machines without them.
+
+use super::{try_lock, InputBackend, MouseButton};
+use crate::keys::KeyId;
+use crate::x11::Connection;
+use std::os::raw::{c_int, c_ulong};
+use std::sync::Mutex;
+use x11_dl::xlib::{self, Xlib};
+use x11_dl::xtest::Xf86vmode as XTest;
+
+/// XTEST-based backend.
+pub struct XTestBackend {
+    /// Before `xlib`, so it is closed while Xlib is still loaded
+    conn: Mutex<Connection>,
+    xlib: Xlib,
+    xtest: XTest,
+}
+
+impl XTestBackend {
+    /// Connect to `display`, or to `$DISPLAY` when `None`.
+    pub fn open(display: Option<&str>) -> Result<XTestBackend, String> {
+        let xlib = Xlib::open().map_err(|e| e.to_string())?;
+        let xtest = XTest::open().map_err(|e| e.to_string())?;
+        let conn = Connection::open(&xlib, display)?;
+        let (mut event_base, mut error_base, mut major, mut minor) = (0, 0, 0, 0);
+        let has_xtest = unsafe {
+            (xtest.XTestQueryExtension)(conn.display, &mut event_base, &mut error_base, &mut major, &mut minor)
+        };
+        if has_xtest == 0 {
+            return Err("X server has no XTEST extension".into());
+        }
+        Ok(XTestBackend { conn: Mutex::new(conn), xlib, xtest })
+    }
+
+    fn button(&self, conn: &Connection, button: MouseButton, down: bool) {
+        unsafe {
+            (self.xtest.XTestFakeButtonEvent)(conn.display, button_number(button), down as c_int, 0);
+            (self.xlib.XFlush)(conn.display);
+        }
+    }
+
+    fn key(&self, conn: &Connection, key: KeyId, down: bool) {
+        let Some(codes) = key.codes() else { return };
+        let keysym = codes.keysym as c_ulong;
+        unsafe {
+            let keycode = (self.xlib.XKeysymToKeycode)(conn.display, keysym);
+            if keycode == 0 {
+                return;
+            }
+            (self.xtest.XTestFakeKeyEvent)(conn.display, keycode as u32, down as c_int, 0);
+            (self.xlib.XFlush)(conn.display);
+        }
+    }
+}
+
+fn button_number(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::X1 => 8,
+        MouseButton::X2 => 9,
+    }
+}
+
+impl InputBackend for XTestBackend {
+    fn name(&self) -> &'static str { "xtest" }
+
+    fn mouse_down(&self, button: MouseButton) {
+        self.button(&self.conn.lock().unwrap(), button, true);
+    }
+
+    fn mouse_up(&self, button: MouseButton) {
+        self.button(&self.conn.lock().unwrap(), button, false);
+    }
+
+    fn move_absolute(&self, x: i32, y: i32) {
+        let conn = self.conn.lock().unwrap();
+        unsafe {
+            // Screen -1 means "the screen the pointer is currently on"
+            (self.xtest.XTestFakeMotionEvent)(conn.display, -1, x, y, 0);
+            (self.xlib.XFlush)(conn.display);
+        }
+    }
+
+    fn move_relative(&self, dx: i32, dy: i32) {
+        // XTestFakeRelativeMotionEvent changed arity between libXtst releases,
+        // so resolve the offset against the current pointer position instead
+        let conn = self.conn.lock().unwrap();
+        unsafe {
+            let screen = (self.xlib.XDefaultScreen)(conn.display);
+            let root = (self.xlib.XRootWindow)(conn.display, screen);
+            let (mut root_ret, mut child) = (0, 0);
+            let (mut x, mut y, mut wx, mut wy, mut mask) = (0, 0, 0, 0, 0);
+            (self.xlib.XQueryPointer)(
+                conn.display, root, &mut root_ret, &mut child,
+                &mut x, &mut y, &mut wx, &mut wy, &mut mask,
+            );
+            (self.xtest.XTestFakeMotionEvent)(conn.display, -1, x + dx, y + dy, 0);
+            (self.xlib.XFlush)(conn.display);
+        }
+    }
+
+    fn key_down(&self, key: KeyId) {
+        self.key(&self.conn.lock().unwrap(), key, true);
+    }
+
+    fn key_up(&self, key: KeyId) {
+        self.key(&self.conn.lock().unwrap(), key, false);
+    }
+
+    fn try_key_up(&self, key: KeyId) -> bool {
+        try_lock(&self.conn).map(|conn| self.key(&conn, key, false)).is_some()
+    }
+
+    fn try_mouse_up(&self, button: MouseButton) -> bool {
+        try_lock(&self.conn).map(|conn| self.button(&conn, button, false)).is_some()
+    }
+
+    fn scroll(&self, notches: i32) {
+        // The wheel is buttons 4 (up) and 5 (down), one click per notch
+        let button = if notches > 0 { 4 } else { 5 };
+        let conn = self.conn.lock().unwrap();
+        unsafe {
+            for _ in 0..notches.unsigned_abs() {
+                (self.xtest.XTestFakeButtonEvent)(conn.display, button, xlib::True, 0);
+                (self.xtest.XTestFakeButtonEvent)(conn.display, button, xlib::False, 0);
+            }
+            (self.xlib.XFlush)(conn.display);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    /// A private Xvfb server, killed on drop.
+    struct Xvfb {
+        child: Child,
+        display: String,
+    }
+
+    impl Xvfb {
+        /// Start Xvfb, or `None` if it is not installed.
+        fn start() -> Option<Xvfb> {
+            // Tests run in parallel, so every server gets its own display number
+            static NEXT: AtomicU32 = AtomicU32::new(0);
+            let n = NEXT.fetch_add(1, Ordering::SeqCst);
+            let display = format!(":{}", 200 + (std::process::id() % 100) * 10 + n);
+            let child = Command::new("Xvfb")
+                .args([display.as_str(), "-screen", "0", "1024x768x24", "-nolisten", "tcp"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            Some(Xvfb { child, display })
+        }
+
+        fn backend(&self) -> XTestBackend {
+            for _ in 0..50 {
+                if let Ok(backend) = XTestBackend::open(Some(&self.display)) {
+                    return backend;
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            panic!("Xvfb on {} did not come up", self.display);
+        }
+    }
+
+    impl Drop for Xvfb {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    struct Pointer {
+        x: i32,
+        y: i32,
+        mask: u32,
+    }
+
+    fn query_pointer(backend: &XTestBackend) -> Pointer {
+        let conn = backend.conn.lock().unwrap();
+        let (mut root, mut child) = (0, 0);
+        let (mut x, mut y, mut wx, mut wy, mut mask) = (0, 0, 0, 0, 0);
+        unsafe {
+            (backend.xlib.XSync)(conn.display, xlib::False);
+            let screen = (backend.xlib.XDefaultScreen)(conn.display);
+            let root_window = (backend.xlib.XRootWindow)(conn.display, screen);
+            (backend.xlib.XQueryPointer)(
+                conn.display, root_window, &mut root, &mut child,
+                &mut x, &mut y, &mut wx, &mut wy, &mut mask,
+            );
+        }
+        Pointer { x, y, mask }
+    }
+
+    fn key_is_down(backend: &XTestBackend, key: KeyId) -> bool {
+        let conn = backend.conn.lock().unwrap();
+        let mut keymap = [0 as std::os::raw::c_char; 32];
+        unsafe {
+            (backend.xlib.XSync)(conn.display, xlib::False);
+            let keycode = (backend.xlib.XKeysymToKeycode)(conn.display, key.codes().unwrap().keysym as c_ulong);
+            (backend.xlib.XQueryKeymap)(conn.display, keymap.as_mut_ptr());
+            keymap[keycode as usize / 8] as u8 & (1 << (keycode % 8)) != 0
+        }
+    }
+
+    macro_rules! require_xvfb {
+        () => {
+            match Xvfb::start() {
+                Some(xvfb) => xvfb,
+                None => {
+                    eprintln!("Xvfb not installed, skipping");
+                    return;
+                }
+            }
+        };
+    }
+
+    #[test]
+    fn moves_pointer_to_absolute_position() {
+        let xvfb = require_xvfb!();
+        let backend = xvfb.backend();
+
+        backend.move_absolute(321, 123);
+        let p = query_pointer(&backend);
+        assert_eq!((p.x, p.y), (321, 123));
+
+        backend.move_relative(-21, 27);
+        let p = query_pointer(&backend);
+        assert_eq!((p.x, p.y), (300, 150));
+    }
+
+    #[test]
+    fn presses_and_releases_mouse_buttons() {
+        let xvfb = require_xvfb!();
+        let backend = xvfb.backend();
+
+        backend.mouse_down(MouseButton::Left);
+        assert_ne!(query_pointer(&backend).mask & xlib::Button1Mask, 0);
+        backend.mouse_up(MouseButton::Left);
+        assert_eq!(query_pointer(&backend).mask & xlib::Button1Mask, 0);
+
+        backend.mouse_down(MouseButton::Right);
+        assert_ne!(query_pointer(&backend).mask & xlib::Button3Mask, 0);
+        backend.mouse_up(MouseButton::Right);
+        assert_eq!(query_pointer(&backend).mask & xlib::Button3Mask, 0);
+    }
+
+    #[test]
+    fn presses_and_releases_keys() {
+        let xvfb = require_xvfb!();
+        let backend = xvfb.backend();
+
+        for name in ["KeyQ", "Digit6", "Tab", "AltLeft", "F6", "ArrowUp", "Numpad5", "Slash"] {
+            let key = KeyId::parse(name).unwrap();
+            backend.key_down(key);
+            assert!(key_is_down(&backend, key), "{} not down", key);
+            backend.key_up(key);
+            assert!(!key_is_down(&backend, key), "{} still down", key);
+        }
+    }
+}
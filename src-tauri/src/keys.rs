@@ -0,0 +1,329 @@
+use crate::input::MouseButton;
+use device_query::Keycode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// KEY TABLE — one row per key: detection and every backend's native code
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Names are `KeyboardEvent.code` values, which is what the frontend records.
+// Punctuation codes are the US layout positions, like the browser's.
+
+/// Native codes for injecting a keyboard key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyCodes {
+    /// Windows virtual-key code
+    pub vk: u16,
+    /// Sent with KEYEVENTF_EXTENDEDKEY on Windows (right-hand modifiers,
+    /// navigation block, numpad Enter and Divide, media keys)
+    pub extended: bool,
+    /// Linux input event code (KEY_*)
+    pub evdev: u16,
+    /// X11 keysym
+    pub keysym: u32,
+}
+
+#[derive(Clone, Copy)]
+enum Output {
+    Key(KeyCodes),
+    /// Mouse buttons bindable like keys: device_query `button_pressed` index
+    /// (1 = left, 2 = middle, 3 = right, 4/5 = X) and the button it presses
+    Button(usize, MouseButton),
+}
+
+struct KeyDef {
+    name: &'static str,
+    /// How device_query reports the key; `None` if it cannot be polled
+    detect: Option<Keycode>,
+    output: Output,
+}
+
+const fn key(name: &'static str, detect: Option<Keycode>, vk: u16, evdev: u16, keysym: u32) -> KeyDef {
+    KeyDef { name, detect, output: Output::Key(KeyCodes { vk, extended: false, evdev, keysym }) }
+}
+
+const fn ext(name: &'static str, detect: Option<Keycode>, vk: u16, evdev: u16, keysym: u32) -> KeyDef {
+    KeyDef { name, detect, output: Output::Key(KeyCodes { vk, extended: true, evdev, keysym }) }
+}
+
+const fn button(name: &'static str, index: usize, button: MouseButton) -> KeyDef {
+    KeyDef { name, detect: None, output: Output::Button(index, button) }
+}
+
+use Keycode as K;
+
+#[rustfmt::skip]
+const KEYS: &[KeyDef] = &[
+    // Letters
+    key("KeyA", Some(K::A), 0x41, 30, 0x61), key("KeyB", Some(K::B), 0x42, 48, 0x62),
+    key("KeyC", Some(K::C), 0x43, 46, 0x63), key("KeyD", Some(K::D), 0x44, 32, 0x64),
+    key("KeyE", Some(K::E), 0x45, 18, 0x65), key("KeyF", Some(K::F), 0x46, 33, 0x66),
+    key("KeyG", Some(K::G), 0x47, 34, 0x67), key("KeyH", Some(K::H), 0x48, 35, 0x68),
+    key("KeyI", Some(K::I), 0x49, 23, 0x69), key("KeyJ", Some(K::J), 0x4A, 36, 0x6A),
+    key("KeyK", Some(K::K), 0x4B, 37, 0x6B), key("KeyL", Some(K::L), 0x4C, 38, 0x6C),
+    key("KeyM", Some(K::M), 0x4D, 50, 0x6D), key("KeyN", Some(K::N), 0x4E, 49, 0x6E),
+    key("KeyO", Some(K::O), 0x4F, 24, 0x6F), key("KeyP", Some(K::P), 0x50, 25, 0x70),
+    key("KeyQ", Some(K::Q), 0x51, 16, 0x71), key("KeyR", Some(K::R), 0x52, 19, 0x72),
+    key("KeyS", Some(K::S), 0x53, 31, 0x73), key("KeyT", Some(K::T), 0x54, 20, 0x74),
+    key("KeyU", Some(K::U), 0x55, 22, 0x75), key("KeyV", Some(K::V), 0x56, 47, 0x76),
+    key("KeyW", Some(K::W), 0x57, 17, 0x77), key("KeyX", Some(K::X), 0x58, 45, 0x78),
+    key("KeyY", Some(K::Y), 0x59, 21, 0x79), key("KeyZ", Some(K::Z), 0x5A, 44, 0x7A),
+    // Digit row
+    key("Digit0", Some(K::Key0), 0x30, 11, 0x30), key("Digit1", Some(K::Key1), 0x31, 2, 0x31),
+    key("Digit2", Some(K::Key2), 0x32, 3, 0x32), key("Digit3", Some(K::Key3), 0x33, 4, 0x33),
+    key("Digit4", Some(K::Key4), 0x34, 5, 0x34), key("Digit5", Some(K::Key5), 0x35, 6, 0x35),
+    key("Digit6", Some(K::Key6), 0x36, 7, 0x36), key("Digit7", Some(K::Key7), 0x37, 8, 0x37),
+    key("Digit8", Some(K::Key8), 0x38, 9, 0x38), key("Digit9", Some(K::Key9), 0x39, 10, 0x39),
+    // Function keys
+    key("F1", Some(K::F1), 0x70, 59, 0xFFBE), key("F2", Some(K::F2), 0x71, 60, 0xFFBF),
+    key("F3", Some(K::F3), 0x72, 61, 0xFFC0), key("F4", Some(K::F4), 0x73, 62, 0xFFC1),
+    key("F5", Some(K::F5), 0x74, 63, 0xFFC2), key("F6", Some(K::F6), 0x75, 64, 0xFFC3),
+    key("F7", Some(K::F7), 0x76, 65, 0xFFC4), key("F8", Some(K::F8), 0x77, 66, 0xFFC5),
+    key("F9", Some(K::F9), 0x78, 67, 0xFFC6), key("F10", Some(K::F10), 0x79, 68, 0xFFC7),
+    key("F11", Some(K::F11), 0x7A, 87, 0xFFC8), key("F12", Some(K::F12), 0x7B, 88, 0xFFC9),
+    // Modifiers
+    key("ShiftLeft", Some(K::LShift), 0xA0, 42, 0xFFE1), key("ShiftRight", Some(K::RShift), 0xA1, 54, 0xFFE2),
+    key("ControlLeft", Some(K::LControl), 0xA2, 29, 0xFFE3), ext("ControlRight", Some(K::RControl), 0xA3, 97, 0xFFE4),
+    key("AltLeft", Some(K::LAlt), 0xA4, 56, 0xFFE9), ext("AltRight", Some(K::RAlt), 0xA5, 100, 0xFFEA),
+    ext("MetaLeft", Some(K::LMeta), 0x5B, 125, 0xFFEB), ext("MetaRight", Some(K::RMeta), 0x5C, 126, 0xFFEC),
+    // Whitespace and editing
+    key("Space", Some(K::Space), 0x20, 57, 0x20), key("Enter", Some(K::Enter), 0x0D, 28, 0xFF0D),
+    key("Escape", Some(K::Escape), 0x1B, 1, 0xFF1B), key("Backspace", Some(K::Backspace), 0x08, 14, 0xFF08),
+    key("Tab", Some(K::Tab), 0x09, 15, 0xFF09), key("CapsLock", Some(K::CapsLock), 0x14, 58, 0xFFE5),
+    // Navigation
+    ext("Insert", Some(K::Insert), 0x2D, 110, 0xFF63), ext("Delete", Some(K::Delete), 0x2E, 111, 0xFFFF),
+    ext("Home", Some(K::Home), 0x24, 102, 0xFF50), ext("End", Some(K::End), 0x23, 107, 0xFF57),
+    ext("PageUp", Some(K::PageUp), 0x21, 104, 0xFF55), ext("PageDown", Some(K::PageDown), 0x22, 109, 0xFF56),
+    ext("ArrowUp", Some(K::Up), 0x26, 103, 0xFF52), ext("ArrowDown", Some(K::Down), 0x28, 108, 0xFF54),
+    ext("ArrowLeft", Some(K::Left), 0x25, 105, 0xFF51), ext("ArrowRight", Some(K::Right), 0x27, 106, 0xFF53),
+    // Numpad
+    key("Numpad0", Some(K::Numpad0), 0x60, 82, 0xFFB0), key("Numpad1", Some(K::Numpad1), 0x61, 79, 0xFFB1),
+    key("Numpad2", Some(K::Numpad2), 0x62, 80, 0xFFB2), key("Numpad3", Some(K::Numpad3), 0x63, 81, 0xFFB3),
+    key("Numpad4", Some(K::Numpad4), 0x64, 75, 0xFFB4), key("Numpad5", Some(K::Numpad5), 0x65, 76, 0xFFB5),
+    key("Numpad6", Some(K::Numpad6), 0x66, 77, 0xFFB6), key("Numpad7", Some(K::Numpad7), 0x67, 71, 0xFFB7),
+    key("Numpad8", Some(K::Numpad8), 0x68, 72, 0xFFB8), key("Numpad9", Some(K::Numpad9), 0x69, 73, 0xFFB9),
+    key("NumpadMultiply", Some(K::NumpadMultiply), 0x6A, 55, 0xFFAA),
+    key("NumpadAdd", Some(K::NumpadAdd), 0x6B, 78, 0xFFAB),
+    key("NumpadSubtract", Some(K::NumpadSubtract), 0x6D, 74, 0xFFAD),
+    key("NumpadDecimal", Some(K::NumpadDecimal), 0x6E, 83, 0xFFAE),
+    ext("NumpadDivide", Some(K::NumpadDivide), 0x6F, 98, 0xFFAF),
+    ext("NumpadEnter", Some(K::NumpadEnter), 0x0D, 96, 0xFF8D),
+    key("NumLock", None, 0x90, 69, 0xFF7F),
+    // Punctuation
+    key("Backquote", Some(K::Grave), 0xC0, 41, 0x60), key("Minus", Some(K::Minus), 0xBD, 12, 0x2D),
+    key("Equal", Some(K::Equal), 0xBB, 13, 0x3D), key("BracketLeft", Some(K::LeftBracket), 0xDB, 26, 0x5B),
+    key("BracketRight", Some(K::RightBracket), 0xDD, 27, 0x5D), key("Backslash", Some(K::BackSlash), 0xDC, 43, 0x5C),
+    key("Semicolon", Some(K::Semicolon), 0xBA, 39, 0x3B), key("Quote", Some(K::Apostrophe), 0xDE, 40, 0x27),
+    key("Comma", Some(K::Comma), 0xBC, 51, 0x2C), key("Period", Some(K::Dot), 0xBE, 52, 0x2E),
+    key("Slash", Some(K::Slash), 0xBF, 53, 0x2F),
+    // System
+    ext("PrintScreen", None, 0x2C, 99, 0xFF61), key("ScrollLock", None, 0x91, 70, 0xFF14),
+    key("Pause", None, 0x13, 119, 0xFF13), ext("ContextMenu", None, 0x5D, 127, 0xFF67),
+    // Media
+    ext("AudioVolumeMute", None, 0xAD, 113, 0x1008_FF12),
+    ext("AudioVolumeDown", None, 0xAE, 114, 0x1008_FF11),
+    ext("AudioVolumeUp", None, 0xAF, 115, 0x1008_FF13),
+    ext("MediaTrackNext", None, 0xB0, 163, 0x1008_FF17),
+    ext("MediaTrackPrevious", None, 0xB1, 165, 0x1008_FF16),
+    ext("MediaStop", None, 0xB2, 166, 0x1008_FF15),
+    ext("MediaPlayPause", None, 0xB3, 164, 0x1008_FF14),
+    // Mouse buttons
+    button("Mouse3", 3, MouseButton::Right),
+    button("Mouse4", 4, MouseButton::X1),
+    button("Mouse5", 5, MouseButton::X2),
+];
+
+// ═══════════════════════════════════════════════════════════════════════════
+// KEY IDS — validated references into the key table
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A key from the table. Stored and sent to the frontend as its name, e.g.
+/// "KeyQ" or "Mouse4".
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyId(u8);
+
+impl KeyId {
+    pub fn parse(name: &str) -> Result<KeyId, String> {
+        KeyId::all().find(|key| key.name() == name).ok_or_else(|| format!("unknown key '{}'", name))
+    }
+
+    pub fn all() -> impl Iterator<Item = KeyId> {
+        (0..KEYS.len()).map(|i| KeyId(i as u8))
+    }
+
+    fn def(self) -> &'static KeyDef {
+        &KEYS[self.0 as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        self.def().name
+    }
+
+    /// Injection codes, or `None` for the mouse buttons
+    pub fn codes(self) -> Option<KeyCodes> {
+        match self.def().output {
+            Output::Key(codes) => Some(codes),
+            Output::Button(..) => None,
+        }
+    }
+
+    /// The button pressed by "Mouse3".."Mouse5"
+    pub fn button(self) -> Option<MouseButton> {
+        match self.def().output {
+            Output::Button(_, button) => Some(button),
+            Output::Key(_) => None,
+        }
+    }
+
+    /// device_query key reporting this key
+    pub fn keycode(self) -> Option<Keycode> {
+        self.def().detect
+    }
+
+    /// Index into device_query's `button_pressed` for the mouse buttons
+    pub fn button_index(self) -> Option<usize> {
+        match self.def().output {
+            Output::Button(index, _) => Some(index),
+            Output::Key(_) => None,
+        }
+    }
+
+    /// Whether pressing it can be noticed, which hotkeys need
+    pub fn is_detectable(self) -> bool {
+        self.keycode().is_some() || self.button_index().is_some()
+    }
+
+    pub fn from_keycode(keycode: Keycode) -> Option<KeyId> {
+        KeyId::all().find(|key| key.keycode() == Some(keycode))
+    }
+
+    /// The key that binds `button`, if it can be bound
+    pub fn from_button(button: MouseButton) -> Option<KeyId> {
+        KeyId::all().find(|key| key.button() == Some(button))
+    }
+
+    /// The key or mouse button a Linux input event code reports
+    pub fn from_evdev(code: u16) -> Option<KeyId> {
+        KeyId::all().find(|key| match key.def().output {
+            Output::Key(codes) => codes.evdev == code,
+            Output::Button(_, button) => button.evdev() == code,
+        })
+    }
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Debug for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for KeyId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<KeyId, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        KeyId::parse(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse a hotkey setting, where "" means unbound
+pub fn parse_hotkey(name: &str) -> Result<Option<KeyId>, String> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+    let key = KeyId::parse(name)?;
+    if !key.is_detectable() {
+        return Err(format!("key '{}' cannot be detected as a hotkey", name));
+    }
+    Ok(Some(key))
+}
+
+/// Every detectable key currently down, from one device_query snapshot
+pub fn pressed(keys: &[Keycode], mouse_buttons: &[bool]) -> Vec<KeyId> {
+    KeyId::all()
+        .filter(|key| match (key.keycode(), key.button_index()) {
+            (Some(kc), _) => keys.contains(&kc),
+            (None, Some(i)) => mouse_buttons.get(i).copied().unwrap_or(false),
+            (None, None) => false,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Every value `column` gives is unique, so it maps back to one key
+    fn assert_unique<T: Eq + std::hash::Hash + fmt::Debug>(column: impl Fn(KeyId) -> Option<T>) {
+        let mut seen = HashSet::new();
+        for key in KeyId::all() {
+            if let Some(value) = column(key) {
+                assert!(seen.insert(value), "{} shares its code with another key", key);
+            }
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        assert!(KEYS.len() <= u8::MAX as usize);
+        for key in KeyId::all() {
+            assert_eq!(KeyId::parse(key.name()), Ok(key));
+            let json = serde_json::to_string(&key).unwrap();
+            assert_eq!(serde_json::from_str::<KeyId>(&json).unwrap(), key);
+        }
+    }
+
+    #[test]
+    fn detection_round_trips() {
+        for key in KeyId::all() {
+            if let Some(keycode) = key.keycode() {
+                assert_eq!(KeyId::from_keycode(keycode), Some(key));
+            }
+            let evdev = key.codes().map(|c| c.evdev).or(key.button().map(MouseButton::evdev));
+            assert_eq!(evdev.and_then(KeyId::from_evdev), Some(key));
+        }
+        assert_unique(|key| key.button_index());
+    }
+
+    #[test]
+    fn every_backend_code_identifies_one_key() {
+        // Windows tells numpad Enter from Enter by the extended flag
+        assert_unique(|key| key.codes().map(|c| (c.vk, c.extended)));
+        assert_unique(|key| key.codes().map(|c| c.evdev));
+        assert_unique(|key| key.codes().map(|c| c.keysym));
+        for key in KeyId::all() {
+            assert!(key.codes().is_some() != key.button().is_some(), "{} must be a key or a button", key);
+        }
+    }
+
+    #[test]
+    fn left_and_right_modifiers_are_distinct() {
+        let vk = |name| KeyId::parse(name).unwrap().codes().unwrap().vk;
+        assert_ne!(vk("ShiftLeft"), vk("ShiftRight"));
+        assert_ne!(vk("ControlLeft"), vk("ControlRight"));
+        assert_ne!(vk("AltLeft"), vk("AltRight"));
+    }
+
+    #[test]
+    fn hotkeys_must_be_detectable() {
+        assert_eq!(parse_hotkey(""), Ok(None));
+        assert_eq!(parse_hotkey("ArrowUp").unwrap().map(KeyId::name), Some("ArrowUp"));
+        assert_eq!(parse_hotkey("keyq"), Err("unknown key 'keyq'".into()));
+        assert!(parse_hotkey("AudioVolumeUp").unwrap_err().contains("cannot be detected"));
+
+        let err = serde_json::from_str::<KeyId>("\"Hyper7\"").unwrap_err();
+        assert!(err.to_string().contains("unknown key 'Hyper7'"), "{}", err);
+    }
+}
@@ -0,0 +1,333 @@
+//! Keys and buttons read from the `/dev/input/event*` devices.
+//!
+//! Every device that reports keys or buttons is opened non-blocking and
+//! waited on with `poll`, together with an inotify watch on `/dev/input` so
+//! that keyboards and mice plugged in later are picked up. Reading them
+//! usually needs membership of the `input` group. Our own uinput devices are
+//! skipped, so nothing the app sends is taken for a hotkey.
+
+use super::KeySource;
+use crate::input::{ioctl, VIRTUAL_DEVICE_PREFIX};
+use crate::keys::KeyId;
+use std::collections::{HashSet, VecDeque};
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::time::{Duration, Instant};
+
+const INPUT_DIR: &str = "/dev/input";
+
+// linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const SYN_REPORT: u16 = 0;
+const SYN_DROPPED: u16 = 3;
+const KEY_CNT: usize = 0x300;
+
+/// `_IOC(_IOC_READ, 'E', nr, len)` from linux/input.h
+const fn eviocg(nr: u32, len: usize) -> libc::c_ulong {
+    ((2 << 30) | ((len as u32) << 16) | ((b'E' as u32) << 8) | nr) as libc::c_ulong
+}
+
+/// Read a device property into `buf`
+fn ioctl_read(file: &File, nr: u32, buf: &mut [u8]) -> io::Result<()> {
+    ioctl(file, eviocg(nr, buf.len()), buf.as_mut_ptr() as libc::c_ulong)
+}
+
+/// What one event did to a device's held keys
+#[derive(Debug, PartialEq)]
+enum Change {
+    Unchanged,
+    Held,
+    /// Events were lost; the held keys must be read from the device again
+    Resync,
+}
+
+/// Keys held on one device, kept up to date from its events
+#[derive(Default)]
+struct HeldKeys {
+    keys: HashSet<KeyId>,
+    /// The kernel dropped events; ignore the rest of the report
+    dropped: bool,
+}
+
+impl HeldKeys {
+    fn apply(&mut self, kind: u16, code: u16, value: i32) -> Change {
+        match (kind, code) {
+            (EV_SYN, SYN_DROPPED) => {
+                self.dropped = true;
+                Change::Unchanged
+            }
+            (EV_SYN, SYN_REPORT) if self.dropped => {
+                self.dropped = false;
+                Change::Resync
+            }
+            // Value 2 is autorepeat, which changes nothing
+            (EV_KEY, _) if !self.dropped && value != 2 => match KeyId::from_evdev(code) {
+                Some(key) if value == 1 && self.keys.insert(key) => Change::Held,
+                Some(key) if value == 0 && self.keys.remove(&key) => Change::Held,
+                _ => Change::Unchanged,
+            },
+            _ => Change::Unchanged,
+        }
+    }
+}
+
+/// Keys set in an `EVIOCGKEY` bitmap
+fn keys_in(bits: &[u8]) -> HashSet<KeyId> {
+    (0..bits.len() * 8)
+        .filter(|&code| bits[code / 8] & (1 << (code % 8)) != 0)
+        .filter_map(|code| KeyId::from_evdev(code as u16))
+        .collect()
+}
+
+struct Device {
+    path: PathBuf,
+    file: File,
+    held: HeldKeys,
+}
+
+impl Device {
+    /// Open an event device, or `None` if it has no keys or buttons (a lid
+    /// switch, an accelerometer) or is one of ours
+    fn open(path: &Path) -> io::Result<Option<Device>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_CLOEXEC)
+            .open(path)?;
+        let mut types = [0u8; 4];
+        ioctl_read(&file, 0x20, &mut types)?;
+        if types[0] & (1 << EV_KEY) == 0 {
+            return Ok(None);
+        }
+        let mut name = [0u8; 256];
+        ioctl_read(&file, 0x06, &mut name)?;
+        let name = String::from_utf8_lossy(name.split(|&b| b == 0).next().unwrap_or_default()).into_owned();
+        if name.starts_with(VIRTUAL_DEVICE_PREFIX) {
+            return Ok(None);
+        }
+        println!("Hotkeys: reading {} ({})", path.display(), name);
+
+        let mut device = Device { path: path.to_owned(), file, held: HeldKeys::default() };
+        device.resync()?;
+        Ok(Some(device))
+    }
+
+    /// Replace the held keys with what the device reports now
+    fn resync(&mut self) -> io::Result<()> {
+        let mut bits = [0u8; KEY_CNT / 8];
+        ioctl_read(&self.file, 0x18, &mut bits)?;
+        self.held.keys = keys_in(&bits);
+        Ok(())
+    }
+}
+
+/// `/dev/input` key source.
+pub struct EvdevSource {
+    inotify: File,
+    devices: Vec<Device>,
+    /// Held keys after each change read but not yet returned
+    pending: VecDeque<Vec<KeyId>>,
+}
+
+impl EvdevSource {
+    pub fn open() -> io::Result<EvdevSource> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let inotify = unsafe { File::from_raw_fd(fd) };
+        // udev sets the permissions after creating the node, hence IN_ATTRIB
+        let dir = CString::new(INPUT_DIR).unwrap();
+        if unsafe { libc::inotify_add_watch(fd, dir.as_ptr(), libc::IN_CREATE | libc::IN_ATTRIB) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut source = EvdevSource { inotify, devices: Vec::new(), pending: VecDeque::new() };
+        let failed = source.scan();
+        if source.devices.is_empty() {
+            return Err(failed.unwrap_or_else(|| io::Error::other("no keyboard or mouse found")));
+        }
+        Ok(source)
+    }
+
+    /// Open the event devices not open yet, returning the last failure
+    fn scan(&mut self) -> Option<io::Error> {
+        let mut failed = None;
+        let entries = match fs::read_dir(INPUT_DIR) {
+            Ok(entries) => entries,
+            Err(e) => return Some(e),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_event = entry.file_name().to_string_lossy().starts_with("event");
+            if !is_event || self.devices.iter().any(|d| d.path == path) {
+                continue;
+            }
+            match Device::open(&path) {
+                Ok(Some(device)) => {
+                    let had_keys = !device.held.keys.is_empty();
+                    self.devices.push(device);
+                    if had_keys {
+                        self.changed();
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => failed = Some(e),
+            }
+        }
+        failed
+    }
+
+    /// Every key held on any device
+    fn held(&self) -> Vec<KeyId> {
+        KeyId::all().filter(|key| self.devices.iter().any(|d| d.held.keys.contains(key))).collect()
+    }
+
+    fn changed(&mut self) {
+        let held = self.held();
+        self.pending.push_back(held);
+    }
+
+    /// Wait up to `timeout` for events and read them. False on timeout.
+    fn read_events(&mut self, timeout: Option<Duration>) -> bool {
+        let mut fds: Vec<libc::pollfd> = std::iter::once(&self.inotify)
+            .chain(self.devices.iter().map(|d| &d.file))
+            .map(|file| libc::pollfd { fd: file.as_raw_fd(), events: libc::POLLIN, revents: 0 })
+            .collect();
+        let ms = timeout.map_or(-1, |t| t.as_millis().min(i32::MAX as u128) as libc::c_int);
+        if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, ms) } <= 0 {
+            return false;
+        }
+
+        // Devices first: a device that went away reports an error here
+        let mut gone = Vec::new();
+        for (i, fd) in fds.iter().enumerate().skip(1) {
+            if fd.revents != 0 && self.read_device(i - 1).is_err() {
+                gone.push(i - 1);
+            }
+        }
+        for &i in gone.iter().rev() {
+            let device = self.devices.remove(i);
+            println!("Hotkeys: {} removed", device.path.display());
+            if !device.held.keys.is_empty() {
+                self.changed();
+            }
+        }
+
+        if fds[0].revents != 0 {
+            let mut buf = [0u8; 4096];
+            while matches!((&self.inotify).read(&mut buf), Ok(n) if n > 0) {}
+            self.scan();
+        }
+        true
+    }
+
+    fn read_device(&mut self, i: usize) -> io::Result<()> {
+        const SIZE: usize = mem::size_of::<libc::input_event>();
+        let mut buf = [0u8; SIZE * 64];
+        loop {
+            let n = match self.devices[i].file.read(&mut buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            for chunk in buf[..n].chunks_exact(SIZE) {
+                let ev: libc::input_event = unsafe { ptr::read_unaligned(chunk.as_ptr().cast()) };
+                match self.devices[i].held.apply(ev.type_, ev.code, ev.value) {
+                    Change::Unchanged => {}
+                    Change::Held => self.changed(),
+                    Change::Resync => {
+                        self.devices[i].resync()?;
+                        self.changed();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl KeySource for EvdevSource {
+    fn name(&self) -> &'static str { "evdev" }
+
+    fn next(&mut self, timeout: Option<Duration>) -> Vec<KeyId> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        // Reads can bring nothing of interest (mouse motion, autorepeat)
+        while self.pending.is_empty() {
+            let left = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if !self.read_events(left) {
+                break;
+            }
+        }
+        self.pending.pop_front().unwrap_or_else(|| self.held())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: u16 = 30;
+    const KEY_LEFTCTRL: u16 = 29;
+    const BTN_SIDE: u16 = 0x113;
+
+    /// Feed (type, code, value) events, returning what each one changed
+    fn run(held: &mut HeldKeys, events: &[(u16, u16, i32)]) -> Vec<Change> {
+        events.iter().map(|&(kind, code, value)| held.apply(kind, code, value)).collect()
+    }
+
+    fn names(held: &HeldKeys) -> Vec<&'static str> {
+        let mut names: Vec<_> = held.keys.iter().map(|k| k.name()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn tracks_presses_and_releases() {
+        let mut held = HeldKeys::default();
+        let changes = run(&mut held, &[
+            (EV_KEY, KEY_LEFTCTRL, 1),
+            (EV_SYN, SYN_REPORT, 0),
+            (EV_KEY, KEY_A, 1),
+            (EV_KEY, KEY_A, 2),
+            (EV_KEY, BTN_SIDE, 1),
+            // Motion and unknown keys are not hotkeys
+            (0x02, 0x00, 5),
+            (EV_KEY, 0x2ff, 1),
+        ]);
+        use Change::{Held, Unchanged};
+        assert_eq!(changes, vec![Held, Unchanged, Held, Unchanged, Held, Unchanged, Unchanged]);
+        assert_eq!(names(&held), vec!["ControlLeft", "KeyA", "Mouse4"]);
+
+        assert_eq!(run(&mut held, &[(EV_KEY, KEY_A, 0), (EV_KEY, KEY_A, 0)]), vec![Held, Unchanged]);
+        assert_eq!(names(&held), vec!["ControlLeft", "Mouse4"]);
+    }
+
+    #[test]
+    fn resyncs_after_dropped_events() {
+        let mut held = HeldKeys::default();
+        let changes = run(&mut held, &[
+            (EV_SYN, SYN_DROPPED, 0),
+            (EV_KEY, KEY_A, 1),
+            (EV_SYN, SYN_REPORT, 0),
+            (EV_KEY, KEY_A, 1),
+        ]);
+        assert_eq!(changes, vec![Change::Unchanged, Change::Unchanged, Change::Resync, Change::Held]);
+    }
+
+    #[test]
+    fn reads_held_keys_from_the_bitmap() {
+        let mut bits = [0u8; KEY_CNT / 8];
+        for code in [KEY_A, KEY_LEFTCTRL, BTN_SIDE, 0x2ff] {
+            bits[code as usize / 8] |= 1 << (code % 8);
+        }
+        let held = HeldKeys { keys: keys_in(&bits), dropped: false };
+        assert_eq!(names(&held), vec!["ControlLeft", "KeyA", "Mouse4"]);
+    }
+}
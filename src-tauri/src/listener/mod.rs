@@ -0,0 +1,62 @@
+use crate::input::Injected;
+use crate::keys::{self, KeyId};
+use device_query::{DeviceQuery, DeviceState};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+mod evdev;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// KEY SOURCES — what the user holds down, for the hotkey listener
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// On Linux the keyboards and mice are read directly, so the listener sleeps
+// until something changes and sees every press, however short. Elsewhere,
+// and when /dev/input cannot be read, device_query is polled instead.
+//
+// Neither may report what the app itself presses. The evdev source skips our
+// uinput devices; polling cannot tell injected input apart, so it leaves out
+// whatever the backend holds, as noted in `Injected`.
+
+/// How often the polling source samples
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub trait KeySource {
+    /// Short human-readable name, used in logs.
+    fn name(&self) -> &'static str;
+
+    /// Wait for the next key change, or at most `timeout`, and return every
+    /// key then held. Changes are returned one at a time, so a tap between
+    /// two calls still shows up as a press and a release.
+    fn next(&mut self, timeout: Option<Duration>) -> Vec<KeyId>;
+}
+
+/// Samples device_query every `POLL_INTERVAL`, whatever the timeout
+pub struct PollingSource {
+    device_state: DeviceState,
+    injected: Arc<Injected>,
+}
+
+impl KeySource for PollingSource {
+    fn name(&self) -> &'static str { "polling" }
+
+    fn next(&mut self, _timeout: Option<Duration>) -> Vec<KeyId> {
+        thread::sleep(POLL_INTERVAL);
+        let down = keys::pressed(&self.device_state.get_keys(), &self.device_state.get_mouse().button_pressed);
+        self.injected.physical(down, Instant::now())
+    }
+}
+
+/// Pick the best available key source for the current platform.
+pub fn default_source(injected: Arc<Injected>) -> Box<dyn KeySource> {
+    #[cfg(target_os = "linux")]
+    {
+        match evdev::EvdevSource::open() {
+            Ok(source) => return Box::new(source),
+            Err(e) => println!("Hotkeys: cannot read /dev/input ({}), polling instead", e),
+        }
+    }
+    Box::new(PollingSource { device_state: DeviceState::new(), injected })
+}
@@ -0,0 +1,422 @@
+use crate::actions::{click_button, drag_mouse, hold_key, release_key, tap_key};
+use crate::automation::ScriptBinding;
+use crate::cancel::CancelToken;
+use crate::failsafe::FailsafeConfig;
+use crate::hotkeys::Hotkey;
+use crate::input::{GuardedInput, InputBackend, MouseButton};
+use crate::keys::KeyId;
+use crate::script;
+use crate::triggers::Trigger;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MACRO DEFINITIONS
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn default_hold_ms() -> u64 {
+    30
+}
+
+/// One instruction of a macro. Any `KeyId` works as a key, including the
+/// "Mouse3".."Mouse5" buttons.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MacroStep {
+    /// Press a key and keep it held
+    KeyDown { key: KeyId },
+    /// Release a held key
+    KeyUp { key: KeyId },
+    /// Press and release a key
+    KeyTap {
+        key: KeyId,
+        #[serde(default = "default_hold_ms")]
+        hold_ms: u64,
+    },
+    MouseDown { button: MouseButton },
+    MouseUp { button: MouseButton },
+    /// Press and release a mouse button
+    Click {
+        button: MouseButton,
+        #[serde(default = "default_hold_ms")]
+        hold_ms: u64,
+    },
+    /// Move the cursor to (x, y), or by (x, y) when `relative`
+    Move {
+        x: i32,
+        y: i32,
+        #[serde(default)]
+        relative: bool,
+    },
+    /// Drag with the left button held from one position to another
+    Drag { from: (i32, i32), to: (i32, i32) },
+    /// Turn the wheel by whole notches (positive = away from the user)
+    Scroll { notches: i32 },
+    Wait { ms: u64 },
+    /// Run the nested steps `times` times
+    Repeat { times: u32, steps: Vec<MacroStep> },
+    /// Run a macro script (see `script`)
+    Script { source: String },
+}
+
+/// A named step sequence started by its own hotkey.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    #[serde(with = "crate::hotkeys::binding")]
+    pub hotkey: Option<Hotkey>,
+    /// Hold runs the macro until the hotkey is released
+    #[serde(default)]
+    pub trigger: Trigger,
+    pub steps: Vec<MacroStep>,
+}
+
+fn default_record_key() -> Option<Hotkey> {
+    Hotkey::parse("F10").ok()
+}
+
+fn default_panic_key() -> Option<Hotkey> {
+    Hotkey::parse("F12").ok()
+}
+
+fn default_script_time_limit_ms() -> u64 {
+    60_000
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroConfig {
+    pub macros: Vec<Macro>,
+    /// Starts and stops the input recorder
+    #[serde(default = "default_record_key", with = "crate::hotkeys::binding")]
+    pub record_key: Option<Hotkey>,
+    /// Recorded pauses longer than this are shortened to it (0 = keep as is)
+    #[serde(default)]
+    pub record_max_gap_ms: u64,
+    /// Emergency stop: halts the clicker and cancels the running macro
+    #[serde(default = "default_panic_key", with = "crate::hotkeys::binding")]
+    pub panic_key: Option<Hotkey>,
+    /// Cursor-in-corner abort
+    #[serde(default)]
+    pub failsafe: FailsafeConfig,
+    /// Rhai scripts from the config directory bound to hotkeys
+    #[serde(default)]
+    pub scripts: Vec<ScriptBinding>,
+    /// Scripts still running after this long are stopped
+    #[serde(default = "default_script_time_limit_ms")]
+    pub script_time_limit_ms: u64,
+}
+
+impl Default for MacroConfig {
+    fn default() -> MacroConfig {
+        MacroConfig {
+            macros: Vec::new(),
+            record_key: default_record_key(),
+            record_max_gap_ms: 0,
+            panic_key: default_panic_key(),
+            failsafe: FailsafeConfig::default(),
+            scripts: Vec::new(),
+            script_time_limit_ms: default_script_time_limit_ms(),
+        }
+    }
+}
+
+impl Macro {
+    /// Check the trigger and the script steps, which otherwise only parse
+    /// when run
+    pub fn validate(&self) -> Result<(), String> {
+        self.trigger.validate()?;
+        check_steps(&self.steps)
+    }
+}
+
+fn check_steps(steps: &[MacroStep]) -> Result<(), String> {
+    for (i, step) in steps.iter().enumerate() {
+        let result = match step {
+            MacroStep::Repeat { steps, .. } => check_steps(steps),
+            MacroStep::Script { source } => script::parse(source).map(|_| ()).map_err(|e| e.to_string()),
+            _ => Ok(()),
+        };
+        result.map_err(|e| format!("step {}: {}", i + 1, e))?;
+    }
+    Ok(())
+}
+
+impl MacroConfig {
+    /// Check values coming from the frontend or the settings file before
+    /// they are applied
+    pub fn validate(&self) -> Result<(), String> {
+        if self.script_time_limit_ms == 0 {
+            return Err("script time limit must be more than 0 ms".into());
+        }
+        for (i, m) in self.macros.iter().enumerate() {
+            if m.name.trim().is_empty() {
+                return Err("every macro needs a name".into());
+            }
+            if self.macros[..i].iter().any(|other| other.name == m.name) {
+                return Err(format!("there are two macros named '{}'", m.name));
+            }
+            m.validate().map_err(|e| format!("macro '{}': {}", m.name, e))?;
+        }
+        for s in &self.scripts {
+            s.trigger.validate().map_err(|e| format!("script '{}': {}", s.file, e))?;
+        }
+        Ok(())
+    }
+
+    /// "`prefix` N" with the lowest N no macro is named yet
+    pub fn unused_name(&self, prefix: &str) -> String {
+        (1..)
+            .map(|n| format!("{} {}", prefix, n))
+            .find(|name| self.macros.iter().all(|m| &m.name != name))
+            .expect("some number is free")
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MACRO EXECUTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Run a macro until it finishes or `cancel` is set. Anything the macro
+/// still holds at that point is released.
+pub fn run_macro(input: Arc<dyn InputBackend>, m: &Macro, cancel: &CancelToken) {
+    println!("Macro '{}': executing", m.name);
+    let guarded = GuardedInput::new(input, cancel.clone());
+    run_steps(&guarded, &m.steps, cancel);
+    guarded.release_all();
+    if cancel.is_cancelled() {
+        println!("Macro '{}': cancelled", m.name);
+    } else {
+        println!("Macro '{}': done", m.name);
+    }
+}
+
+pub fn run_steps(input: &dyn InputBackend, steps: &[MacroStep], cancel: &CancelToken) {
+    for step in steps {
+        if cancel.is_cancelled() {
+            return;
+        }
+        run_step(input, step, cancel);
+    }
+}
+
+fn run_step(input: &dyn InputBackend, step: &MacroStep, cancel: &CancelToken) {
+    match step {
+        MacroStep::KeyDown { key } => hold_key(input, *key),
+        MacroStep::KeyUp { key } => release_key(input, *key),
+        MacroStep::KeyTap { key, hold_ms } => tap_key(input, *key, *hold_ms),
+        MacroStep::MouseDown { button } => input.mouse_down(*button),
+        MacroStep::MouseUp { button } => input.mouse_up(*button),
+        MacroStep::Click { button, hold_ms } => click_button(input, *button, *hold_ms),
+        MacroStep::Move { x, y, relative: false } => input.move_absolute(*x, *y),
+        MacroStep::Move { x, y, relative: true } => input.move_relative(*x, *y),
+        MacroStep::Drag { from, to } => drag_mouse(input, *from, *to),
+        MacroStep::Scroll { notches } => input.scroll(*notches),
+        MacroStep::Wait { ms } => input.sleep(Duration::from_millis(*ms)),
+        MacroStep::Repeat { times, steps } => {
+            for _ in 0..*times {
+                if cancel.is_cancelled() {
+                    break;
+                }
+                run_steps(input, steps, cancel);
+            }
+        }
+        MacroStep::Script { source } => {
+            if let Err(e) = script::run_source(input, source, cancel) {
+                if !cancel.is_cancelled() {
+                    println!("Script error: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent::*, MockBackend};
+    use crate::input::MouseButton::{Left, Right};
+
+    fn key(name: &str) -> KeyId {
+        KeyId::parse(name).unwrap()
+    }
+
+    fn key_tap(name: &str) -> MacroStep {
+        MacroStep::KeyTap { key: key(name), hold_ms: 30 }
+    }
+
+    fn wait(ms: u64) -> MacroStep {
+        MacroStep::Wait { ms }
+    }
+
+    /// The snap-hook "part 1" sequence that used to be hard-coded
+    fn safe_pocket_to_quick_use() -> Macro {
+        Macro {
+            name: "Part 1".into(),
+            hotkey: Hotkey::parse("F7").ok(),
+            trigger: Trigger::Press,
+            steps: vec![
+                key_tap("Tab"),
+                wait(50),
+                MacroStep::Drag { from: (100, 200), to: (300, 400) },
+                wait(50),
+                key_tap("Tab"),
+                wait(50),
+                MacroStep::KeyDown { key: key("KeyQ") },
+                wait(300),
+                key_tap("Digit6"),
+                wait(50),
+                MacroStep::KeyUp { key: key("KeyQ") },
+            ],
+        }
+    }
+
+    /// Drop the interpolated drag moves, which `actions` tests cover
+    fn without_drag_steps(timeline: Vec<(u64, crate::input::mock::InputEvent)>) -> Vec<(u64, crate::input::mock::InputEvent)> {
+        let mut out = Vec::new();
+        let mut dragging = false;
+        for (t, e) in timeline {
+            match e {
+                MouseDown(Left) => dragging = true,
+                MouseUp(Left) => dragging = false,
+                MoveAbsolute(..) if dragging => continue,
+                _ => {}
+            }
+            out.push((t, e));
+        }
+        out
+    }
+
+    #[test]
+    fn runs_steps_in_order_with_their_timing() {
+        let mock = Arc::new(MockBackend::new());
+        run_macro(mock.clone(), &safe_pocket_to_quick_use(), &CancelToken::new());
+
+        assert_eq!(without_drag_steps(mock.timeline()), vec![
+            (0, KeyDown(0x09)),
+            (30, KeyUp(0x09)),
+            (80, MoveAbsolute(100, 200)),
+            (110, MouseDown(Left)),
+            (245, MouseUp(Left)),
+            (295, KeyDown(0x09)),
+            (325, KeyUp(0x09)),
+            (375, KeyDown(0x51)),
+            (675, KeyDown(0x36)),
+            (705, KeyUp(0x36)),
+            (755, KeyUp(0x51)),
+        ]);
+        assert!(mock.events().contains(&MoveAbsolute(300, 400)));
+    }
+
+    #[test]
+    fn repeat_runs_nested_steps() {
+        let mock = MockBackend::new();
+        run_steps(&mock, &[MacroStep::Repeat {
+            times: 3,
+            steps: vec![MacroStep::Click { button: Right, hold_ms: 10 }, wait(90)],
+        }], &CancelToken::new());
+
+        assert_eq!(mock.timeline(), vec![
+            (0, MouseDown(Right)), (10, MouseUp(Right)),
+            (100, MouseDown(Right)), (110, MouseUp(Right)),
+            (200, MouseDown(Right)), (210, MouseUp(Right)),
+        ]);
+        assert_eq!(mock.now(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn pointer_steps_move_and_scroll() {
+        let mock = MockBackend::new();
+        run_steps(&mock, &[
+            MacroStep::Move { x: 10, y: 20, relative: false },
+            MacroStep::Move { x: -5, y: 5, relative: true },
+            MacroStep::Scroll { notches: -3 },
+        ], &CancelToken::new());
+
+        assert_eq!(mock.events(), vec![MoveAbsolute(10, 20), MoveRelative(-5, 5), Scroll(-3)]);
+    }
+
+    #[test]
+    fn cancel_stops_mid_sequence_and_releases_held_keys() {
+        /// Cancels the run once the virtual clock passes `at`
+        struct CancelAt {
+            mock: MockBackend,
+            at: Duration,
+            cancel: CancelToken,
+        }
+        impl InputBackend for CancelAt {
+            fn name(&self) -> &'static str { "cancel-at" }
+            fn mouse_down(&self, b: MouseButton) { self.mock.mouse_down(b) }
+            fn mouse_up(&self, b: MouseButton) { self.mock.mouse_up(b) }
+            fn move_absolute(&self, x: i32, y: i32) { self.mock.move_absolute(x, y) }
+            fn move_relative(&self, x: i32, y: i32) { self.mock.move_relative(x, y) }
+            fn key_down(&self, key: KeyId) { self.mock.key_down(key) }
+            fn key_up(&self, key: KeyId) { self.mock.key_up(key) }
+            fn scroll(&self, n: i32) { self.mock.scroll(n) }
+            fn sleep(&self, d: Duration) {
+                self.mock.sleep(d);
+                if self.mock.now() >= self.at {
+                    self.cancel.cancel();
+                }
+            }
+        }
+
+        // Cancel while part 1 holds Q during its 300 ms wait (375..675 ms)
+        let cancel = CancelToken::new();
+        let input = Arc::new(CancelAt { mock: MockBackend::new(), at: Duration::from_millis(505), cancel: cancel.clone() });
+        run_macro(input.clone(), &safe_pocket_to_quick_use(), &cancel);
+
+        let timeline = input.mock.timeline();
+        assert_eq!(timeline.last(), Some(&(505, KeyUp(0x51))));
+        assert!(!timeline.iter().any(|(_, e)| *e == KeyDown(0x36)), "steps after the cancel must not run");
+        assert_eq!(input.mock.now(), Duration::from_millis(505));
+    }
+
+    #[test]
+    fn cancelled_token_runs_nothing() {
+        let mock = Arc::new(MockBackend::new());
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        run_macro(mock.clone(), &safe_pocket_to_quick_use(), &cancel);
+        assert!(mock.events().is_empty());
+    }
+
+    #[test]
+    fn steps_round_trip_through_json() {
+        let json = r#"[
+            {"type": "keyTap", "key": "KeyE"},
+            {"type": "click", "button": "x1", "holdMs": 15},
+            {"type": "move", "x": 1, "y": 2},
+            {"type": "drag", "from": [1, 2], "to": [3, 4]},
+            {"type": "repeat", "times": 2, "steps": [{"type": "wait", "ms": 5}]}
+        ]"#;
+        let steps: Vec<MacroStep> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(steps, vec![
+            key_tap("KeyE"),
+            MacroStep::Click { button: MouseButton::X1, hold_ms: 15 },
+            MacroStep::Move { x: 1, y: 2, relative: false },
+            MacroStep::Drag { from: (1, 2), to: (3, 4) },
+            MacroStep::Repeat { times: 2, steps: vec![wait(5)] },
+        ]);
+        let back: Vec<MacroStep> = serde_json::from_str(&serde_json::to_string(&steps).unwrap()).unwrap();
+        assert_eq!(back, steps);
+    }
+
+    #[test]
+    fn validate_needs_unique_names_and_scripts_that_parse() {
+        let config = |macros: Vec<Macro>| MacroConfig { macros, ..MacroConfig::default() };
+        let named = |name: &str| Macro { name: name.into(), ..safe_pocket_to_quick_use() };
+        assert!(config(vec![named("A"), named("B")]).validate().is_ok());
+        assert_eq!(config(vec![named("A"), named("A")]).validate(), Err("there are two macros named 'A'".into()));
+        assert_eq!(config(vec![named(" ")]).validate(), Err("every macro needs a name".into()));
+
+        let mut broken = named("Broken");
+        broken.steps = vec![wait(5), MacroStep::Repeat { times: 2, steps: vec![MacroStep::Script { source: "(".into() }] }];
+        let err = config(vec![broken]).validate().unwrap_err();
+        assert!(err.starts_with("macro 'Broken': step 2: step 1: line 1"), "{}", err);
+
+        assert_eq!(config(vec![named("Recording 1")]).unused_name("Recording"), "Recording 2");
+    }
+}
@@ -1,480 +1,326 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use device_query::{DeviceQuery, DeviceState, Keycode};
+mod actions;
+mod automation;
+mod bindings;
+mod bundle;
+mod cancel;
+mod clicker;
+mod config;
+mod failsafe;
+mod focus;
+mod hotkeys;
+mod input;
+mod keys;
+mod listener;
+mod macros;
+mod profiles;
+mod recorder;
+mod reload;
+mod script;
+mod triggers;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use device_query::{DeviceQuery, DeviceState};
 use std::collections::HashMap;
+use std::panic;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{Emitter, Manager, State, AppHandle};
-use windows::Win32::UI::Input::KeyboardAndMouse::{
-    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYBD_EVENT_FLAGS,
-    KEYEVENTF_KEYUP, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
-    MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_XDOWN,
-    MOUSEEVENTF_XUP, MOUSEINPUT, VIRTUAL_KEY,
-};
-use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
-use rand::Rng;
-use serde::{Serialize, Deserialize};
+use serde::Serialize;
+use automation::InputState;
+use bindings::UpdateError;
+use cancel::CancelToken;
+use clicker::{ClickMode, ClickerState};
+use config::PersistentConfig;
+use failsafe::{Bounds, FailsafeWatcher};
+use focus::{AutoProfileRule, AutoSwitcher};
+use hotkeys::Hotkey;
+use input::{InputBackend, Injected, TrackedBackend};
+use keys::KeyId;
+use macros::{Macro, MacroConfig};
+use profiles::ProfileStore;
+use recorder::Recorder;
+use triggers::{Trigger, TriggerEvent, TriggerState};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // STATE
 // ═══════════════════════════════════════════════════════════════════════════
 
-#[derive(Clone, Serialize, Deserialize)]
-struct ClickerState {
-    running: bool,
-    cps: u64,
-    randomness: u64,
-    humanization_enabled: bool,
-    toggle_key: String,
-    click_mode: String,
-}
-
-#[derive(Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct MacroConfig {
-    part1_key: String,
-    part2_key: String,
-    dodge_key: String,
-    safe_pocket_x: i32,
-    safe_pocket_y: i32,
-    quick_use_x: i32,
-    quick_use_y: i32,
-    delay_ms: u64,
+fn get_config_path(app: &AppHandle) -> PathBuf {
+    app.path().app_config_dir().unwrap().join("settings.json")
 }
 
-#[derive(Serialize, Deserialize)]
-struct PersistentConfig {
-    clicker: ClickerState,
-    macro_config: MacroConfig,
+/// Save every profile, with the live settings as the active one
+fn save_config(
+    app: &AppHandle, profiles: &Mutex<ProfileStore>, clicker: &ClickerState, macro_config: &MacroConfig,
+) -> Result<(), String> {
+    let mut store = profiles.lock().unwrap();
+    store.store_active(clicker, macro_config);
+    let cfg = PersistentConfig::new(store.clone());
+    // Lets the hot reload watcher recognise this write as ours
+    *app.state::<AppState>().saved_config.lock().unwrap() = Some(config::to_json(&cfg));
+    config::save(&get_config_path(app), &cfg).inspect_err(|e| println!("Could not save config: {}", e))
 }
 
-fn get_config_path(app: &AppHandle) -> PathBuf {
-    app.path().app_config_dir().unwrap().join("settings.json")
+/// Check the hotkeys settings would have once applied, in every profile.
+/// Duplicates fail the update; overlaps are logged and sent to the frontend
+/// as warnings.
+fn check_bindings(
+    app: &AppHandle, clicker: &ClickerState, macro_config: &MacroConfig, profiles: &ProfileStore,
+) -> Result<(), UpdateError> {
+    let overlaps = bindings::check_store(clicker, macro_config, profiles)?;
+    for overlap in &overlaps {
+        println!("Hotkey overlap: {}", overlap.message);
+    }
+    let _ = app.emit("binding-warnings", overlaps);
+    Ok(())
 }
 
-fn save_config(app: &AppHandle, clicker: &ClickerState, macro_config: &MacroConfig) {
-    let cfg = PersistentConfig {
-        clicker: clicker.clone(),
-        macro_config: macro_config.clone(),
-    };
-    if let Ok(json) = serde_json::to_string_pretty(&cfg) {
-        let path = get_config_path(app);
-        if let Some(parent) = path.parent() {
-            let _ = fs::create_dir_all(parent);
-        }
-        let _ = fs::write(path, json);
-    }
+fn get_scripts_dir(app: &AppHandle) -> PathBuf {
+    app.path().app_config_dir().unwrap().join("scripts")
 }
 
 struct AppState {
     clicker: Arc<Mutex<ClickerState>>,
     macro_config: Arc<Mutex<MacroConfig>>,
-    #[allow(dead_code)]
+    /// All profiles; the active one's settings are `clicker`/`macro_config`
+    profiles: Arc<Mutex<ProfileStore>>,
     macro_running: Arc<Mutex<bool>>,
+    backend: Arc<dyn InputBackend>,
+    /// The same backend, for releasing whatever it still holds
+    tracker: Arc<TrackedBackend>,
+    /// Stops the running macro or script
+    cancel: CancelToken,
+    /// Problem found while loading settings, shown once by the frontend
+    config_warning: Arc<Mutex<Option<String>>>,
+    /// Text of the last settings.json this process wrote
+    saved_config: Mutex<Option<String>>,
+}
+
+#[derive(Clone, Serialize)]
+struct ScriptFinishedPayload {
+    file: String,
+    error: Option<String>,
 }
 
 #[derive(Clone, Serialize)]
 struct ClickerPayload {
     running: bool,
     cps: u64,
-    click_mode: String,
+    click_mode: ClickMode,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// KEY MAPPING — JS KeyboardEvent.code → device_query Keycode (for detection)
+// INPUT KEY DETECTION HELPER
 // ═══════════════════════════════════════════════════════════════════════════
 
-fn build_key_map() -> HashMap<String, Keycode> {
-    let mut m = HashMap::new();
-    // Letters
-    for (js, kc) in [
-        ("KeyA", Keycode::A), ("KeyB", Keycode::B), ("KeyC", Keycode::C),
-        ("KeyD", Keycode::D), ("KeyE", Keycode::E), ("KeyF", Keycode::F),
-        ("KeyG", Keycode::G), ("KeyH", Keycode::H), ("KeyI", Keycode::I),
-        ("KeyJ", Keycode::J), ("KeyK", Keycode::K), ("KeyL", Keycode::L),
-        ("KeyM", Keycode::M), ("KeyN", Keycode::N), ("KeyO", Keycode::O),
-        ("KeyP", Keycode::P), ("KeyQ", Keycode::Q), ("KeyR", Keycode::R),
-        ("KeyS", Keycode::S), ("KeyT", Keycode::T), ("KeyU", Keycode::U),
-        ("KeyV", Keycode::V), ("KeyW", Keycode::W), ("KeyX", Keycode::X),
-        ("KeyY", Keycode::Y), ("KeyZ", Keycode::Z),
-    ] { m.insert(js.into(), kc); }
-    // Digits
-    for (js, kc) in [
-        ("Digit0", Keycode::Key0), ("Digit1", Keycode::Key1), ("Digit2", Keycode::Key2),
-        ("Digit3", Keycode::Key3), ("Digit4", Keycode::Key4), ("Digit5", Keycode::Key5),
-        ("Digit6", Keycode::Key6), ("Digit7", Keycode::Key7), ("Digit8", Keycode::Key8),
-        ("Digit9", Keycode::Key9),
-    ] { m.insert(js.into(), kc); }
-    // Function keys
-    for (js, kc) in [
-        ("F1", Keycode::F1), ("F2", Keycode::F2), ("F3", Keycode::F3), ("F4", Keycode::F4),
-        ("F5", Keycode::F5), ("F6", Keycode::F6), ("F7", Keycode::F7), ("F8", Keycode::F8),
-        ("F9", Keycode::F9), ("F10", Keycode::F10), ("F11", Keycode::F11), ("F12", Keycode::F12),
-    ] { m.insert(js.into(), kc); }
-    // Modifiers / special
-    for (js, kc) in [
-        ("ShiftLeft", Keycode::LShift), ("ShiftRight", Keycode::RShift),
-        ("ControlLeft", Keycode::LControl), ("ControlRight", Keycode::RControl),
-        ("AltLeft", Keycode::LAlt), ("AltRight", Keycode::RAlt),
-        ("Space", Keycode::Space), ("Enter", Keycode::Enter),
-        ("Escape", Keycode::Escape), ("Backspace", Keycode::Backspace),
-        ("Tab", Keycode::Tab), ("CapsLock", Keycode::CapsLock),
-    ] { m.insert(js.into(), kc); }
-    m
+/// Whether a bound hotkey is held, given every key that is down
+fn is_hotkey_active(hotkey: Option<Hotkey>, down: &[KeyId]) -> bool {
+    hotkey.is_some_and(|h| h.is_active(down))
 }
 
-// ═══════════════════════════════════════════════════════════════════════════
-// VK MAPPING — JS KeyboardEvent.code → Windows Virtual Key code (for simulation)
-// ═══════════════════════════════════════════════════════════════════════════
-
-fn js_code_to_vk(code: &str) -> Option<u16> {
-    // Letters: KeyA=0x41, KeyB=0x42, ...
-    if code.starts_with("Key") && code.len() == 4 {
-        let c = code.as_bytes()[3];
-        if c.is_ascii_uppercase() {
-            return Some(c as u16);
-        }
-    }
-    // Digits: Digit0=0x30, Digit1=0x31, ...
-    if code.starts_with("Digit") && code.len() == 6 {
-        let c = code.as_bytes()[5];
-        if c.is_ascii_digit() {
-            return Some(c as u16);
-        }
-    }
-    match code {
-        "AltLeft" | "AltRight" => Some(0x12),   // VK_MENU
-        "ControlLeft" | "ControlRight" => Some(0x11), // VK_CONTROL
-        "ShiftLeft" | "ShiftRight" => Some(0x10), // VK_SHIFT
-        "Space" => Some(0x20),
-        "Tab" => Some(0x09),
-        "Enter" => Some(0x0D),
-        "Escape" => Some(0x1B),
-        "CapsLock" => Some(0x14),
-        "Backspace" => Some(0x08),
-        "F1" => Some(0x70), "F2" => Some(0x71), "F3" => Some(0x72), "F4" => Some(0x73),
-        "F5" => Some(0x74), "F6" => Some(0x75), "F7" => Some(0x76), "F8" => Some(0x77),
-        "F9" => Some(0x78), "F10" => Some(0x79), "F11" => Some(0x7A), "F12" => Some(0x7B),
-        _ => None,
-    }
-}
-
-// ═══════════════════════════════════════════════════════════════════════════
-// MOUSE HELPERS
-// ═══════════════════════════════════════════════════════════════════════════
-
-fn send_mouse_down() {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT { dwFlags: MOUSEEVENTF_LEFTDOWN, ..Default::default() },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-    }
+/// A script binding, told apart by file and hotkey as one file may be bound
+/// to several keys
+type ScriptId = (String, Option<Hotkey>);
+
+/// A macro (by name) or script started by a hold trigger, cancelled on
+/// release. Ids, not list positions, so edits to the lists cannot point
+/// this or the trigger states at another binding.
+#[derive(Clone, PartialEq)]
+enum HeldRun {
+    Macro(String),
+    Script(ScriptId),
 }
 
-fn send_mouse_up() {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT { dwFlags: MOUSEEVENTF_LEFTUP, ..Default::default() },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-    }
-}
+/// Live keyboard/mouse state for Rhai script queries
+struct DeviceInputState;
 
-fn send_right_mouse_down() {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT { dwFlags: MOUSEEVENTF_RIGHTDOWN, ..Default::default() },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+impl InputState for DeviceInputState {
+    fn cursor_position(&self) -> (i32, i32) {
+        DeviceState::new().get_mouse().coords
     }
-}
 
-fn send_right_mouse_up() {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT { dwFlags: MOUSEEVENTF_RIGHTUP, ..Default::default() },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
+    fn is_pressed(&self, key: KeyId) -> bool {
+        let device_state = DeviceState::new();
+        let mouse = device_state.get_mouse();
+        keys::pressed(&device_state.get_keys(), &mouse.button_pressed).contains(&key)
     }
 }
 
-fn move_mouse_absolute(x: i32, y: i32) {
-    unsafe {
-        let sw = GetSystemMetrics(SM_CXSCREEN) as i64;
-        let sh = GetSystemMetrics(SM_CYSCREEN) as i64;
-        let norm_x = ((x as i64) * 65535 / sw) as i32;
-        let norm_y = ((y as i64) * 65535 / sh) as i32;
-        let input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dx: norm_x,
-                    dy: norm_y,
-                    dwFlags: MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE,
-                    ..Default::default()
-                },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-    }
-}
+// ═══════════════════════════════════════════════════════════════════════════
+// SCRIPT RUNNER
+// ═══════════════════════════════════════════════════════════════════════════
 
-/// Smooth drag from one position to another with interpolation
-fn drag_mouse(from: (i32, i32), to: (i32, i32)) {
-    let steps: u32 = 15;
-    move_mouse_absolute(from.0, from.1);
-    thread::sleep(Duration::from_millis(30));
-    send_mouse_down();
-    thread::sleep(Duration::from_millis(30));
-    for i in 1..=steps {
-        let t = i as f64 / steps as f64;
-        let x = from.0 + ((to.0 - from.0) as f64 * t) as i32;
-        let y = from.1 + ((to.1 - from.1) as f64 * t) as i32;
-        move_mouse_absolute(x, y);
-        thread::sleep(Duration::from_millis(5));
+/// Run a script from the scripts directory on its own thread. Scripts share
+/// the macro slot, so only one macro or script runs at a time.
+fn start_script(app: &AppHandle, file: String) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    {
+        let mut running = state.macro_running.lock().unwrap();
+        if *running {
+            return Err("A macro or script is already running".into());
+        }
+        *running = true;
     }
-    thread::sleep(Duration::from_millis(30));
-    send_mouse_up();
+    state.cancel.reset();
+
+    let backend = state.backend.clone();
+    let running = state.macro_running.clone();
+    let cancel = state.cancel.clone();
+    let time_limit = Duration::from_millis(state.macro_config.lock().unwrap().script_time_limit_ms);
+    let dir = get_scripts_dir(app);
+    let app = app.clone();
+    thread::spawn(move || {
+        println!("Script '{}': executing", file);
+        let input_state = Arc::new(DeviceInputState);
+        let result = automation::run_file(backend, input_state, &dir, &file, time_limit, &cancel);
+        *running.lock().unwrap() = false;
+        let error = result.err().map(|e| e.to_string());
+        match &error {
+            Some(e) => println!("Script '{}': {}", file, e),
+            None => println!("Script '{}': done", file),
+        }
+        let _ = app.emit("script-finished", ScriptFinishedPayload { file, error });
+    });
+    Ok(())
 }
 
-/// Perform a click based on the current mode
-fn perform_click(mode: &str, hold_ms: u64) {
-    match mode {
-        "right" => {
-            send_right_mouse_down();
-            thread::sleep(Duration::from_millis(hold_ms));
-            send_right_mouse_up();
-        }
-        "double" => {
-            send_mouse_down();
-            thread::sleep(Duration::from_millis(hold_ms / 2));
-            send_mouse_up();
-            thread::sleep(Duration::from_millis(2));
-            send_mouse_down();
-            thread::sleep(Duration::from_millis(hold_ms / 2));
-            send_mouse_up();
-        }
-        _ => {
-            send_mouse_down();
-            thread::sleep(Duration::from_millis(hold_ms));
-            send_mouse_up();
-        }
-    }
+/// Panic button: stop the clicker and cancel whatever macro or script is
+/// running. The run releases its held keys and buttons as it unwinds, but
+/// everything still held is let go of right away as well.
+fn emergency_stop_all(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    state.cancel.cancel();
+    let mut clicker = state.clicker.lock().unwrap();
+    clicker.running = false;
+    state.tracker.release_all();
+    println!("Emergency stop");
+    let _ = app.emit(
+        "clicker-state-changed",
+        ClickerPayload {
+            running: false,
+            cps: clicker.cps,
+            click_mode: clicker.click_mode,
+        },
+    );
+    let _ = app.emit("emergency-stop", ());
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// KEYBOARD HELPERS
+// PROFILES
 // ═══════════════════════════════════════════════════════════════════════════
 
-fn send_key_down(vk: u16) {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(vk),
-                    dwFlags: KEYBD_EVENT_FLAGS(0),
-                    ..Default::default()
-                },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-    }
-}
-
-fn send_key_up(vk: u16) {
-    unsafe {
-        let input = INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: VIRTUAL_KEY(vk),
-                    dwFlags: KEYEVENTF_KEYUP,
-                    ..Default::default()
-                },
-            },
-        };
-        SendInput(&[input], std::mem::size_of::<INPUT>() as i32);
-    }
+#[derive(Clone, Serialize)]
+struct ProfilesPayload {
+    names: Vec<String>,
+    active: String,
+    #[serde(with = "hotkeys::binding")]
+    cycle_key: Option<Hotkey>,
+    auto_rules: Vec<AutoProfileRule>,
 }
 
-fn press_key(vk: u16) {
-    send_key_down(vk);
-    thread::sleep(Duration::from_millis(30));
-    send_key_up(vk);
-}
-
-/// Press a mouse X button (Mouse4=XBUTTON1, Mouse5=XBUTTON2)
-fn press_mouse_x(button: u32) {
-    unsafe {
-        let down = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dwFlags: MOUSEEVENTF_XDOWN,
-                    mouseData: button,
-                    ..Default::default()
-                },
-            },
-        };
-        SendInput(&[down], std::mem::size_of::<INPUT>() as i32);
-        thread::sleep(Duration::from_millis(30));
-        let up = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dwFlags: MOUSEEVENTF_XUP,
-                    mouseData: button,
-                    ..Default::default()
-                },
-            },
-        };
-        SendInput(&[up], std::mem::size_of::<INPUT>() as i32);
+impl ProfilesPayload {
+    fn new(store: &ProfileStore) -> ProfilesPayload {
+        ProfilesPayload {
+            names: store.names(),
+            active: store.active_name().to_string(),
+            cycle_key: store.cycle_key,
+            auto_rules: store.auto_rules().to_vec(),
+        }
     }
 }
 
-/// Press the dodge-roll key (handles both keyboard keys and mouse buttons)
-fn press_dodge(key: &str) {
-    match key {
-        "Mouse3" => {
-            send_right_mouse_down();
-            thread::sleep(Duration::from_millis(30));
-            send_right_mouse_up();
+/// Apply a change to the profile list, load whichever profile is active
+/// afterwards into the live settings and save. Emits `profile-changed` when
+/// another profile became active or the active one was changed.
+fn change_profiles(
+    app: &AppHandle, change: impl FnOnce(&mut ProfileStore) -> Result<(), String>,
+) -> Result<ProfilesPayload, String> {
+    let state = app.state::<AppState>();
+    let mut clicker = state.clicker.lock().unwrap();
+    let mut mc = state.macro_config.lock().unwrap();
+    let payload = {
+        let mut store = state.profiles.lock().unwrap();
+        store.store_active(&clicker, &mc);
+        let before = store.active().clone();
+        // Changed on a copy, so that hotkey clashes leave everything as it was
+        let mut changed = store.clone();
+        change(&mut changed)?;
+        let active = changed.active();
+        check_bindings(app, &active.clicker, &active.macro_config, &changed).map_err(|e| e.message)?;
+        *store = changed;
+
+        let active = store.active();
+        // Keep the clicker going (or stopped) across the switch
+        let running = clicker.running;
+        *clicker = active.clicker.clone();
+        clicker.running = running;
+        *mc = active.macro_config.clone();
+
+        let payload = ProfilesPayload::new(&store);
+        if payload.active != before.name {
+            println!("Profile: {}", payload.active);
         }
-        "Mouse4" => press_mouse_x(1), // XBUTTON1
-        "Mouse5" => press_mouse_x(2), // XBUTTON2
-        _ => {
-            if let Some(vk) = js_code_to_vk(key) {
-                press_key(vk);
-            }
+        if store.active() != &before {
+            let _ = app.emit("profile-changed", payload.clone());
+            let _ = app.emit(
+                "clicker-state-changed",
+                ClickerPayload { running, cps: clicker.cps, click_mode: clicker.click_mode },
+            );
         }
-    }
-}
-
-// ═══════════════════════════════════════════════════════════════════════════
-// MACRO EXECUTION
-// ═══════════════════════════════════════════════════════════════════════════
-
-const VK_TAB: u16 = 0x09;
-const VK_Q: u16   = 0x51;
-const VK_S: u16   = 0x53;
-const VK_6: u16   = 0x36;
-const VK_1: u16   = 0x31;
-
-/// Part 1: Safe Pocket → Quick Use
-fn execute_macro_part1(config: &MacroConfig) {
-    let delay = Duration::from_millis(config.delay_ms);
-    let sp = (config.safe_pocket_x, config.safe_pocket_y);
-    let qu = (config.quick_use_x, config.quick_use_y);
-
-    // Validate positions are set
-    if sp == (0, 0) || qu == (0, 0) {
-        println!("Macro Part 1: positions not set, aborting");
-        return;
-    }
-
-    println!("Macro Part 1: executing");
-
-    // 1. Open backpack
-    press_key(VK_TAB);
-    thread::sleep(delay);
-
-    // 2. Drag item from Safe Pocket → Quick Use slot
-    drag_mouse(sp, qu);
-    thread::sleep(delay);
-
-    // 3. Close backpack
-    press_key(VK_TAB);
-    thread::sleep(delay);
-
-    // 4. Hold Q to open item wheel
-    send_key_down(VK_Q);
-    thread::sleep(Duration::from_millis(300));
-
-    // 5. Select item 6
-    press_key(VK_6);
-    thread::sleep(Duration::from_millis(50));
-
-    // 6. Release Q
-    send_key_up(VK_Q);
-
-    println!("Macro Part 1: done");
-}
-
-/// Part 2: Quick Use → Safe Pocket
-fn execute_macro_part2(config: &MacroConfig) {
-    let delay = Duration::from_millis(config.delay_ms);
-    let sp = (config.safe_pocket_x, config.safe_pocket_y);
-    let qu = (config.quick_use_x, config.quick_use_y);
-
-    if sp == (0, 0) || qu == (0, 0) {
-        println!("Macro Part 2: positions not set, aborting");
-        return;
-    }
-
-    println!("Macro Part 2: executing");
-
-    // 1. Press 1
-    press_key(VK_1);
-    thread::sleep(delay);
-
-    // 2. Press dodge-roll key
-    press_dodge(&config.dodge_key);
-    thread::sleep(delay.max(Duration::from_millis(150))); // Ensure dodge animation starts
-
-    // 3. Open backpack
-    press_key(VK_TAB);
-    thread::sleep(delay.max(Duration::from_millis(100))); // Ensure backpack is open
-
-    // 4. Drag item from Quick Use to Safe Pocket
-    drag_mouse(qu, sp);
-    thread::sleep(delay);
-
-    // 5. Close backpack
-    press_key(VK_TAB);
-
-    println!("Macro Part 2: done");
+        payload
+    };
+    save_config(app, &state.profiles, &clicker, &mc)?;
+    Ok(payload)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
-// INPUT KEY DETECTION HELPER
+// HOT RELOAD
 // ═══════════════════════════════════════════════════════════════════════════
 
-fn is_key_active(
-    key_str: &str,
-    keys: &[Keycode],
-    mouse_buttons: &[bool],
-    key_map: &HashMap<String, Keycode>,
-) -> bool {
-    // Keyboard check via HashMap
-    if let Some(kc) = key_map.get(key_str) {
-        if keys.contains(kc) {
-            return true;
+/// Re-read settings.json after it changed on disk. Valid edits replace the
+/// live settings and emit `config-reloaded`; invalid ones emit
+/// `config-reload-failed` and change nothing.
+fn reload_config(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let text = match fs::read_to_string(get_config_path(app)) {
+        Ok(text) => text,
+        // Deleted, or caught between two steps of an editor's save
+        Err(_) => return,
+    };
+    let mut clicker = state.clicker.lock().unwrap();
+    let mut mc = state.macro_config.lock().unwrap();
+    let mut store = state.profiles.lock().unwrap();
+    store.store_active(&clicker, &mc);
+    let saved = state.saved_config.lock().unwrap().clone();
+    match reload::check(&text, saved.as_deref(), &store) {
+        reload::Reload::Unchanged => {}
+        reload::Reload::Invalid(e) => {
+            println!("Config reload rejected: {}", e);
+            let _ = app.emit("config-reload-failed", e);
+        }
+        reload::Reload::Changed(cfg) => {
+            *store = cfg.profiles;
+            let active = store.active();
+            let running = clicker.running;
+            *clicker = active.clicker.clone();
+            clicker.running = running;
+            *mc = active.macro_config.clone();
+            println!("Config reloaded from disk");
+            // Duplicates were refused above; this reports the overlaps
+            let _ = check_bindings(app, &clicker, &mc, &store);
+            let _ = app.emit("config-reloaded", ProfilesPayload::new(&store));
+            let _ = app.emit(
+                "clicker-state-changed",
+                ClickerPayload { running, cps: clicker.cps, click_mode: clicker.click_mode },
+            );
         }
-    }
-    // Mouse button check (safe bounds)
-    match key_str {
-        "Mouse3" => mouse_buttons.len() > 3 && mouse_buttons[3],
-        "Mouse4" => mouse_buttons.len() > 4 && mouse_buttons[4],
-        "Mouse5" => mouse_buttons.len() > 5 && mouse_buttons[5],
-        _ => false,
     }
 }
 
@@ -491,34 +337,38 @@ fn toggle_clicker(state: State<AppState>) -> bool {
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 fn update_config(
     cps: u64, randomness: u64, humanization_enabled: bool,
-    toggle_key: String, click_mode: String, state: State<AppState>,
+    toggle_key: String, toggle_trigger: Trigger, click_mode: ClickMode, state: State<AppState>,
     app: AppHandle,
-) {
+) -> Result<(), UpdateError> {
+    let toggle_key = hotkeys::parse_binding(&toggle_key).map_err(|e| format!("toggle key: {}", e))?;
     let mut clicker = state.clicker.lock().unwrap();
-    clicker.cps = cps;
-    clicker.randomness = randomness;
-    clicker.humanization_enabled = humanization_enabled;
-    clicker.toggle_key = toggle_key;
-    clicker.click_mode = click_mode;
-    
-    // Save
     let macro_config = state.macro_config.lock().unwrap();
-    save_config(&app, &*clicker, &*macro_config);
+    let update = ClickerState {
+        cps, randomness, humanization_enabled, toggle_key, toggle_trigger, click_mode, ..clicker.clone()
+    };
+    update.validate()?;
+    check_bindings(&app, &update, &macro_config, &state.profiles.lock().unwrap())?;
+    *clicker = update;
 
+    // Save
     println!(
-        "Config updated: CPS={}, Rnd={}, Human={}, Key={}, Mode={}",
-        clicker.cps, clicker.randomness, clicker.humanization_enabled, &clicker.toggle_key, &clicker.click_mode
+        "Config updated: CPS={}, Rnd={}, Human={}, Key={} ({:?}), Mode={:?}",
+        clicker.cps, clicker.randomness, clicker.humanization_enabled,
+        hotkeys::binding_name(clicker.toggle_key), clicker.toggle_trigger, clicker.click_mode
     );
+    Ok(save_config(&app, &state.profiles, &clicker, &macro_config)?)
 }
 
 #[tauri::command]
-fn get_clicker_state(state: State<AppState>) -> (bool, u64, u64, bool, String, String) {
+fn get_clicker_state(state: State<AppState>) -> (bool, u64, u64, bool, String, ClickMode, Trigger) {
     let clicker = state.clicker.lock().unwrap();
     (
         clicker.running, clicker.cps, clicker.randomness,
-        clicker.humanization_enabled, clicker.toggle_key.clone(), clicker.click_mode.clone(),
+        clicker.humanization_enabled, hotkeys::binding_name(clicker.toggle_key), clicker.click_mode,
+        clicker.toggle_trigger,
     )
 }
 
@@ -548,37 +398,171 @@ fn capture_position() -> (i32, i32) {
 }
 
 #[tauri::command]
-fn update_macro_config(
-    part1_key: String, part2_key: String, dodge_key: String,
-    safe_pocket_x: i32, safe_pocket_y: i32,
-    quick_use_x: i32, quick_use_y: i32,
-    delay_ms: u64, state: State<AppState>,
-    app: AppHandle,
-) {
+fn update_macro_config(config: MacroConfig, state: State<AppState>, app: AppHandle) -> Result<(), UpdateError> {
+    config.validate()?;
+    let clicker = state.clicker.lock().unwrap();
     let mut mc = state.macro_config.lock().unwrap();
-    mc.part1_key = part1_key;
-    mc.part2_key = part2_key;
-    mc.dodge_key = dodge_key;
-    mc.safe_pocket_x = safe_pocket_x;
-    mc.safe_pocket_y = safe_pocket_y;
-    mc.quick_use_x = quick_use_x;
-    mc.quick_use_y = quick_use_y;
-    mc.delay_ms = delay_ms;
+    check_bindings(&app, &clicker, &config, &state.profiles.lock().unwrap())?;
+    *mc = config;
+
+    println!(
+        "Macro config: {} macro(s), Record={}, MaxGap={}, Panic={}",
+        mc.macros.len(), hotkeys::binding_name(mc.record_key), mc.record_max_gap_ms, hotkeys::binding_name(mc.panic_key)
+    );
+    for m in &mc.macros {
+        println!("  {} [{}]: {} step(s)", m.name, hotkeys::binding_name(m.hotkey), m.steps.len());
+    }
+    for s in &mc.scripts {
+        println!("  {} [{}]", s.file, hotkeys::binding_name(s.hotkey));
+    }
 
     // Save
+    Ok(save_config(&app, &state.profiles, &clicker, &mc)?)
+}
+
+#[tauri::command]
+fn validate_script(source: String) -> Result<(), script::ScriptError> {
+    script::parse(&source).map(|_| ())
+}
+
+#[tauri::command]
+fn list_scripts(app: AppHandle) -> Vec<String> {
+    let dir = get_scripts_dir(&app);
+    let _ = fs::create_dir_all(&dir);
+    automation::list_scripts(&dir)
+}
+
+#[tauri::command]
+fn run_script(file: String, app: AppHandle) -> Result<(), String> {
+    start_script(&app, file)
+}
+
+#[tauri::command]
+fn emergency_stop(app: AppHandle) {
+    emergency_stop_all(&app);
+}
+
+#[tauri::command]
+fn get_macro_config(state: State<AppState>) -> MacroConfig {
+    state.macro_config.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn list_profiles(state: State<AppState>) -> ProfilesPayload {
+    ProfilesPayload::new(&state.profiles.lock().unwrap())
+}
+
+#[tauri::command]
+fn create_profile(name: String, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.create(&name))
+}
+
+#[tauri::command]
+fn rename_profile(name: String, new_name: String, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.rename(&name, &new_name))
+}
+
+#[tauri::command]
+fn duplicate_profile(name: String, new_name: String, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.duplicate(&name, &new_name))
+}
+
+#[tauri::command]
+fn delete_profile(name: String, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.delete(&name).map(|_| ()))
+}
+
+#[tauri::command]
+fn activate_profile(name: String, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.activate(&name).map(|_| ()))
+}
+
+#[tauri::command]
+fn set_profile_cycle_key(key: String, state: State<AppState>, app: AppHandle) -> Result<ProfilesPayload, UpdateError> {
+    let key = hotkeys::parse_binding(&key)?;
+    {
+        let clicker = state.clicker.lock().unwrap();
+        let mc = state.macro_config.lock().unwrap();
+        let mut update = state.profiles.lock().unwrap().clone();
+        update.cycle_key = key;
+        check_bindings(&app, &clicker, &mc, &update)?;
+    }
+    Ok(change_profiles(&app, |s| {
+        s.cycle_key = key;
+        Ok(())
+    })?)
+}
+
+#[derive(Clone, Serialize)]
+struct WindowKeysPayload {
+    #[serde(with = "hotkeys::binding")]
+    window_key: Option<Hotkey>,
+    #[serde(with = "hotkeys::binding")]
+    overlay_key: Option<Hotkey>,
+}
+
+#[tauri::command]
+fn get_window_keys(state: State<AppState>) -> WindowKeysPayload {
+    let store = state.profiles.lock().unwrap();
+    WindowKeysPayload { window_key: store.window_key, overlay_key: store.overlay_key }
+}
+
+#[tauri::command]
+fn set_window_keys(
+    window_key: String, overlay_key: String, state: State<AppState>, app: AppHandle,
+) -> Result<WindowKeysPayload, UpdateError> {
+    let window_key = hotkeys::parse_binding(&window_key).map_err(|e| format!("show/hide key: {}", e))?;
+    let overlay_key = hotkeys::parse_binding(&overlay_key).map_err(|e| format!("overlay key: {}", e))?;
     let clicker = state.clicker.lock().unwrap();
-    save_config(&app, &*clicker, &*mc);
+    let mc = state.macro_config.lock().unwrap();
+    {
+        let mut store = state.profiles.lock().unwrap();
+        let mut update = store.clone();
+        update.window_key = window_key;
+        update.overlay_key = overlay_key;
+        check_bindings(&app, &clicker, &mc, &update)?;
+        *store = update;
+    }
+    println!(
+        "Window keys: Show/Hide={}, Overlay={}",
+        hotkeys::binding_name(window_key), hotkeys::binding_name(overlay_key)
+    );
+    save_config(&app, &state.profiles, &clicker, &mc)?;
+    Ok(WindowKeysPayload { window_key, overlay_key })
+}
+
+#[tauri::command]
+fn set_auto_profile_rules(rules: Vec<AutoProfileRule>, app: AppHandle) -> Result<ProfilesPayload, String> {
+    change_profiles(&app, |s| s.set_auto_rules(rules))
+}
 
+#[tauri::command]
+fn export_bundle(profiles: Vec<String>, macros: Vec<String>, state: State<AppState>) -> Result<String, String> {
+    let clicker = state.clicker.lock().unwrap();
+    let mc = state.macro_config.lock().unwrap();
+    let mut store = state.profiles.lock().unwrap();
+    store.store_active(&clicker, &mc);
+    bundle::export(&store, &profiles, &macros)
+}
+
+#[tauri::command]
+fn import_bundle(text: String, conflict: bundle::Conflict, app: AppHandle) -> Result<bundle::ImportReport, String> {
+    let mut report = None;
+    change_profiles(&app, |s| {
+        report = Some(bundle::import(&text, s, conflict)?);
+        Ok(())
+    })?;
+    let report = report.unwrap_or_default();
     println!(
-        "Macro config: P1={}, P2={}, Dodge={}, SP=({},{}), QU=({},{}), Delay={}",
-        mc.part1_key, mc.part2_key, mc.dodge_key,
-        mc.safe_pocket_x, mc.safe_pocket_y, mc.quick_use_x, mc.quick_use_y, mc.delay_ms
+        "Imported {} profile(s), {} macro(s); {} warning(s), {} skipped",
+        report.profiles.len(), report.macros.len(), report.warnings.len(), report.skipped.len()
     );
+    Ok(report)
 }
 
 #[tauri::command]
-fn get_macro_config(state: State<AppState>) -> MacroConfig {
-    state.macro_config.lock().unwrap().clone()
+fn take_config_warning(state: State<AppState>) -> Option<String> {
+    state.config_warning.lock().unwrap().take()
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -586,42 +570,45 @@ fn get_macro_config(state: State<AppState>) -> MacroConfig {
 // ═══════════════════════════════════════════════════════════════════════════
 
 fn main() {
-    let key_map = build_key_map();
-
-    let clicker_state = Arc::new(Mutex::new(ClickerState {
-        running: false,
-        cps: 10,
-        randomness: 0,
-        humanization_enabled: true,
-        toggle_key: "F6".to_string(),
-        click_mode: "left".to_string(),
-    }));
+    let clicker_state = Arc::new(Mutex::new(ClickerState::default()));
 
-    let macro_config = Arc::new(Mutex::new(MacroConfig {
-        part1_key: "F7".to_string(),
-        part2_key: "F8".to_string(),
-        dodge_key: "AltLeft".to_string(),
-        safe_pocket_x: 0,
-        safe_pocket_y: 0,
-        quick_use_x: 0,
-        quick_use_y: 0,
-        delay_ms: 50,
-    }));
+    let macro_config = Arc::new(Mutex::new(MacroConfig::default()));
+    let profiles = Arc::new(Mutex::new(ProfileStore::default()));
 
     let macro_running = Arc::new(Mutex::new(false));
+    let config_warning = Arc::new(Mutex::new(None));
+
+    // Everything sent goes through the tracker, so that hotkeys ignore it
+    let injected = Arc::new(Injected::default());
+    let tracker = Arc::new(TrackedBackend::new(input::default_backend(), injected.clone()));
+    let backend: Arc<dyn InputBackend> = tracker.clone();
+    println!("Input backend: {}", backend.name());
+
+    // A panicking thread never gets to release what it pressed. It may hold
+    // an input lock, so the hook only releases through free ones.
+    let panic_tracker = tracker.clone();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        panic_tracker.try_release_all();
+        default_hook(info);
+    }));
+    let state_backend = backend.clone();
 
     let clicker_clone = clicker_state.clone();
     let macro_clone = macro_config.clone();
+    let profiles_clone = profiles.clone();
     let running_clone = macro_running.clone();
+    let warning_clone = config_warning.clone();
 
     tauri::Builder::default()
         .on_window_event(|window, event| {
-            // When the main window is closed, force-exit the entire process
-            // so background threads (input listener, clicker engine) and
-            // the overlay window are all terminated.
+            // When the main window is closed, exit the entire app so
+            // background threads (input listener, clicker engine) and the
+            // overlay window are all terminated. Held input is released on
+            // the way out, see `RunEvent::Exit` below.
             if window.label() == "main" {
                 if let tauri::WindowEvent::CloseRequested { .. } = event {
-                    std::process::exit(0);
+                    window.app_handle().exit(0);
                 }
             }
         })
@@ -632,55 +619,105 @@ fn main() {
 
             // Load Config
             let config_path = get_config_path(&app_handle);
-            if config_path.exists() {
-                if let Ok(content) = fs::read_to_string(&config_path) {
-                    if let Ok(cfg) = serde_json::from_str::<PersistentConfig>(&content) {
-                        println!("Loaded config from disk.");
-                        let mut c = clicker_clone.lock().unwrap();
-                        let mut m = macro_clone.lock().unwrap();
-                        
-                        // Restore Clicker State (but keep running=false safely)
-                        c.cps = cfg.clicker.cps;
-                        c.randomness = cfg.clicker.randomness;
-                        c.humanization_enabled = cfg.clicker.humanization_enabled;
-                        c.toggle_key = cfg.clicker.toggle_key;
-                        c.click_mode = cfg.clicker.click_mode;
-                        // Avoid auto-starting on load
-                        c.running = false; 
-
-                        // Restore Macro Config
-                        m.part1_key = cfg.macro_config.part1_key;
-                        m.part2_key = cfg.macro_config.part2_key;
-                        m.dodge_key = cfg.macro_config.dodge_key;
-                        m.safe_pocket_x = cfg.macro_config.safe_pocket_x;
-                        m.safe_pocket_y = cfg.macro_config.safe_pocket_y;
-                        m.quick_use_x = cfg.macro_config.quick_use_x;
-                        m.quick_use_y = cfg.macro_config.quick_use_y;
-                        m.delay_ms = cfg.macro_config.delay_ms;
-                    }
+            let loaded = match config::load(&config_path) {
+                config::LoadOutcome::Missing => None,
+                config::LoadOutcome::Loaded(cfg) => {
+                    println!("Loaded config from disk.");
+                    Some(cfg)
+                }
+                config::LoadOutcome::Recovered { config, error } => {
+                    let msg = format!("Settings were damaged ({}); restored the last good backup.", error);
+                    println!("{}", msg);
+                    *warning_clone.lock().unwrap() = Some(msg);
+                    Some(config)
+                }
+                config::LoadOutcome::Failed(error) => {
+                    let msg = format!("Settings could not be loaded ({}); using defaults.", error);
+                    println!("{}", msg);
+                    *warning_clone.lock().unwrap() = Some(msg);
+                    None
                 }
+            };
+            if let Some(cfg) = loaded {
+                let mut c = clicker_clone.lock().unwrap();
+                let mut m = macro_clone.lock().unwrap();
+                let active = cfg.profiles.active().clone();
+
+                // Restore Clicker State (but keep running=false safely)
+                *c = active.clicker;
+                // Avoid auto-starting on load
+                c.running = false;
+
+                // Restore Macro Config
+                *m = active.macro_config;
+
+                // Settings from before conflicts were checked may still have some
+                let found: Vec<String> = match bindings::check_store(&c, &m, &cfg.profiles) {
+                    Ok(overlaps) => overlaps.into_iter().map(|c| c.message).collect(),
+                    Err(e) => vec![e.message],
+                };
+                if !found.is_empty() {
+                    let list = found.join("; ");
+                    let msg = format!("Some hotkeys conflict: {}.", list);
+                    println!("{}", msg);
+                    let mut warning = warning_clone.lock().unwrap();
+                    *warning = Some(match warning.take() {
+                        Some(w) => format!("{} {}", w, msg),
+                        None => msg,
+                    });
+                }
+                *profiles_clone.lock().unwrap() = cfg.profiles;
             }
 
             // ─── THREAD 1: Input Listener ───────────────────────────────
             let input_clicker = clicker_clone.clone();
             let input_macro = macro_clone.clone();
+            let input_profiles = profiles_clone.clone();
             let input_running = running_clone.clone();
-            let km = key_map.clone();
+            let input_backend = backend.clone();
+            let input_injected = injected.clone();
 
             thread::spawn(move || {
                 let device_state = DeviceState::new();
-                let mut last_toggle = false;
-                let mut last_insert = false;
-                let mut last_part1 = false;
-                let mut last_part2 = false;
+                let cancel = app_handle.state::<AppState>().cancel.clone();
+                let mut last_panic = false;
+                let mut toggle_trigger = TriggerState::default();
+                let mut last_cycle = false;
+                let mut last_window = false;
+                let mut last_overlay = false;
+                let mut macro_triggers: HashMap<String, TriggerState> = HashMap::new();
+                let mut script_triggers: HashMap<ScriptId, TriggerState> = HashMap::new();
+                let mut held_run: Option<HeldRun> = None;
+                let mut last_record = false;
+                let mut recorder: Option<Recorder> = None;
+                let started = Instant::now();
+                let mut source = listener::default_source(input_injected);
+                println!("Hotkeys: using {} key source", source.name());
+                let mut down: Vec<KeyId> = Vec::new();
 
                 loop {
-                    let keys: Vec<Keycode> = device_state.get_keys();
-                    let mouse_buttons = device_state.get_mouse().button_pressed;
+                    // Between changes only long presses and the recorder need
+                    // the clock, and only while something is held or recorded
+                    let timeout = (!down.is_empty() || recorder.is_some()).then_some(Duration::from_millis(10));
+                    down = source.next(timeout);
+                    let now_ms = started.elapsed().as_millis() as u64;
+
+                    // 0. Emergency Stop (checked first so nothing else reacts)
+                    let panic_key = input_macro.lock().unwrap().panic_key;
+                    let panic_now = is_hotkey_active(panic_key, &down);
+                    if panic_now && !last_panic {
+                        emergency_stop_all(&app_handle);
+                    }
+                    last_panic = panic_now;
 
-                    // 1. Global Visibility Toggle (Insert)
-                    let insert_pressed = keys.contains(&Keycode::Insert);
-                    if insert_pressed && !last_insert {
+                    // 1. Window Visibility: swap main window and overlay, or
+                    //    toggle just the overlay
+                    let (window_key, overlay_key) = {
+                        let p = input_profiles.lock().unwrap();
+                        (p.window_key, p.overlay_key)
+                    };
+                    let window_now = is_hotkey_active(window_key, &down);
+                    if window_now && !last_window {
                         if main_window.is_visible().unwrap_or(true) {
                             main_window.hide().unwrap();
                             overlay_window.show().unwrap();
@@ -690,125 +727,248 @@ fn main() {
                             overlay_window.hide().unwrap();
                         }
                     }
-                    last_insert = insert_pressed;
+                    last_window = window_now;
+                    let overlay_now = is_hotkey_active(overlay_key, &down);
+                    if overlay_now && !last_overlay {
+                        if overlay_window.is_visible().unwrap_or(false) {
+                            overlay_window.hide().unwrap();
+                        } else {
+                            overlay_window.show().unwrap();
+                        }
+                    }
+                    last_overlay = overlay_now;
 
                     // 2. Clicker Toggle
-                    let toggle_key = {
+                    let (toggle_key, trigger) = {
                         let s = input_clicker.lock().unwrap();
-                        s.toggle_key.clone()
+                        (s.toggle_key, s.toggle_trigger)
                     };
-                    let toggle_now = is_key_active(&toggle_key, &keys, &mouse_buttons, &km);
-                    if toggle_now && !last_toggle {
+                    let held = is_hotkey_active(toggle_key, &down);
+                    if let Some(event) = toggle_trigger.update(trigger, now_ms, held) {
                         let mut s = input_clicker.lock().unwrap();
-                        s.running = !s.running;
+                        s.running = match event {
+                            TriggerEvent::Fire => !s.running,
+                            TriggerEvent::HoldStart => true,
+                            TriggerEvent::HoldEnd => false,
+                        };
                         println!("Global Toggle: {}", s.running);
                         let _ = app_handle.emit(
                             "clicker-state-changed",
                             ClickerPayload {
                                 running: s.running,
                                 cps: s.cps,
-                                click_mode: s.click_mode.clone(),
+                                click_mode: s.click_mode,
                             },
                         );
                     }
-                    last_toggle = toggle_now;
-
-                    // 3. Macro Keys (only when no macro is already running)
-                    let macro_is_running = *input_running.lock().unwrap();
-                    if !macro_is_running {
-                        let mc = input_macro.lock().unwrap().clone();
-
-                        // Part 1
-                        let p1_now = is_key_active(&mc.part1_key, &keys, &mouse_buttons, &km);
-                        if p1_now && !last_part1 {
-                            let config = mc.clone();
-                            let flag = input_running.clone();
-                            *flag.lock().unwrap() = true;
-                            thread::spawn(move || {
-                                execute_macro_part1(&config);
-                                *flag.lock().unwrap() = false;
-                            });
+
+                    // 3. Profile Cycle
+                    let cycle_key = input_profiles.lock().unwrap().cycle_key;
+                    let cycle_now = is_hotkey_active(cycle_key, &down);
+                    if cycle_now && !last_cycle {
+                        let cycled = change_profiles(&app_handle, |s| {
+                            s.cycle();
+                            Ok(())
+                        });
+                        if let Err(e) = cycled {
+                            println!("Profile cycle: {}", e);
                         }
-                        last_part1 = p1_now;
-
-                        // Part 2
-                        let p2_now = is_key_active(&mc.part2_key, &keys, &mouse_buttons, &km);
-                        if p2_now && !last_part2 {
-                            let config = mc.clone();
-                            let flag = input_running.clone();
-                            *flag.lock().unwrap() = true;
-                            thread::spawn(move || {
-                                execute_macro_part2(&config);
-                                *flag.lock().unwrap() = false;
-                            });
+                    }
+                    last_cycle = cycle_now;
+
+                    // 4. Macro Recorder
+                    let mc = input_macro.lock().unwrap().clone();
+                    let record_now = is_hotkey_active(mc.record_key, &down);
+                    if record_now && !last_record {
+                        match recorder.take() {
+                            None => {
+                                let max_gap = Some(mc.record_max_gap_ms).filter(|&ms| ms > 0);
+                                recorder = Some(Recorder::new(mc.record_key.map(|h| h.key), max_gap));
+                                println!("Recording started");
+                            }
+                            Some(r) => {
+                                let recorded = Macro {
+                                    name: mc.unused_name("Recording"),
+                                    hotkey: None,
+                                    trigger: Trigger::Press,
+                                    steps: r.finish(),
+                                };
+                                println!("Recording stopped: {} step(s)", recorded.steps.len());
+                                // Clicker before macro config, like every command
+                                let c = input_clicker.lock().unwrap();
+                                let mut m = input_macro.lock().unwrap();
+                                m.macros.push(recorded.clone());
+                                let _ = save_config(&app_handle, &input_profiles, &c, &m);
+                                let _ = app_handle.emit("macro-recorded", recorded);
+                            }
                         }
-                        last_part2 = p2_now;
+                        let _ = app_handle.emit("recording-state-changed", recorder.is_some());
+                    }
+                    last_record = record_now;
+                    if let Some(r) = recorder.as_mut() {
+                        // The recorder also follows the pointer, which only
+                        // device_query reports
+                        let mouse = device_state.get_mouse();
+                        r.sample(now_ms, &device_state.get_keys(), &mouse.button_pressed, mouse.coords);
                     }
 
-                    thread::sleep(Duration::from_millis(10));
+                    // 5. Macro Keys (only when no macro is already running, and
+                    //    not while recording so macro output isn't captured)
+                    // Bindings that were removed or renamed lose their state
+                    macro_triggers.retain(|name, _| mc.macros.iter().any(|m| &m.name == name));
+                    script_triggers.retain(|(file, hotkey), _| {
+                        mc.scripts.iter().any(|b| &b.file == file && b.hotkey == *hotkey)
+                    });
+                    let held_gone = match &held_run {
+                        Some(HeldRun::Macro(name)) => !macro_triggers.contains_key(name),
+                        Some(HeldRun::Script(id)) => !script_triggers.contains_key(id),
+                        None => false,
+                    };
+                    if held_gone {
+                        held_run = None;
+                    }
+
+                    let mut macro_is_running = *input_running.lock().unwrap() || recorder.is_some();
+                    for m in &mc.macros {
+                        let state = macro_triggers.entry(m.name.clone()).or_default();
+                        match state.update(m.trigger, now_ms, is_hotkey_active(m.hotkey, &down)) {
+                            Some(TriggerEvent::Fire | TriggerEvent::HoldStart) if !macro_is_running => {
+                                let m = m.clone();
+                                let flag = input_running.clone();
+                                let backend = input_backend.clone();
+                                let cancel = cancel.clone();
+                                *flag.lock().unwrap() = true;
+                                macro_is_running = true;
+                                held_run = (m.trigger == Trigger::Hold).then(|| HeldRun::Macro(m.name.clone()));
+                                cancel.reset();
+                                thread::spawn(move || {
+                                    macros::run_macro(backend, &m, &cancel);
+                                    *flag.lock().unwrap() = false;
+                                });
+                            }
+                            Some(TriggerEvent::HoldEnd) if held_run == Some(HeldRun::Macro(m.name.clone())) => {
+                                cancel.cancel();
+                                held_run = None;
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    // 6. Script Keys (same rules as macro keys)
+                    for binding in &mc.scripts {
+                        let id: ScriptId = (binding.file.clone(), binding.hotkey);
+                        let state = script_triggers.entry(id.clone()).or_default();
+                        match state.update(binding.trigger, now_ms, is_hotkey_active(binding.hotkey, &down)) {
+                            Some(TriggerEvent::Fire | TriggerEvent::HoldStart) if !macro_is_running => {
+                                match start_script(&app_handle, binding.file.clone()) {
+                                    Ok(()) => {
+                                        macro_is_running = true;
+                                        held_run = (binding.trigger == Trigger::Hold).then_some(HeldRun::Script(id));
+                                    }
+                                    Err(e) => println!("Script '{}': {}", binding.file, e),
+                                }
+                            }
+                            Some(TriggerEvent::HoldEnd) if held_run == Some(HeldRun::Script(id)) => {
+                                cancel.cancel();
+                                held_run = None;
+                            }
+                            _ => {}
+                        }
+                    }
                 }
             });
 
             // ─── THREAD 2: Clicker Engine ───────────────────────────────
             let engine_clicker = clicker_clone.clone();
             let engine_macro_running = running_clone.clone();
+            let engine_backend = backend.clone();
+            let engine_profiles = profiles_clone.clone();
+            let engine_app = app.handle().clone();
             thread::spawn(move || {
+                let mut rng = rand::thread_rng();
+                let focus = focus::default_source();
+                let mut switcher = AutoSwitcher::new();
+                let mut focus_read: Option<Instant> = None;
                 loop {
-                    let (running, cps, randomness, humanization_enabled, click_mode) = {
-                        let s = engine_clicker.lock().unwrap();
-                        (s.running, s.cps, s.randomness, s.humanization_enabled, s.click_mode.clone())
-                    };
-
-                    // Pause clicker while macro is running to avoid interference
-                    let macro_active = *engine_macro_running.lock().unwrap();
-
-                    if running && !macro_active {
-                        if cps > 0 {
-                            let target_ms: u64 = 1000 / cps;
-                            let mut hold_ms = target_ms / 2;
-                            let gap_ms;
-
-                            if humanization_enabled {
-                                let mut rng = rand::thread_rng();
-                                let hold_ratio = rng.gen_range(0.3..0.6);
-                                hold_ms = (target_ms as f64 * hold_ratio) as u64;
-                                if randomness > 0 {
-                                    let jitter = rng.gen_range(0..=randomness);
-                                    if rng.gen_bool(0.5) { hold_ms = hold_ms.saturating_add(jitter); }
-                                    else { hold_ms = hold_ms.saturating_sub(jitter); }
+                    // Follow the focused window before clicking, so a click
+                    // never uses the previous window's profile. While idle a
+                    // few checks per second are enough.
+                    let running = engine_clicker.lock().unwrap().running;
+                    if let Some(source) = &focus {
+                        if running || focus_read.is_none_or(|t| t.elapsed() > Duration::from_millis(250)) {
+                            let rules = engine_profiles.lock().unwrap().auto_rules().to_vec();
+                            if let Some(name) = switcher.check(source.focused_window(), &rules) {
+                                let switched = change_profiles(&engine_app, |s| s.activate(&name).map(|_| ()));
+                                if let Err(e) = switched {
+                                    println!("Auto profile '{}': {}", name, e);
                                 }
-                                let min_hold = 2.max(target_ms / 4);
-                                let max_hold = (target_ms * 3 / 4).max(min_hold);
-                                hold_ms = hold_ms.clamp(min_hold, max_hold);
-                                gap_ms = {
-                                    let mut g = target_ms.saturating_sub(hold_ms);
-                                    if randomness > 0 {
-                                        let jitter = rng.gen_range(0..=randomness);
-                                        if rng.gen_bool(0.5) { g = g.saturating_add(jitter); }
-                                        else { g = g.saturating_sub(jitter); }
-                                    }
-                                    g
-                                };
-                            } else {
-                                hold_ms = hold_ms.max(20);
-                                gap_ms = target_ms.saturating_sub(hold_ms);
                             }
+                            focus_read = Some(Instant::now());
+                        }
+                    }
+                    clicker::tick(&*engine_backend, &engine_clicker, &engine_macro_running, &mut rng);
+                }
+            });
+
+            // ─── THREAD 3: Fail-safe Corner Watcher ─────────────────────
+            let failsafe_macro = macro_clone.clone();
+            let failsafe_app = app.handle().clone();
+            let failsafe_injected = injected.clone();
+            thread::spawn(move || {
+                let device_state = DeviceState::new();
+                let mut watcher = FailsafeWatcher::new();
+                let mut bounds: Option<Bounds> = None;
+                let mut bounds_read: Option<Instant> = None;
+
+                loop {
+                    // Corners of the primary monitor; re-read now and then
+                    // in case the display layout changes
+                    if bounds_read.is_none_or(|t| t.elapsed() > Duration::from_secs(5)) {
+                        bounds = failsafe_app.primary_monitor().ok().flatten().map(|m| {
+                            let (pos, size) = (m.position(), m.size());
+                            Bounds {
+                                left: pos.x,
+                                top: pos.y,
+                                right: pos.x + size.width as i32,
+                                bottom: pos.y + size.height as i32,
+                            }
+                        });
+                        bounds_read = Some(Instant::now());
+                    }
 
-                            perform_click(&click_mode, hold_ms);
-                            thread::sleep(Duration::from_millis(gap_ms));
+                    let config = failsafe_macro.lock().unwrap().failsafe.clone();
+                    if let Some(b) = bounds {
+                        let pos = device_state.get_mouse().coords;
+                        let ours = failsafe_injected.cursor_is_ours(pos, Instant::now());
+                        if watcher.check(pos, ours, b, &config) {
+                            println!("Fail-safe: cursor reached {:?} corner", config.corner);
+                            emergency_stop_all(&failsafe_app);
+                            let _ = failsafe_app.emit("failsafe-triggered", config.corner);
                         }
-                    } else {
-                        thread::sleep(Duration::from_millis(10));
                     }
+
+                    thread::sleep(Duration::from_millis(10));
                 }
             });
 
+            // ─── THREAD 4: Settings File Watcher ────────────────────────
+            let reload_app = app.handle().clone();
+            if let Err(e) = reload::watch(config_path, move || reload_config(&reload_app)) {
+                println!("Config hot reload unavailable: {}", e);
+            }
+
             Ok(())
         })
         .manage(AppState {
             clicker: clicker_state,
             macro_config,
+            profiles,
             macro_running,
+            backend: state_backend,
+            tracker,
+            cancel: CancelToken::new(),
+            config_warning,
+            saved_config: Mutex::new(None),
         })
         .invoke_handler(tauri::generate_handler![
             toggle_clicker,
@@ -817,7 +977,29 @@ fn main() {
             capture_position,
             update_macro_config,
             get_macro_config,
+            validate_script,
+            list_scripts,
+            run_script,
+            emergency_stop,
+            take_config_warning,
+            list_profiles,
+            create_profile,
+            rename_profile,
+            duplicate_profile,
+            delete_profile,
+            activate_profile,
+            set_profile_cycle_key,
+            set_auto_profile_rules,
+            get_window_keys,
+            set_window_keys,
+            export_bundle,
+            import_bundle,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().tracker.release_all();
+            }
+        });
 }
@@ -0,0 +1,317 @@
+use crate::clicker::ClickerState;
+use crate::focus::AutoProfileRule;
+use crate::hotkeys::Hotkey;
+use crate::macros::MacroConfig;
+use serde::{Deserialize, Serialize};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PROFILES — named sets of clicker settings, macros and hotkeys
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// The active profile's settings live in the shared ClickerState and
+// MacroConfig that the worker threads read. The store keeps every profile,
+// and the active one is written back into it (`store_active`) before the
+// store is saved or another profile is activated.
+
+pub const DEFAULT_PROFILE: &str = "Default";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub clicker: ClickerState,
+    pub macro_config: MacroConfig,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Profile {
+        Profile { name: name.to_string(), clicker: ClickerState::default(), macro_config: MacroConfig::default() }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProfileStore {
+    #[serde(rename = "active_profile")]
+    active: String,
+    /// Global hotkey that switches to the next profile; empty for none
+    #[serde(rename = "profile_cycle_key", default, with = "crate::hotkeys::binding")]
+    pub cycle_key: Option<Hotkey>,
+    /// Global hotkey that swaps the main window and the overlay
+    #[serde(rename = "window_toggle_key", default = "default_window_key", with = "crate::hotkeys::binding")]
+    pub window_key: Option<Hotkey>,
+    /// Global hotkey that shows or hides only the overlay; empty for none
+    #[serde(rename = "overlay_toggle_key", default, with = "crate::hotkeys::binding")]
+    pub overlay_key: Option<Hotkey>,
+    /// Profiles to activate when certain windows get focus
+    #[serde(rename = "auto_profile_rules", default)]
+    auto_rules: Vec<AutoProfileRule>,
+    profiles: Vec<Profile>,
+}
+
+fn default_window_key() -> Option<Hotkey> {
+    Hotkey::parse("Insert").ok()
+}
+
+impl Default for ProfileStore {
+    fn default() -> ProfileStore {
+        ProfileStore::with_profile(Profile::new(DEFAULT_PROFILE))
+    }
+}
+
+impl ProfileStore {
+    pub fn with_profile(profile: Profile) -> ProfileStore {
+        ProfileStore {
+            active: profile.name.clone(),
+            cycle_key: None,
+            window_key: default_window_key(),
+            overlay_key: None,
+            auto_rules: Vec::new(),
+            profiles: vec![profile],
+        }
+    }
+
+    /// Repair a store read from disk: at least one profile, an active one
+    /// that exists, and no duplicate names
+    pub fn normalize(&mut self) {
+        let mut seen: Vec<String> = Vec::new();
+        self.profiles.retain(|p| {
+            let keep = !seen.contains(&p.name);
+            seen.push(p.name.clone());
+            keep
+        });
+        if self.profiles.is_empty() {
+            self.profiles.push(Profile::new(DEFAULT_PROFILE));
+        }
+        if self.index_of(&self.active).is_none() {
+            self.active = self.profiles[0].name.clone();
+        }
+        let names = self.names();
+        self.auto_rules.retain(|r| names.contains(&r.profile));
+    }
+
+    /// Check every profile's settings, as `update_config` and
+    /// `update_macro_config` would
+    pub fn validate(&self) -> Result<(), String> {
+        for p in &self.profiles {
+            p.clicker.validate().and_then(|_| p.macro_config.validate())
+                .map_err(|e| format!("profile '{}': {}", p.name, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.index_of(&self.active).expect("active profile exists")]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.index_of(name).map(|i| &self.profiles[i])
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        let i = self.index_of(&self.active).expect("active profile exists");
+        &mut self.profiles[i]
+    }
+
+    /// Add `profile`, or replace the one with the same name in place
+    pub fn put(&mut self, profile: Profile) {
+        match self.index_of(&profile.name) {
+            Some(i) => self.profiles[i] = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// Copy the live settings into the active profile. Whether the clicker
+    /// is running is not a setting and is stored as stopped.
+    pub fn store_active(&mut self, clicker: &ClickerState, macro_config: &MacroConfig) {
+        let i = self.index_of(&self.active).expect("active profile exists");
+        self.profiles[i].clicker = ClickerState { running: false, ..clicker.clone() };
+        self.profiles[i].macro_config = macro_config.clone();
+    }
+
+    pub fn auto_rules(&self) -> &[AutoProfileRule] {
+        &self.auto_rules
+    }
+
+    pub fn set_auto_rules(&mut self, rules: Vec<AutoProfileRule>) -> Result<(), String> {
+        if let Some(r) = rules.iter().find(|r| self.index_of(&r.profile).is_none()) {
+            return Err(format!("rule for unknown profile '{}'", r.profile));
+        }
+        self.auto_rules = rules;
+        Ok(())
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.profiles.iter().position(|p| p.name == name)
+    }
+
+    fn find(&self, name: &str) -> Result<usize, String> {
+        self.index_of(name).ok_or_else(|| format!("no profile named '{}'", name))
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("profile name must not be empty".into());
+        }
+        if self.index_of(name).is_some() {
+            return Err(format!("a profile named '{}' already exists", name));
+        }
+        Ok(name.to_string())
+    }
+
+    /// Add a profile with default settings
+    pub fn create(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_new_name(name)?;
+        self.profiles.push(Profile::new(&name));
+        Ok(())
+    }
+
+    /// Add a copy of `source` under a new name
+    pub fn duplicate(&mut self, source: &str, name: &str) -> Result<(), String> {
+        let i = self.find(source)?;
+        let name = self.check_new_name(name)?;
+        let copy = Profile { name, ..self.profiles[i].clone() };
+        self.profiles.insert(i + 1, copy);
+        Ok(())
+    }
+
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<(), String> {
+        let i = self.find(old)?;
+        if new.trim() == old {
+            return Ok(());
+        }
+        let new = self.check_new_name(new)?;
+        if self.active == old {
+            self.active = new.clone();
+        }
+        for rule in self.auto_rules.iter_mut().filter(|r| r.profile == old) {
+            rule.profile = new.clone();
+        }
+        self.profiles[i].name = new;
+        Ok(())
+    }
+
+    /// Remove a profile. Deleting the active one activates its neighbour,
+    /// in which case that profile is returned.
+    pub fn delete(&mut self, name: &str) -> Result<Option<&Profile>, String> {
+        let i = self.find(name)?;
+        if self.profiles.len() == 1 {
+            return Err("the last profile cannot be deleted".into());
+        }
+        self.profiles.remove(i);
+        self.auto_rules.retain(|r| r.profile != name);
+        if self.active != name {
+            return Ok(None);
+        }
+        let next = i.min(self.profiles.len() - 1);
+        self.active = self.profiles[next].name.clone();
+        Ok(Some(&self.profiles[next]))
+    }
+
+    pub fn activate(&mut self, name: &str) -> Result<&Profile, String> {
+        let i = self.find(name)?;
+        self.active = self.profiles[i].name.clone();
+        Ok(&self.profiles[i])
+    }
+
+    /// Activate the profile after the active one, wrapping around
+    pub fn cycle(&mut self) -> &Profile {
+        let i = self.index_of(&self.active).expect("active profile exists");
+        let next = (i + 1) % self.profiles.len();
+        self.active = self.profiles[next].name.clone();
+        &self.profiles[next]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(names: &[&str]) -> ProfileStore {
+        let mut store = ProfileStore::with_profile(Profile::new(names[0]));
+        for name in &names[1..] {
+            store.create(name).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn create_rejects_empty_and_duplicate_names() {
+        let mut s = store(&["Default"]);
+        assert!(s.create("  ").is_err());
+        assert!(s.create("Default").is_err());
+        s.create(" Farming ").unwrap();
+        assert_eq!(s.names(), vec!["Default", "Farming"]);
+    }
+
+    #[test]
+    fn duplicate_copies_settings_next_to_source() {
+        let mut s = store(&["A", "B"]);
+        let clicker = ClickerState { cps: 17, ..ClickerState::default() };
+        s.store_active(&clicker, &MacroConfig::default());
+
+        s.duplicate("A", "A copy").unwrap();
+        assert_eq!(s.names(), vec!["A", "A copy", "B"]);
+        assert_eq!(s.activate("A copy").unwrap().clicker.cps, 17);
+        assert!(s.duplicate("missing", "C").is_err());
+    }
+
+    #[test]
+    fn rename_follows_active_profile() {
+        let mut s = store(&["A", "B"]);
+        s.rename("A", "Main").unwrap();
+        assert_eq!(s.active_name(), "Main");
+        assert!(s.rename("Main", "B").is_err());
+        s.rename("B", "B").unwrap();
+    }
+
+    #[test]
+    fn delete_active_moves_to_neighbour_and_keeps_last() {
+        let mut s = store(&["A", "B", "C"]);
+        s.activate("C").unwrap();
+        assert!(s.delete("A").unwrap().is_none());
+        assert_eq!(s.delete("C").unwrap().unwrap().name, "B");
+        assert_eq!(s.active_name(), "B");
+        assert!(s.delete("B").is_err());
+    }
+
+    #[test]
+    fn auto_rules_follow_renames_and_deletes() {
+        let rule = |profile: &str| AutoProfileRule { profile: profile.into(), class: "game".into(), title: String::new() };
+        let mut s = store(&["A", "B"]);
+        assert!(s.set_auto_rules(vec![rule("missing")]).is_err());
+        s.set_auto_rules(vec![rule("A"), rule("B")]).unwrap();
+
+        s.rename("B", "Game").unwrap();
+        assert_eq!(s.auto_rules(), &[rule("A"), rule("Game")]);
+        s.delete("A").unwrap();
+        assert_eq!(s.auto_rules(), &[rule("Game")]);
+    }
+
+    #[test]
+    fn cycle_wraps_around() {
+        let mut s = store(&["A", "B", "C"]);
+        let order: Vec<String> = (0..4).map(|_| s.cycle().name.clone()).collect();
+        assert_eq!(order, vec!["B", "C", "A", "B"]);
+    }
+
+    #[test]
+    fn normalize_repairs_bad_store() {
+        let mut s: ProfileStore =
+            serde_json::from_str(r#"{"active_profile": "gone", "profiles": []}"#).unwrap();
+        s.normalize();
+        assert_eq!(s.names(), vec![DEFAULT_PROFILE]);
+        assert_eq!(s.active_name(), DEFAULT_PROFILE);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::input::MouseButton;
+use crate::keys::KeyId;
+use crate::macros::MacroStep;
+use device_query::Keycode;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// INPUT RECORDER — polled key/mouse snapshots → macro steps
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// device_query `button_pressed` index → button, using the same numbering as
+/// the "Mouse3".."Mouse5" hotkeys (1 = left, 2 = middle, 3 = right, 4/5 = X)
+fn button_for_index(index: usize) -> Option<MouseButton> {
+    match index {
+        1 => Some(MouseButton::Left),
+        2 => Some(MouseButton::Middle),
+        3 => Some(MouseButton::Right),
+        4 => Some(MouseButton::X1),
+        5 => Some(MouseButton::X2),
+        _ => None,
+    }
+}
+
+/// Raw transitions in the order they were observed, with timestamps in ms
+enum Recorded {
+    KeyDown(KeyId),
+    KeyUp(KeyId),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    Move(i32, i32),
+}
+
+pub struct Recorder {
+    ignored_key: Option<Keycode>,
+    ignored_button: Option<usize>,
+    /// Waits longer than this are shortened to it (idle-gap compression)
+    max_gap_ms: Option<u64>,
+    keys: Vec<Keycode>,
+    buttons: Vec<bool>,
+    /// Held when recording started; their releases are not recorded either
+    held_at_start: Vec<Keycode>,
+    buttons_at_start: Vec<usize>,
+    position: Option<(i32, i32)>,
+    events: Vec<(u64, Recorded)>,
+}
+
+impl Recorder {
+    /// `ignore` is the record hotkey itself, which must not end up in the macro.
+    pub fn new(ignore: Option<KeyId>, max_gap_ms: Option<u64>) -> Recorder {
+        Recorder {
+            ignored_key: ignore.and_then(KeyId::keycode),
+            ignored_button: ignore.and_then(KeyId::button_index),
+            max_gap_ms,
+            keys: Vec::new(),
+            buttons: Vec::new(),
+            held_at_start: Vec::new(),
+            buttons_at_start: Vec::new(),
+            position: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Feed one polled snapshot. The first one only establishes the baseline
+    /// so that keys already held when recording starts are not captured.
+    pub fn sample(&mut self, now_ms: u64, keys: &[Keycode], buttons: &[bool], position: (i32, i32)) {
+        if self.position.is_none() {
+            self.keys = keys.to_vec();
+            self.buttons = buttons.to_vec();
+            self.held_at_start = keys.to_vec();
+            self.buttons_at_start = (0..buttons.len()).filter(|&i| buttons[i]).collect();
+            self.position = Some(position);
+            return;
+        }
+
+        for kc in keys.iter().filter(|kc| !self.keys.contains(kc)) {
+            if let Some(name) = self.key_name(kc) {
+                self.events.push((now_ms, Recorded::KeyDown(name)));
+            }
+        }
+        for kc in self.keys.iter().filter(|kc| !keys.contains(kc)) {
+            if let Some(pos) = self.held_at_start.iter().position(|held| held == kc) {
+                self.held_at_start.remove(pos);
+            } else if let Some(name) = self.key_name(kc) {
+                self.events.push((now_ms, Recorded::KeyUp(name)));
+            }
+        }
+        self.keys = keys.to_vec();
+
+        for (index, &pressed) in buttons.iter().enumerate() {
+            let was_pressed = self.buttons.get(index).copied().unwrap_or(false);
+            if pressed == was_pressed || Some(index) == self.ignored_button {
+                continue;
+            }
+            if !pressed {
+                if let Some(pos) = self.buttons_at_start.iter().position(|&i| i == index) {
+                    self.buttons_at_start.remove(pos);
+                    continue;
+                }
+            }
+            let Some(button) = button_for_index(index) else { continue };
+            // Cursor position matters only where a button changes state
+            if self.position != Some(position) {
+                self.events.push((now_ms, Recorded::Move(position.0, position.1)));
+                self.position = Some(position);
+            }
+            let event = if pressed { Recorded::MouseDown(button) } else { Recorded::MouseUp(button) };
+            self.events.push((now_ms, event));
+        }
+        self.buttons = buttons.to_vec();
+    }
+
+    fn key_name(&self, kc: &Keycode) -> Option<KeyId> {
+        if Some(*kc) == self.ignored_key {
+            return None;
+        }
+        KeyId::from_keycode(*kc)
+    }
+
+    /// Turn the recording into steps: waits between events, and taps/clicks
+    /// for presses with nothing else in between. Keys still down at the end
+    /// are the stop hotkey's modifiers and are left out.
+    pub fn finish(self) -> Vec<MacroStep> {
+        let mut released = Vec::new();
+        let mut events: Vec<_> = self.events.into_iter().rev().filter(|(_, event)| match event {
+            Recorded::KeyUp(key) => {
+                released.push(*key);
+                true
+            }
+            Recorded::KeyDown(key) => released.contains(key),
+            _ => true,
+        }).collect();
+        events.reverse();
+
+        let mut steps = Vec::new();
+        let mut last_ms: Option<u64> = None;
+        for (t, event) in events {
+            if let Some(last) = last_ms {
+                let gap = t - last;
+                if gap > 0 {
+                    let ms = self.max_gap_ms.map_or(gap, |max| gap.min(max));
+                    steps.push(MacroStep::Wait { ms });
+                }
+            }
+            last_ms = Some(t);
+            steps.push(match event {
+                Recorded::KeyDown(key) => MacroStep::KeyDown { key },
+                Recorded::KeyUp(key) => MacroStep::KeyUp { key },
+                Recorded::MouseDown(button) => MacroStep::MouseDown { button },
+                Recorded::MouseUp(button) => MacroStep::MouseUp { button },
+                Recorded::Move(x, y) => MacroStep::Move { x, y, relative: false },
+            });
+        }
+        merge_presses(steps)
+    }
+}
+
+/// Collapse `down, wait, up` of the same key/button into a single tap/click
+fn merge_presses(steps: Vec<MacroStep>) -> Vec<MacroStep> {
+    let mut out: Vec<MacroStep> = Vec::with_capacity(steps.len());
+    for step in steps {
+        let merged = match (&step, out.as_slice()) {
+            (MacroStep::KeyUp { key }, [.., MacroStep::KeyDown { key: down }, MacroStep::Wait { ms }])
+                if key == down => Some(MacroStep::KeyTap { key: *key, hold_ms: *ms }),
+            (MacroStep::KeyUp { key }, [.., MacroStep::KeyDown { key: down }])
+                if key == down => Some(MacroStep::KeyTap { key: *key, hold_ms: 0 }),
+            (MacroStep::MouseUp { button }, [.., MacroStep::MouseDown { button: down }, MacroStep::Wait { ms }])
+                if button == down => Some(MacroStep::Click { button: *button, hold_ms: *ms }),
+            (MacroStep::MouseUp { button }, [.., MacroStep::MouseDown { button: down }])
+                if button == down => Some(MacroStep::Click { button: *button, hold_ms: 0 }),
+            _ => None,
+        };
+        match merged {
+            Some(tap) => {
+                if matches!(out.last(), Some(MacroStep::Wait { .. })) {
+                    out.pop();
+                }
+                out.pop();
+                out.push(tap);
+            }
+            None => out.push(step),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_BUTTONS: [bool; 6] = [false; 6];
+
+    fn buttons(pressed: &[usize]) -> Vec<bool> {
+        (0..6).map(|i| pressed.contains(&i)).collect()
+    }
+
+    fn key(name: &str) -> KeyId {
+        KeyId::parse(name).unwrap()
+    }
+
+    fn recorder(max_gap_ms: Option<u64>) -> Recorder {
+        let mut r = Recorder::new(Some(key("F10")), max_gap_ms);
+        r.sample(0, &[Keycode::F10], &NO_BUTTONS, (0, 0));
+        r
+    }
+
+    #[test]
+    fn records_taps_with_relative_timing() {
+        let mut r = recorder(None);
+        r.sample(100, &[Keycode::Q], &NO_BUTTONS, (0, 0));
+        r.sample(140, &[], &NO_BUTTONS, (0, 0));
+        r.sample(400, &[Keycode::LShift], &NO_BUTTONS, (0, 0));
+        r.sample(420, &[Keycode::LShift, Keycode::Key1], &NO_BUTTONS, (0, 0));
+        r.sample(450, &[Keycode::LShift], &NO_BUTTONS, (0, 0));
+        r.sample(460, &[], &NO_BUTTONS, (0, 0));
+
+        assert_eq!(r.finish(), vec![
+            MacroStep::KeyTap { key: key("KeyQ"), hold_ms: 40 },
+            MacroStep::Wait { ms: 260 },
+            MacroStep::KeyDown { key: key("ShiftLeft") },
+            MacroStep::Wait { ms: 20 },
+            MacroStep::KeyTap { key: key("Digit1"), hold_ms: 30 },
+            MacroStep::Wait { ms: 10 },
+            MacroStep::KeyUp { key: key("ShiftLeft") },
+        ]);
+    }
+
+    #[test]
+    fn records_cursor_position_at_button_changes() {
+        let mut r = recorder(None);
+        r.sample(10, &[], &buttons(&[1]), (50, 60));
+        r.sample(30, &[], &NO_BUTTONS, (50, 60));
+        r.sample(100, &[], &buttons(&[1]), (70, 80));
+        r.sample(200, &[], &NO_BUTTONS, (90, 100));
+
+        assert_eq!(r.finish(), vec![
+            MacroStep::Move { x: 50, y: 60, relative: false },
+            MacroStep::Click { button: MouseButton::Left, hold_ms: 20 },
+            MacroStep::Wait { ms: 70 },
+            MacroStep::Move { x: 70, y: 80, relative: false },
+            MacroStep::MouseDown { button: MouseButton::Left },
+            MacroStep::Wait { ms: 100 },
+            MacroStep::Move { x: 90, y: 100, relative: false },
+            MacroStep::MouseUp { button: MouseButton::Left },
+        ]);
+    }
+
+    #[test]
+    fn compresses_idle_gaps() {
+        let mut r = recorder(Some(500));
+        r.sample(100, &[Keycode::E], &NO_BUTTONS, (0, 0));
+        r.sample(130, &[], &NO_BUTTONS, (0, 0));
+        r.sample(5_000, &[Keycode::E], &NO_BUTTONS, (0, 0));
+        r.sample(5_030, &[], &NO_BUTTONS, (0, 0));
+
+        assert_eq!(r.finish(), vec![
+            MacroStep::KeyTap { key: key("KeyE"), hold_ms: 30 },
+            MacroStep::Wait { ms: 500 },
+            MacroStep::KeyTap { key: key("KeyE"), hold_ms: 30 },
+        ]);
+    }
+
+    #[test]
+    fn ignores_record_hotkey_and_keys_held_at_start() {
+        let mut r = Recorder::new(Some(key("Mouse4")), None);
+        r.sample(0, &[Keycode::W], &buttons(&[1, 4]), (0, 0));
+        r.sample(50, &[], &NO_BUTTONS, (0, 0));
+        r.sample(90, &[], &buttons(&[4]), (0, 0));
+        r.sample(95, &[], &NO_BUTTONS, (0, 0));
+
+        assert!(r.finish().is_empty());
+    }
+
+    #[test]
+    fn leaves_out_modifiers_of_the_stop_hotkey() {
+        // Recording stops on Ctrl+F10, so only the F10 press is ignored
+        let mut r = recorder(None);
+        r.sample(10, &[], &NO_BUTTONS, (0, 0));
+        r.sample(100, &[Keycode::Q], &NO_BUTTONS, (0, 0));
+        r.sample(130, &[], &NO_BUTTONS, (0, 0));
+        r.sample(300, &[Keycode::LControl], &NO_BUTTONS, (0, 0));
+
+        assert_eq!(r.finish(), vec![MacroStep::KeyTap { key: key("KeyQ"), hold_ms: 30 }]);
+    }
+}
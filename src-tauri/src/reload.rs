@@ -0,0 +1,167 @@
+use crate::bindings;
+use crate::config::{self, PersistentConfig};
+use crate::profiles::ProfileStore;
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// HOT RELOAD — pick up edits to settings.json made outside the app
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// Saves replace settings.json by renaming a temp file over it, so the
+// directory is watched rather than the file itself. Our own saves show up
+// as changes too; they are recognised by comparing against the text last
+// written and against the live settings.
+
+/// Editors often write a file in several steps; wait this long for the
+/// events to settle before reading it
+const SETTLE: Duration = Duration::from_millis(200);
+
+pub enum Reload {
+    /// Nothing to apply: our own save, or the same settings we already have
+    Unchanged,
+    Changed(PersistentConfig),
+    /// The file does not parse, or holds values or hotkeys the settings
+    /// screens would refuse; the live settings stay as they are
+    Invalid(String),
+}
+
+/// Decide what a changed settings file means for the live `store`
+pub fn check(text: &str, last_saved: Option<&str>, store: &ProfileStore) -> Reload {
+    if last_saved == Some(text) {
+        return Reload::Unchanged;
+    }
+    let checked = config::from_json(text).and_then(|cfg| {
+        cfg.profiles.validate()?;
+        let active = cfg.profiles.active();
+        bindings::check_store(&active.clicker, &active.macro_config, &cfg.profiles).map_err(|e| e.message)?;
+        Ok(cfg)
+    });
+    match checked {
+        Err(e) => Reload::Invalid(e),
+        Ok(cfg) => {
+            let live = config::to_json(&PersistentConfig::new(store.clone()));
+            if config::to_json(&cfg) == live {
+                Reload::Unchanged
+            } else {
+                Reload::Changed(cfg)
+            }
+        }
+    }
+}
+
+/// Call `on_change` from a background thread whenever `path` is written,
+/// replaced or created
+pub fn watch(path: PathBuf, on_change: impl Fn() + Send + 'static) -> Result<(), String> {
+    let dir = path.parent().ok_or("settings path has no folder")?.to_path_buf();
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            if !event.kind.is_access() && event.paths.iter().any(|p| is_settings_file(p, &path)) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+
+    thread::spawn(move || {
+        // The watcher stops when dropped, so it lives as long as this thread
+        let _watcher = watcher;
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(SETTLE).is_ok() {}
+            on_change();
+        }
+    });
+    Ok(())
+}
+
+fn is_settings_file(changed: &Path, settings: &Path) -> bool {
+    changed.file_name().is_some() && changed.file_name() == settings.file_name()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clicker::ClickerState;
+    use crate::macros::MacroConfig;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    fn store() -> ProfileStore {
+        config::from_json(include_str!("../tests/fixtures/config/v4.json")).unwrap().profiles
+    }
+
+    fn text(store: &ProfileStore) -> String {
+        config::to_json(&PersistentConfig::new(store.clone()))
+    }
+
+    #[test]
+    fn own_saves_and_equal_settings_are_unchanged() {
+        let live = store();
+        let saved = text(&live);
+        assert!(matches!(check(&saved, Some(&saved), &live), Reload::Unchanged));
+        // Same settings, different formatting
+        let compact = serde_json::to_string(&PersistentConfig::new(live.clone())).unwrap();
+        assert!(matches!(check(&compact, None, &live), Reload::Unchanged));
+    }
+
+    #[test]
+    fn edits_are_applied_and_bad_edits_rejected() {
+        let live = store();
+        let mut edited = live.clone();
+        let active = edited.active().clone();
+        edited.store_active(&ClickerState { cps: 3, ..active.clicker }, &active.macro_config);
+
+        match check(&text(&edited), None, &live) {
+            Reload::Changed(cfg) => assert_eq!(cfg.profiles.active().clicker.cps, 3),
+            _ => panic!("edit not picked up"),
+        }
+        match check("{\"version\": 3, ", None, &live) {
+            Reload::Invalid(e) => assert!(e.contains("not valid JSON"), "{}", e),
+            _ => panic!("broken file accepted"),
+        }
+
+        // Values the settings screens refuse are refused here too
+        let mut edited = live.clone();
+        let mc = MacroConfig { script_time_limit_ms: 0, ..active.macro_config.clone() };
+        edited.store_active(&active.clicker, &mc);
+        match check(&text(&edited), None, &live) {
+            Reload::Invalid(e) => assert!(e.contains("script time limit"), "{}", e),
+            _ => panic!("invalid settings accepted"),
+        }
+        let mut edited = live.clone();
+        edited.overlay_key = edited.window_key;
+        match check(&text(&edited), None, &live) {
+            Reload::Invalid(e) => assert!(e.contains("is bound to both"), "{}", e),
+            _ => panic!("duplicate hotkeys accepted"),
+        }
+    }
+
+    #[test]
+    fn watcher_reports_replaced_file() {
+        let dir = std::env::temp_dir().join(format!("cliky-reload-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("settings.json");
+        let changes = Arc::new(Mutex::new(0));
+        let seen = changes.clone();
+        watch(path.clone(), move || *seen.lock().unwrap() += 1).unwrap();
+
+        // Unrelated files in the folder are ignored
+        fs::write(dir.join("notes.txt"), "x").unwrap();
+        config::save(&path, &PersistentConfig::new(store())).unwrap();
+
+        let start = Instant::now();
+        while *changes.lock().unwrap() == 0 && start.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(20));
+        }
+        thread::sleep(SETTLE * 2);
+        assert_eq!(*changes.lock().unwrap(), 1);
+    }
+}
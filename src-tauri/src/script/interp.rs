@@ -0,0 +1,165 @@
+use super::parser::{BinOp, Expr, ExprKind, Program, Stmt, StmtKind};
+use super::{Pos, ScriptError};
+use crate::actions::{click_button, drag_mouse, hold_key, release_key, tap_key};
+use crate::cancel::CancelToken;
+use crate::input::InputBackend;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DEFAULT_HOLD_MS: u64 = 30;
+
+pub struct Interpreter<'a> {
+    input: &'a dyn InputBackend,
+    cancel: &'a CancelToken,
+    vars: HashMap<String, i64>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(input: &'a dyn InputBackend, cancel: &'a CancelToken) -> Interpreter<'a> {
+        Interpreter { input, cancel, vars: HashMap::new() }
+    }
+
+    pub fn run(&mut self, program: &Program) -> Result<(), ScriptError> {
+        self.block(program)
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<(), ScriptError> {
+        for stmt in stmts {
+            self.stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), ScriptError> {
+        self.check_cancel(stmt.pos)?;
+        let input = self.input;
+        match &stmt.kind {
+            StmtKind::Let(name, value) | StmtKind::Assign(name, value) => {
+                let value = self.eval(value)?;
+                self.vars.insert(name.clone(), value);
+            }
+            StmtKind::Repeat(times, body) => {
+                for _ in 0..self.eval(times)?.max(0) {
+                    self.check_cancel(stmt.pos)?;
+                    self.block(body)?;
+                }
+            }
+            StmtKind::While(cond, body) => {
+                while self.eval(cond)? != 0 {
+                    self.check_cancel(stmt.pos)?;
+                    self.block(body)?;
+                }
+            }
+            StmtKind::If(cond, then, otherwise) => {
+                if self.eval(cond)? != 0 {
+                    self.block(then)?;
+                } else {
+                    self.block(otherwise)?;
+                }
+            }
+            StmtKind::Tap(key, hold) => {
+                let hold_ms = self.hold(hold.as_ref())?;
+                tap_key(input, *key, hold_ms);
+            }
+            StmtKind::Press(key) => hold_key(input, *key),
+            StmtKind::Release(key) => release_key(input, *key),
+            StmtKind::Click(button, hold) => {
+                let hold_ms = self.hold(hold.as_ref())?;
+                click_button(input, *button, hold_ms);
+            }
+            StmtKind::MouseDown(button) => input.mouse_down(*button),
+            StmtKind::MouseUp(button) => input.mouse_up(*button),
+            StmtKind::Move { x, y, relative } => {
+                let (x, y) = (self.coord(x)?, self.coord(y)?);
+                if *relative {
+                    input.move_relative(x, y);
+                } else {
+                    input.move_absolute(x, y);
+                }
+            }
+            StmtKind::Drag { from, to } => {
+                let from = (self.coord(&from.0)?, self.coord(&from.1)?);
+                let to = (self.coord(&to.0)?, self.coord(&to.1)?);
+                drag_mouse(input, from, to);
+            }
+            StmtKind::Scroll(notches) => input.scroll(self.coord(notches)?),
+            StmtKind::Wait(ms) => {
+                let ms = self.millis(ms, "wait")?;
+                input.sleep(Duration::from_millis(ms));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_cancel(&self, pos: Pos) -> Result<(), ScriptError> {
+        if self.cancel.is_cancelled() {
+            return Err(ScriptError::new(pos, "cancelled"));
+        }
+        Ok(())
+    }
+
+    fn hold(&mut self, hold: Option<&Expr>) -> Result<u64, ScriptError> {
+        match hold {
+            Some(expr) => self.millis(expr, "hold"),
+            None => Ok(DEFAULT_HOLD_MS),
+        }
+    }
+
+    fn millis(&mut self, expr: &Expr, what: &str) -> Result<u64, ScriptError> {
+        u64::try_from(self.eval(expr)?)
+            .map_err(|_| ScriptError::new(expr.pos, format!("{} must not be negative", what)))
+    }
+
+    fn coord(&mut self, expr: &Expr) -> Result<i32, ScriptError> {
+        let value = self.eval(expr)?;
+        i32::try_from(value).map_err(|_| ScriptError::new(expr.pos, format!("{} is out of range", value)))
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<i64, ScriptError> {
+        match &expr.kind {
+            ExprKind::Int(n) => Ok(*n),
+            ExprKind::Var(name) => self
+                .vars
+                .get(name)
+                .copied()
+                // Declared further down the script but not assigned yet
+                .ok_or_else(|| ScriptError::new(expr.pos, format!("variable '{}' has no value yet", name))),
+            ExprKind::Neg(inner) => {
+                let value = self.eval(inner)?;
+                value.checked_neg().ok_or_else(|| overflow(expr.pos))
+            }
+            ExprKind::Not(inner) => Ok((self.eval(inner)? == 0) as i64),
+            ExprKind::Binary(BinOp::And, lhs, rhs) => {
+                Ok((self.eval(lhs)? != 0 && self.eval(rhs)? != 0) as i64)
+            }
+            ExprKind::Binary(BinOp::Or, lhs, rhs) => {
+                Ok((self.eval(lhs)? != 0 || self.eval(rhs)? != 0) as i64)
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                let (a, b) = (self.eval(lhs)?, self.eval(rhs)?);
+                let result = match op {
+                    BinOp::Add => a.checked_add(b),
+                    BinOp::Sub => a.checked_sub(b),
+                    BinOp::Mul => a.checked_mul(b),
+                    BinOp::Div | BinOp::Rem if b == 0 => {
+                        return Err(ScriptError::new(expr.pos, "division by zero"));
+                    }
+                    BinOp::Div => a.checked_div(b),
+                    BinOp::Rem => a.checked_rem(b),
+                    BinOp::Eq => Some((a == b) as i64),
+                    BinOp::Ne => Some((a != b) as i64),
+                    BinOp::Lt => Some((a < b) as i64),
+                    BinOp::Le => Some((a <= b) as i64),
+                    BinOp::Gt => Some((a > b) as i64),
+                    BinOp::Ge => Some((a >= b) as i64),
+                    BinOp::And | BinOp::Or => unreachable!(),
+                };
+                result.ok_or_else(|| overflow(expr.pos))
+            }
+        }
+    }
+}
+
+fn overflow(pos: Pos) -> ScriptError {
+    ScriptError::new(pos, "arithmetic overflow")
+}
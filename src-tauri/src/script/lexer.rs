@@ -0,0 +1,156 @@
+use super::{Pos, ScriptError};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tok {
+    Ident(String),
+    Int(i64),
+    /// Number with a unit suffix, already converted to milliseconds
+    Millis(i64),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Assign,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    /// Statement separator: newline or `;`
+    Sep,
+    Eof,
+}
+
+impl Tok {
+    /// How the token is shown in "expected X, found Y" errors
+    pub fn describe(&self) -> String {
+        match self {
+            Tok::Ident(s) => format!("'{}'", s),
+            Tok::Int(n) => format!("'{}'", n),
+            Tok::Millis(n) => format!("'{}ms'", n),
+            Tok::Sep => "end of statement".into(),
+            Tok::Eof => "end of script".into(),
+            other => format!("'{}'", other.symbol()),
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Tok::LBrace => "{",
+            Tok::RBrace => "}",
+            Tok::LParen => "(",
+            Tok::RParen => ")",
+            Tok::Comma => ",",
+            Tok::Assign => "=",
+            Tok::Eq => "==",
+            Tok::Ne => "!=",
+            Tok::Lt => "<",
+            Tok::Le => "<=",
+            Tok::Gt => ">",
+            Tok::Ge => ">=",
+            Tok::Plus => "+",
+            Tok::Minus => "-",
+            Tok::Star => "*",
+            Tok::Slash => "/",
+            Tok::Percent => "%",
+            _ => "",
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<(Tok, Pos)>, ScriptError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut col) = (0, 1, 1);
+
+    while i < chars.len() {
+        let c = chars[i];
+        let pos = Pos { line, column: col };
+        let start = i;
+
+        if c == '\n' || c == ';' {
+            tokens.push((Tok::Sep, pos));
+            i += 1;
+            if c == '\n' {
+                line += 1;
+                col = 1;
+            } else {
+                col += 1;
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            col += 1;
+            continue;
+        }
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        let tok = if c.is_ascii_digit() {
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let value: i64 = digits
+                .parse()
+                .map_err(|_| ScriptError::new(pos, format!("number '{}' is too large", digits)))?;
+            let unit_start = i;
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            let unit: String = chars[unit_start..i].iter().collect();
+            match unit.as_str() {
+                "" => Tok::Int(value),
+                "ms" => Tok::Millis(value),
+                "s" => Tok::Millis(value.saturating_mul(1000)),
+                _ => return Err(ScriptError::new(pos, format!("unknown unit '{}' (use ms or s)", unit))),
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Tok::Ident(chars[start..i].iter().collect())
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (tok, len) = match (c, next) {
+                ('=', Some('=')) => (Tok::Eq, 2),
+                ('!', Some('=')) => (Tok::Ne, 2),
+                ('<', Some('=')) => (Tok::Le, 2),
+                ('>', Some('=')) => (Tok::Ge, 2),
+                ('=', _) => (Tok::Assign, 1),
+                ('<', _) => (Tok::Lt, 1),
+                ('>', _) => (Tok::Gt, 1),
+                ('{', _) => (Tok::LBrace, 1),
+                ('}', _) => (Tok::RBrace, 1),
+                ('(', _) => (Tok::LParen, 1),
+                (')', _) => (Tok::RParen, 1),
+                (',', _) => (Tok::Comma, 1),
+                ('+', _) => (Tok::Plus, 1),
+                ('-', _) => (Tok::Minus, 1),
+                ('*', _) => (Tok::Star, 1),
+                ('/', _) => (Tok::Slash, 1),
+                ('%', _) => (Tok::Percent, 1),
+                _ => return Err(ScriptError::new(pos, format!("unexpected character '{}'", c))),
+            };
+            i += len;
+            tok
+        };
+        col += i - start;
+        tokens.push((tok, pos));
+    }
+
+    tokens.push((Tok::Eof, Pos { line, column: col }));
+    Ok(tokens)
+}
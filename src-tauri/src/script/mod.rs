@@ -0,0 +1,188 @@
+use crate::cancel::CancelToken;
+use crate::input::InputBackend;
+use serde::Serialize;
+use std::fmt;
+
+mod interp;
+mod lexer;
+mod parser;
+
+pub use parser::Program;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MACRO SCRIPTS — a small text language for macros
+// ═══════════════════════════════════════════════════════════════════════════
+//
+//   # tap Q ten times, faster each round
+//   let delay = 200
+//   repeat 10 {
+//       tap KeyQ hold 30ms
+//       wait delay
+//       if delay > 50 { delay = delay - 15 }
+//   }
+//   click right; move by 10, -5; drag 100, 200 to 300, 400; scroll -3
+//
+// Statements end at a newline or `;`. Values are integers; `200ms` and `2s`
+// are milliseconds, and comparisons yield 1 or 0.
+
+/// 1-based position in the script source
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Pos {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A syntax error, or a runtime error such as division by zero
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ScriptError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ScriptError {
+    fn new(pos: Pos, message: impl Into<String>) -> ScriptError {
+        ScriptError { line: pos.line, column: pos.column, message: message.into() }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Check a script for errors without running it
+pub fn parse(source: &str) -> Result<Program, ScriptError> {
+    parser::Parser::new(lexer::tokenize(source)?).program()
+}
+
+/// Run a parsed script; a set `cancel` stops it with a "cancelled" error
+pub fn run(input: &dyn InputBackend, program: &Program, cancel: &CancelToken) -> Result<(), ScriptError> {
+    interp::Interpreter::new(input, cancel).run(program)
+}
+
+pub fn run_source(input: &dyn InputBackend, source: &str, cancel: &CancelToken) -> Result<(), ScriptError> {
+    run(input, &parse(source)?, cancel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::mock::{InputEvent::*, MockBackend};
+    use crate::input::MouseButton::{Left, Right};
+    use std::time::Duration;
+
+    fn run_source(input: &dyn InputBackend, source: &str) -> Result<(), ScriptError> {
+        super::run_source(input, source, &CancelToken::new())
+    }
+
+    fn error_at(source: &str) -> (usize, usize, String) {
+        let e = parse(source).unwrap_err();
+        (e.line, e.column, e.message)
+    }
+
+    #[test]
+    fn repeat_with_clicks_and_waits() {
+        let mock = MockBackend::new();
+        run_source(&mock, "repeat 3 { click left; wait 200ms }").unwrap();
+
+        assert_eq!(mock.timeline(), vec![
+            (0, MouseDown(Left)), (30, MouseUp(Left)),
+            (230, MouseDown(Left)), (260, MouseUp(Left)),
+            (460, MouseDown(Left)), (490, MouseUp(Left)),
+        ]);
+        assert_eq!(mock.now(), Duration::from_millis(690));
+    }
+
+    #[test]
+    fn variables_loops_and_conditionals() {
+        let source = "
+            let n = 0
+            while n < 4 {
+                if n % 2 == 0 { tap KeyQ hold 10ms } else if n == 1 { click right hold 5 } else {
+                    scroll -n
+                }
+                n = n + 1
+            }
+        ";
+        let mock = MockBackend::new();
+        run_source(&mock, source).unwrap();
+
+        assert_eq!(mock.events(), vec![
+            KeyDown(0x51), KeyUp(0x51),
+            MouseDown(Right), MouseUp(Right),
+            KeyDown(0x51), KeyUp(0x51),
+            Scroll(-3),
+        ]);
+    }
+
+    #[test]
+    fn pointer_and_key_commands() {
+        let mock = MockBackend::new();
+        run_source(&mock, "press ShiftLeft\nmove 10, 20 * 2\nmove by -5, 5\nmouse down x1\nmouse up x1\nrelease ShiftLeft").unwrap();
+
+        assert_eq!(mock.events(), vec![
+            KeyDown(0xA0), MoveAbsolute(10, 40), MoveRelative(-5, 5),
+            MouseDown(crate::input::MouseButton::X1), MouseUp(crate::input::MouseButton::X1),
+            KeyUp(0xA0),
+        ]);
+    }
+
+    #[test]
+    fn operator_precedence() {
+        let mock = MockBackend::new();
+        run_source(&mock, "let a = 2 + 3 * 4 - (1 + 1)\nif a == 12 and not a < 0 or false { scroll a }").unwrap();
+        assert_eq!(mock.events(), vec![Scroll(12)]);
+    }
+
+    #[test]
+    fn syntax_errors_report_line_and_column() {
+        assert_eq!(error_at("repeat 10 {\n  click left\n"), (3, 1, "expected '}', found end of script".into()));
+        assert_eq!(error_at("wait 5\ntap KeyZZ"), (2, 5, "unknown key 'KeyZZ'".into()));
+        assert_eq!(error_at("click sideways"), (1, 7, "unknown mouse button 'sideways'".into()));
+        assert_eq!(error_at("wait 3h"), (1, 6, "unknown unit 'h' (use ms or s)".into()));
+        assert_eq!(error_at("  jump 5"), (1, 3, "unknown command 'jump'".into()));
+        assert_eq!(error_at("n = 1"), (1, 1, "variable 'n' is not declared (use 'let')".into()));
+        assert_eq!(error_at("wait 5 5"), (1, 8, "expected end of statement, found '5'".into()));
+        assert_eq!(error_at("let x = 1 $"), (1, 11, "unexpected character '$'".into()));
+        assert_eq!(error_at("}"), (1, 1, "'}' without matching '{'".into()));
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error_not_a_crash() {
+        let deep = 300_000;
+        for source in [
+            format!("wait {}1", "-".repeat(deep)),
+            format!("wait {}1{}", "(".repeat(deep), ")".repeat(deep)),
+            format!("wait {}", vec!["1"; deep].join(" + ")),
+            format!("wait {}1", "not ".repeat(deep)),
+            "repeat 1 {".repeat(deep),
+            format!("if 1 {{ }}{}", " else if 1 { }".repeat(deep)),
+        ] {
+            let err = parse(&source).unwrap_err();
+            assert_eq!(err.message, "nested more than 64 levels deep");
+        }
+        let fine = format!("wait {}1{}", "(".repeat(60), ")".repeat(60));
+        run_source(&MockBackend::new(), &fine).unwrap();
+    }
+
+    #[test]
+    fn runtime_errors_stop_the_script() {
+        let mock = MockBackend::new();
+        let err = run_source(&mock, "let d = 0\nclick\nwait 10 / d\nclick").unwrap_err();
+
+        assert_eq!(err, ScriptError { line: 3, column: 9, message: "division by zero".into() });
+        assert_eq!(mock.events(), vec![MouseDown(Left), MouseUp(Left)]);
+        assert_eq!(run_source(&mock, "wait -1").unwrap_err().message, "wait must not be negative");
+    }
+
+    #[test]
+    fn cancel_stops_endless_loops() {
+        let mock = MockBackend::new();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let err = super::run_source(&mock, "while true { }", &cancel).unwrap_err();
+        assert_eq!((err.line, err.column, err.message.as_str()), (1, 1, "cancelled"));
+    }
+}
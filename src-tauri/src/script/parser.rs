@@ -0,0 +1,475 @@
+use super::lexer::Tok;
+use super::{Pos, ScriptError};
+use crate::input::MouseButton;
+use crate::keys::KeyId;
+use std::collections::HashSet;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// AST
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinOp {
+    Add, Sub, Mul, Div, Rem,
+    Eq, Ne, Lt, Le, Gt, Ge,
+    And, Or,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExprKind {
+    Int(i64),
+    Var(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub pos: Pos,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StmtKind {
+    Let(String, Expr),
+    Assign(String, Expr),
+    Repeat(Expr, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    Tap(KeyId, Option<Expr>),
+    Press(KeyId),
+    Release(KeyId),
+    Click(MouseButton, Option<Expr>),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    Move { x: Expr, y: Expr, relative: bool },
+    Drag { from: (Expr, Expr), to: (Expr, Expr) },
+    Scroll(Expr),
+    Wait(Expr),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub pos: Pos,
+}
+
+pub type Program = Vec<Stmt>;
+
+const KEYWORDS: &[&str] = &[
+    "let", "repeat", "while", "if", "else", "and", "or", "not",
+    "tap", "press", "release", "hold", "click", "mouse", "down", "up",
+    "move", "by", "drag", "to", "scroll", "wait",
+];
+
+// ═══════════════════════════════════════════════════════════════════════════
+// PARSER — recursive descent over the token list
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Deepest nesting of blocks, parentheses and operators. The parser and the
+/// interpreter recurse once per level, so this keeps a hostile script from
+/// overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+pub struct Parser {
+    tokens: Vec<(Tok, Pos)>,
+    at: usize,
+    /// Variables declared so far; scripts have a single flat scope
+    declared: HashSet<String>,
+    /// Blocks and expressions the parser is inside of
+    depth: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<(Tok, Pos)>) -> Parser {
+        Parser { tokens, at: 0, declared: HashSet::new(), depth: 0 }
+    }
+
+    pub fn program(mut self) -> Result<Program, ScriptError> {
+        let stmts = self.statements()?;
+        match self.peek() {
+            Tok::Eof => Ok(stmts),
+            Tok::RBrace => Err(self.error("'}' without matching '{'")),
+            _ => Err(self.unexpected("a statement")),
+        }
+    }
+
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.at].0
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens[self.at].1
+    }
+
+    fn advance(&mut self) -> (Tok, Pos) {
+        let tok = self.tokens[self.at].clone();
+        if self.at < self.tokens.len() - 1 {
+            self.at += 1;
+        }
+        tok
+    }
+
+    fn error(&self, message: impl Into<String>) -> ScriptError {
+        ScriptError::new(self.pos(), message)
+    }
+
+    fn unexpected(&self, expected: &str) -> ScriptError {
+        self.error(format!("expected {}, found {}", expected, self.peek().describe()))
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Tok::Ident(s) if s == word)
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if self.is_word(word) {
+            self.advance();
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, tok: Tok) -> Result<(), ScriptError> {
+        if *self.peek() == tok {
+            self.advance();
+            return Ok(());
+        }
+        Err(self.unexpected(&tok.describe()))
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), ScriptError> {
+        if self.eat_word(word) {
+            return Ok(());
+        }
+        Err(self.unexpected(&format!("'{}'", word)))
+    }
+
+    /// Run `parse` one nesting level further in
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Parser) -> Result<T, ScriptError>) -> Result<T, ScriptError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(too_deep(self.pos()));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// `lhs op rhs`, unless operator chains make it too deep to evaluate
+    fn binary(&self, op: BinOp, lhs: Expr, rhs: Expr, pos: Pos) -> Result<Expr, ScriptError> {
+        if self.depth + 1 + depth(&lhs).max(depth(&rhs)) > MAX_DEPTH {
+            return Err(too_deep(pos));
+        }
+        Ok(Expr { kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)), pos })
+    }
+
+    fn skip_separators(&mut self) {
+        while *self.peek() == Tok::Sep {
+            self.advance();
+        }
+    }
+
+    /// Statements up to (not including) `}` or the end of the script
+    fn statements(&mut self) -> Result<Vec<Stmt>, ScriptError> {
+        let mut stmts = Vec::new();
+        loop {
+            self.skip_separators();
+            if matches!(self.peek(), Tok::RBrace | Tok::Eof) {
+                return Ok(stmts);
+            }
+            stmts.push(self.statement()?);
+            match self.peek() {
+                Tok::Sep | Tok::RBrace | Tok::Eof => {}
+                _ => return Err(self.unexpected("end of statement")),
+            }
+        }
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ScriptError> {
+        self.expect(Tok::LBrace)?;
+        let stmts = self.nested(Parser::statements)?;
+        if *self.peek() != Tok::RBrace {
+            return Err(self.unexpected("'}'"));
+        }
+        self.advance();
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ScriptError> {
+        let pos = self.pos();
+        let word = match self.peek() {
+            Tok::Ident(word) => word.clone(),
+            _ => return Err(self.unexpected("a statement")),
+        };
+        self.advance();
+
+        let kind = match word.as_str() {
+            "let" => {
+                let name = self.variable_name()?;
+                self.expect(Tok::Assign)?;
+                let value = self.expr()?;
+                self.declared.insert(name.clone());
+                StmtKind::Let(name, value)
+            }
+            "repeat" => {
+                let times = self.expr()?;
+                StmtKind::Repeat(times, self.block()?)
+            }
+            "while" => {
+                let cond = self.expr()?;
+                StmtKind::While(cond, self.block()?)
+            }
+            "if" => self.if_rest()?,
+            "tap" => {
+                let key = self.key()?;
+                let hold = if self.eat_word("hold") { Some(self.expr()?) } else { None };
+                StmtKind::Tap(key, hold)
+            }
+            "press" => StmtKind::Press(self.key()?),
+            "release" => StmtKind::Release(self.key()?),
+            "click" => {
+                let button = if matches!(self.peek(), Tok::Ident(w) if w != "hold") {
+                    self.button()?
+                } else {
+                    MouseButton::Left
+                };
+                let hold = if self.eat_word("hold") { Some(self.expr()?) } else { None };
+                StmtKind::Click(button, hold)
+            }
+            "mouse" => {
+                if self.eat_word("down") {
+                    StmtKind::MouseDown(self.button()?)
+                } else if self.eat_word("up") {
+                    StmtKind::MouseUp(self.button()?)
+                } else {
+                    return Err(self.unexpected("'down' or 'up'"));
+                }
+            }
+            "move" => {
+                let relative = self.eat_word("by");
+                let (x, y) = self.point()?;
+                StmtKind::Move { x, y, relative }
+            }
+            "drag" => {
+                let from = self.point()?;
+                self.expect_word("to")?;
+                let to = self.point()?;
+                StmtKind::Drag { from, to }
+            }
+            "scroll" => StmtKind::Scroll(self.expr()?),
+            "wait" => StmtKind::Wait(self.expr()?),
+            "else" => return Err(ScriptError::new(pos, "'else' without 'if'")),
+            name if !KEYWORDS.contains(&name) && *self.peek() == Tok::Assign => {
+                if !self.declared.contains(name) {
+                    return Err(ScriptError::new(pos, format!("variable '{}' is not declared (use 'let')", name)));
+                }
+                self.advance();
+                StmtKind::Assign(name.to_string(), self.expr()?)
+            }
+            other => return Err(ScriptError::new(pos, format!("unknown command '{}'", other))),
+        };
+        Ok(Stmt { kind, pos })
+    }
+
+    /// Everything after `if`, including any `else` / `else if` chain
+    fn if_rest(&mut self) -> Result<StmtKind, ScriptError> {
+        let cond = self.expr()?;
+        let then = self.block()?;
+        // Allow `}` and `else` on different lines
+        let save = self.at;
+        self.skip_separators();
+        if !self.eat_word("else") {
+            self.at = save;
+            return Ok(StmtKind::If(cond, then, Vec::new()));
+        }
+        let otherwise = if self.is_word("if") {
+            let pos = self.pos();
+            self.advance();
+            vec![Stmt { kind: self.nested(Parser::if_rest)?, pos }]
+        } else {
+            self.block()?
+        };
+        Ok(StmtKind::If(cond, then, otherwise))
+    }
+
+    fn variable_name(&mut self) -> Result<String, ScriptError> {
+        match self.peek().clone() {
+            Tok::Ident(name) if KEYWORDS.contains(&name.as_str()) => {
+                Err(self.error(format!("'{}' is a keyword and cannot be a variable name", name)))
+            }
+            Tok::Ident(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.unexpected("a variable name")),
+        }
+    }
+
+    fn key(&mut self) -> Result<KeyId, ScriptError> {
+        match self.peek().clone() {
+            Tok::Ident(name) => {
+                let key = KeyId::parse(&name).map_err(|e| self.error(e))?;
+                self.advance();
+                Ok(key)
+            }
+            _ => Err(self.unexpected("a key name")),
+        }
+    }
+
+    fn button(&mut self) -> Result<MouseButton, ScriptError> {
+        let button = match self.peek() {
+            Tok::Ident(name) => match name.as_str() {
+                "left" => MouseButton::Left,
+                "right" => MouseButton::Right,
+                "middle" => MouseButton::Middle,
+                "x1" => MouseButton::X1,
+                "x2" => MouseButton::X2,
+                other => return Err(self.error(format!("unknown mouse button '{}'", other))),
+            },
+            _ => return Err(self.unexpected("a mouse button")),
+        };
+        self.advance();
+        Ok(button)
+    }
+
+    fn point(&mut self) -> Result<(Expr, Expr), ScriptError> {
+        let x = self.expr()?;
+        self.expect(Tok::Comma)?;
+        Ok((x, self.expr()?))
+    }
+
+    // ─── Expressions, lowest precedence first ───────────────────────────
+
+    pub fn expr(&mut self) -> Result<Expr, ScriptError> {
+        self.or_expr()
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.and_expr()?;
+        while self.is_word("or") {
+            let pos = self.pos();
+            self.advance();
+            let rhs = self.and_expr()?;
+            lhs = self.binary(BinOp::Or, lhs, rhs, pos)?;
+        }
+        Ok(lhs)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.not_expr()?;
+        while self.is_word("and") {
+            let pos = self.pos();
+            self.advance();
+            let rhs = self.not_expr()?;
+            lhs = self.binary(BinOp::And, lhs, rhs, pos)?;
+        }
+        Ok(lhs)
+    }
+
+    fn not_expr(&mut self) -> Result<Expr, ScriptError> {
+        if self.is_word("not") {
+            let pos = self.pos();
+            self.advance();
+            let inner = self.nested(Parser::not_expr)?;
+            return Ok(Expr { kind: ExprKind::Not(Box::new(inner)), pos });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ScriptError> {
+        let lhs = self.additive()?;
+        let op = match self.peek() {
+            Tok::Eq => BinOp::Eq,
+            Tok::Ne => BinOp::Ne,
+            Tok::Lt => BinOp::Lt,
+            Tok::Le => BinOp::Le,
+            Tok::Gt => BinOp::Gt,
+            Tok::Ge => BinOp::Ge,
+            _ => return Ok(lhs),
+        };
+        let pos = self.pos();
+        self.advance();
+        let rhs = self.additive()?;
+        self.binary(op, lhs, rhs, pos)
+    }
+
+    fn additive(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Tok::Plus => BinOp::Add,
+                Tok::Minus => BinOp::Sub,
+                _ => return Ok(lhs),
+            };
+            let pos = self.pos();
+            self.advance();
+            let rhs = self.multiplicative()?;
+            lhs = self.binary(op, lhs, rhs, pos)?;
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ScriptError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Tok::Star => BinOp::Mul,
+                Tok::Slash => BinOp::Div,
+                Tok::Percent => BinOp::Rem,
+                _ => return Ok(lhs),
+            };
+            let pos = self.pos();
+            self.advance();
+            let rhs = self.unary()?;
+            lhs = self.binary(op, lhs, rhs, pos)?;
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ScriptError> {
+        if *self.peek() == Tok::Minus {
+            let pos = self.pos();
+            self.advance();
+            let inner = self.nested(Parser::unary)?;
+            return Ok(Expr { kind: ExprKind::Neg(Box::new(inner)), pos });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, ScriptError> {
+        let pos = self.pos();
+        let kind = match self.peek().clone() {
+            Tok::Int(n) | Tok::Millis(n) => ExprKind::Int(n),
+            Tok::Ident(name) if name == "true" => ExprKind::Int(1),
+            Tok::Ident(name) if name == "false" => ExprKind::Int(0),
+            Tok::Ident(name) if !KEYWORDS.contains(&name.as_str()) => {
+                if !self.declared.contains(&name) {
+                    return Err(self.error(format!("variable '{}' is not declared (use 'let')", name)));
+                }
+                ExprKind::Var(name)
+            }
+            Tok::LParen => {
+                self.advance();
+                let inner = self.nested(Parser::expr)?;
+                self.expect(Tok::RParen)?;
+                return Ok(inner);
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.advance();
+        Ok(Expr { kind, pos })
+    }
+}
+
+fn too_deep(pos: Pos) -> ScriptError {
+    ScriptError::new(pos, format!("nested more than {} levels deep", MAX_DEPTH))
+}
+
+/// Levels of an expression tree, counting negations and `not`
+fn depth(expr: &Expr) -> usize {
+    match &expr.kind {
+        ExprKind::Int(_) | ExprKind::Var(_) => 1,
+        ExprKind::Neg(inner) | ExprKind::Not(inner) => 1 + depth(inner),
+        ExprKind::Binary(_, lhs, rhs) => 1 + depth(lhs).max(depth(rhs)),
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TRIGGERS — when a held hotkey counts as pressed
+// ═══════════════════════════════════════════════════════════════════════════
+//
+// The input listener polls every hotkey; a `TriggerState` per binding turns
+// those samples into the moments the binding acts on.
+
+/// How a binding reacts to its hotkey
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Trigger {
+    /// Fires when the hotkey goes down
+    #[default]
+    Press,
+    /// Fires when the hotkey comes up
+    Release,
+    /// Active for as long as the hotkey is held
+    Hold,
+    /// Fires on the second press within `window_ms` of the first
+    DoubleTap { window_ms: u64 },
+    /// Fires once the hotkey has been held for `hold_ms`
+    LongPress { hold_ms: u64 },
+}
+
+impl Trigger {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Trigger::DoubleTap { window_ms: 0 } => Err("double-tap window must be more than 0 ms".into()),
+            Trigger::LongPress { hold_ms: 0 } => Err("long-press time must be more than 0 ms".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerEvent {
+    /// The binding acts once (every trigger but hold)
+    Fire,
+    /// Hold: the hotkey went down
+    HoldStart,
+    /// Hold: the hotkey came up
+    HoldEnd,
+}
+
+/// Per-binding state between samples
+#[derive(Clone, Debug, Default)]
+pub struct TriggerState {
+    down: bool,
+    pressed_at: u64,
+    /// Time of a press still waiting for its double-tap partner
+    first_tap: Option<u64>,
+    long_press_fired: bool,
+}
+
+impl TriggerState {
+    /// Feed whether the hotkey is held at `now_ms`. Call on every poll, held
+    /// or not, so long presses fire while the key is still down.
+    pub fn update(&mut self, trigger: Trigger, now_ms: u64, held: bool) -> Option<TriggerEvent> {
+        let pressed = held && !self.down;
+        let released = !held && self.down;
+        self.down = held;
+        if pressed {
+            self.pressed_at = now_ms;
+            self.long_press_fired = false;
+        }
+
+        match trigger {
+            Trigger::Press => pressed.then_some(TriggerEvent::Fire),
+            Trigger::Release => released.then_some(TriggerEvent::Fire),
+            Trigger::Hold if pressed => Some(TriggerEvent::HoldStart),
+            Trigger::Hold if released => Some(TriggerEvent::HoldEnd),
+            Trigger::Hold => None,
+            Trigger::DoubleTap { window_ms } => {
+                if !pressed {
+                    return None;
+                }
+                match self.first_tap.take() {
+                    Some(first) if now_ms - first <= window_ms => Some(TriggerEvent::Fire),
+                    // Too slow: this press starts a new pair
+                    _ => {
+                        self.first_tap = Some(now_ms);
+                        None
+                    }
+                }
+            }
+            Trigger::LongPress { hold_ms } => {
+                if held && !self.long_press_fired && now_ms - self.pressed_at >= hold_ms {
+                    self.long_press_fired = true;
+                    return Some(TriggerEvent::Fire);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TriggerEvent::*;
+
+    /// Run a key timeline of (ms, held) samples, returning what fired when
+    fn run(trigger: Trigger, timeline: &[(u64, bool)]) -> Vec<(u64, TriggerEvent)> {
+        let mut state = TriggerState::default();
+        timeline
+            .iter()
+            .filter_map(|&(t, held)| state.update(trigger, t, held).map(|e| (t, e)))
+            .collect()
+    }
+
+    /// Poll every 10 ms up to `end`, holding the key during the given spans
+    fn polled(end: u64, spans: &[(u64, u64)]) -> Vec<(u64, bool)> {
+        (0..=end / 10)
+            .map(|i| i * 10)
+            .map(|t| (t, spans.iter().any(|&(from, to)| (from..to).contains(&t))))
+            .collect()
+    }
+
+    #[test]
+    fn press_and_release_fire_on_their_edge() {
+        let taps = polled(300, &[(50, 100), (200, 250)]);
+        assert_eq!(run(Trigger::Press, &taps), vec![(50, Fire), (200, Fire)]);
+        assert_eq!(run(Trigger::Release, &taps), vec![(100, Fire), (250, Fire)]);
+    }
+
+    #[test]
+    fn hold_spans_the_press() {
+        let taps = polled(300, &[(50, 120), (200, 210)]);
+        assert_eq!(run(Trigger::Hold, &taps), vec![(50, HoldStart), (120, HoldEnd), (200, HoldStart), (210, HoldEnd)]);
+    }
+
+    #[test]
+    fn double_tap_needs_two_presses_within_the_window() {
+        let trigger = Trigger::DoubleTap { window_ms: 250 };
+        assert_eq!(run(trigger, &polled(400, &[(0, 50), (200, 250)])), vec![(200, Fire)]);
+        // Too far apart: the second press starts a new pair instead
+        assert_eq!(run(trigger, &polled(700, &[(0, 50), (300, 350), (500, 550)])), vec![(500, Fire)]);
+        // A third quick tap does not fire again on its own
+        assert_eq!(run(trigger, &polled(700, &[(0, 30), (100, 130), (200, 230)])), vec![(100, Fire)]);
+    }
+
+    #[test]
+    fn long_press_fires_once_after_the_hold_time() {
+        let trigger = Trigger::LongPress { hold_ms: 500 };
+        assert_eq!(run(trigger, &polled(1_500, &[(100, 1_200)])), vec![(600, Fire)]);
+        // Released early: nothing, and the next press starts timing afresh
+        assert_eq!(run(trigger, &polled(1_500, &[(0, 400), (500, 1_100)])), vec![(1_000, Fire)]);
+    }
+
+    #[test]
+    fn serializes_with_its_timing() {
+        let trigger = Trigger::DoubleTap { window_ms: 300 };
+        let json = serde_json::to_string(&trigger).unwrap();
+        assert_eq!(json, r#"{"type":"doubleTap","windowMs":300}"#);
+        assert_eq!(serde_json::from_str::<Trigger>(&json).unwrap(), trigger);
+        assert_eq!(serde_json::from_str::<Trigger>(r#"{"type":"hold"}"#).unwrap(), Trigger::Hold);
+
+        assert!(Trigger::LongPress { hold_ms: 0 }.validate().is_err());
+        assert!(Trigger::Press.validate().is_ok());
+    }
+}
@@ -0,0 +1,38 @@
+//! An Xlib display connection, shared by the XTEST input backend and the
+//! focused-window lookup. Xlib itself is loaded at runtime by each user.
+
+use std::ffi::CString;
+use std::os::raw::c_int;
+use std::ptr;
+use x11_dl::xlib::{Display, Xlib};
+
+/// Raw display connection, closed on drop. Only ever used while holding the
+/// owner's mutex.
+pub struct Connection {
+    pub display: *mut Display,
+    close: unsafe extern "C" fn(*mut Display) -> c_int,
+}
+
+unsafe impl Send for Connection {}
+
+impl Connection {
+    /// Connect to `display`, or to `$DISPLAY` when `None`.
+    pub fn open(xlib: &Xlib, display: Option<&str>) -> Result<Connection, String> {
+        let name = display
+            .map(|d| CString::new(d).map_err(|e| e.to_string()))
+            .transpose()?;
+        let display = unsafe {
+            (xlib.XOpenDisplay)(name.as_ref().map_or(ptr::null(), |n| n.as_ptr()))
+        };
+        if display.is_null() {
+            return Err("cannot open X display".into());
+        }
+        Ok(Connection { display, close: xlib.XCloseDisplay })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe { (self.close)(self.display) };
+    }
+}
@@ -1,6 +1,8 @@
 import { useEffect, useState } from "react";
 import { Page as SidebarPage } from "./components/Sidebar";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import Controls from "./components/Controls";
 import Macro from "./components/Macro";
 import TitleBar from "./components/TitleBar";
@@ -15,11 +17,15 @@ import "./index.css";
 function App() {
   const [isOverlay, setIsOverlay] = useState(false);
   const [page, setPage] = useState<SidebarPage>("home");
+  const [configWarning, setConfigWarning] = useState<string | null>(null);
+  const [overlaps, setOverlaps] = useState<string[]>([]);
 
   useEffect(() => {
     const label = getCurrentWindow().label;
     if (label === 'overlay') {
       setIsOverlay(true);
+    } else {
+      invoke<string | null>('take_config_warning').then(setConfigWarning).catch(console.error);
     }
 
     const handleContextMenu = (e: MouseEvent) => { e.preventDefault(); };
@@ -37,9 +43,21 @@ function App() {
       }
     };
 
+    // Hand edits to settings.json that could not be applied
+    const unlistenReload = listen<string>("config-reload-failed", (event) => {
+      setConfigWarning(`settings.json was changed but not reloaded (${event.payload}); the previous settings are still in use.`);
+    });
+
+    // Sent after every accepted settings change; an empty list clears the banner
+    const unlistenBindings = listen<{ message: string }[]>("binding-warnings", (event) => {
+      setOverlaps(event.payload.map((c) => c.message));
+    });
+
     document.addEventListener("contextmenu", handleContextMenu);
     document.addEventListener("keydown", handleKeyDown);
     return () => {
+      unlistenReload.then((fn) => fn());
+      unlistenBindings.then((fn) => fn());
       document.removeEventListener("contextmenu", handleContextMenu);
       document.removeEventListener("keydown", handleKeyDown);
     };
@@ -53,6 +71,25 @@ function App() {
     <>
       <TitleBar />
       <Layout activePage={page} setPage={setPage}>
+        {configWarning && (
+          <div className="mb-4 p-3 rounded-xl border border-amber-500/30 bg-amber-500/10 text-xs text-amber-300 flex items-start justify-between gap-3">
+            <span>{configWarning}</span>
+            <button onClick={() => setConfigWarning(null)} className="font-bold text-amber-400 hover:text-amber-200">
+              Dismiss
+            </button>
+          </div>
+        )}
+        {overlaps.length > 0 && (
+          <div className="mb-4 p-3 rounded-xl border border-amber-500/30 bg-amber-500/10 text-xs text-amber-300 flex items-start justify-between gap-3">
+            <div>
+              <span className="font-bold block">Some hotkeys overlap:</span>
+              {overlaps.map((message) => <span key={message} className="block">{message}</span>)}
+            </div>
+            <button onClick={() => setOverlaps([])} className="font-bold text-amber-400 hover:text-amber-200">
+              Dismiss
+            </button>
+          </div>
+        )}
         {page === "home" && <Controls />}
         {page === "clicker" && <ClickerConfig />}
         {page === "macro" && <Macro />}
@@ -1,8 +1,11 @@
-import React, { useState, useEffect } from 'react';
+import React, { useState, useEffect, useCallback } from 'react';
 import { Activity, Keyboard } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { clsx, type ClassValue } from 'clsx';
 import { twMerge } from 'tailwind-merge';
+import { errorText, recordKey } from '../hotkey';
+import TriggerPicker, { type Trigger } from './TriggerPicker';
 
 function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs));
@@ -46,71 +49,64 @@ const CLICK_MODES = [
 ] as const;
 
 const ClickerConfig: React.FC = () => {
-    const loadState = <T,>(key: string, defaultVal: T): T => {
-        const saved = localStorage.getItem(key);
-        if (saved !== null) {
-            try { return JSON.parse(saved); } catch (_e) { return defaultVal; }
-        }
-        return defaultVal;
-    }
-
-    const [cps, setCps] = useState(() => loadState('cliky_cps', 10));
-    const [randomness, setRandomness] = useState(() => loadState('cliky_randomness', 0));
-    const [humanizationEnabled, setHumanizationEnabled] = useState(() => loadState('cliky_humanization', true));
-    const [toggleKey, setToggleKey] = useState(() => loadState('cliky_key', 'F6'));
-    const [clickMode, setClickMode] = useState(() => loadState('cliky_click_mode', 'left'));
+    const [cps, setCps] = useState(10);
+    const [randomness, setRandomness] = useState(0);
+    const [humanizationEnabled, setHumanizationEnabled] = useState(true);
+    const [toggleKey, setToggleKey] = useState('F6');
+    const [toggleTrigger, setToggleTrigger] = useState<Trigger>({ type: 'press' });
+    const [clickMode, setClickMode] = useState('left');
+    const [loaded, setLoaded] = useState(false);
     const [isRecordingKey, setIsRecordingKey] = useState(false);
+    const [saveError, setSaveError] = useState<string | null>(null);
+
+    // Load from backend (the active profile in settings.json)
+    const loadConfig = useCallback(() => {
+        invoke<[boolean, number, number, boolean, string, string, Trigger]>('get_clicker_state')
+            .then(([, cps, randomness, humanization, key, mode, trigger]) => {
+                setCps(cps);
+                setRandomness(randomness);
+                setHumanizationEnabled(humanization);
+                setToggleKey(key);
+                setClickMode(mode);
+                setToggleTrigger(trigger);
+            })
+            .catch(console.error)
+            .finally(() => setLoaded(true));
+    }, []);
 
-    // Persist & Push Config
     useEffect(() => {
-        localStorage.setItem('cliky_cps', JSON.stringify(cps));
-        localStorage.setItem('cliky_randomness', JSON.stringify(randomness));
-        localStorage.setItem('cliky_humanization', JSON.stringify(humanizationEnabled));
-        localStorage.setItem('cliky_key', JSON.stringify(toggleKey));
-        localStorage.setItem('cliky_click_mode', JSON.stringify(clickMode));
+        loadConfig();
+        const unlistenProfile = listen('profile-changed', loadConfig);
+        const unlistenReload = listen('config-reloaded', loadConfig);
+        return () => {
+            unlistenProfile.then((fn) => fn());
+            unlistenReload.then((fn) => fn());
+        };
+    }, [loadConfig]);
 
-        invoke('update_config', { cps, randomness, humanizationEnabled, toggleKey, clickMode }).catch(console.error);
-    }, [cps, randomness, humanizationEnabled, toggleKey, clickMode]);
+    // Push config to backend
+    useEffect(() => {
+        if (!loaded) return;
+        invoke('update_config', { cps, randomness, humanizationEnabled, toggleKey, toggleTrigger, clickMode })
+            .then(() => setSaveError(null))
+            .catch((e) => setSaveError(errorText(e)));
+    }, [cps, randomness, humanizationEnabled, toggleKey, toggleTrigger, clickMode, loaded]);
 
     const handleKeyRecord = () => {
         setIsRecordingKey(true);
-        const cleanup = () => {
-            window.removeEventListener('keyup', keyUpHandler);
-            window.removeEventListener('keydown', keyDownHandler);
-            window.removeEventListener('mouseup', mouseUpHandler);
-            window.removeEventListener('mousedown', mouseDownHandler);
-        };
-        const keyUpHandler = (e: KeyboardEvent) => {
-            e.preventDefault();
-            setToggleKey(e.code);
+        recordKey((key) => {
+            setToggleKey(key);
             setIsRecordingKey(false);
-            cleanup();
-        };
-        const keyDownHandler = (e: KeyboardEvent) => { e.preventDefault(); };
-        const mouseUpHandler = (e: MouseEvent) => {
-            e.preventDefault();
-            let key = "";
-            switch (e.button) {
-                case 3: key = "Mouse4"; break;
-                case 4: key = "Mouse5"; break;
-                case 2: key = "Mouse3"; break;
-                default: return;
-            }
-            if (key) {
-                setToggleKey(key);
-                setIsRecordingKey(false);
-                cleanup();
-            }
-        };
-        const mouseDownHandler = (e: MouseEvent) => { if (e.button > 2) e.preventDefault(); };
-        window.addEventListener('keyup', keyUpHandler);
-        window.addEventListener('keydown', keyDownHandler);
-        window.addEventListener('mouseup', mouseUpHandler);
-        window.addEventListener('mousedown', mouseDownHandler);
+        }, true);
     };
 
     return (
         <div className="grid grid-cols-1 md:grid-cols-2 gap-6 pb-20">
+            {saveError && (
+                <div className="md:col-span-2 p-3 rounded-xl border border-red-500/30 bg-red-500/10 text-xs text-red-300">
+                    Settings were not saved: {saveError}
+                </div>
+            )}
             {/* Clicker Config */}
             <Card title="Clicker Configuration" icon={Activity} className="md:col-span-2">
                 <div className="space-y-8">
@@ -178,6 +174,11 @@ const ClickerConfig: React.FC = () => {
                         {isRecordingKey ? "PRESS KEY" : toggleKey}
                     </button>
                 </div>
+                <div className="flex items-center justify-between mt-3">
+                    <span className="text-sm font-bold text-zinc-300">Trigger</span>
+                    <TriggerPicker value={toggleTrigger} onChange={setToggleTrigger} />
+                </div>
+                <p className="text-[10px] text-zinc-500 mt-2">While Held clicks only as long as the key is down; the others toggle clicking.</p>
             </Card>
         </div>
     );
@@ -10,20 +10,12 @@ function cn(...inputs: ClassValue[]) {
 }
 
 const Controls: React.FC = () => {
-    const loadState = <T,>(key: string, defaultVal: T): T => {
-        const saved = localStorage.getItem(key);
-        if (saved !== null) {
-            try { return JSON.parse(saved); } catch (_e) { return defaultVal; }
-        }
-        return defaultVal;
-    }
-
     const [isRunning, setIsRunning] = useState(false);
 
 
     // We read config just for display
-    const [targetCps] = useState(() => loadState('cliky_cps', 10));
-    const [toggleKey] = useState(() => loadState('cliky_key', 'F6'));
+    const [targetCps, setTargetCps] = useState(10);
+    const [toggleKey, setToggleKey] = useState('F6');
 
     // State sync
     useEffect(() => {
@@ -31,18 +23,30 @@ const Controls: React.FC = () => {
             'clicker-state-changed',
             (event) => {
                 setIsRunning(event.payload.running);
+                setTargetCps(event.payload.cps);
                 // event.payload.cps might be live or target depending on backend implementation
                 // For now backend usually sends actual target or 0.
                 // Let's assume dashboard just shows running state mainly.
             }
         );
 
-        invoke<[boolean, number, number, boolean, string, string]>('get_clicker_state')
-            .then(([running]) => setIsRunning(running))
-            .catch(console.error);
+        const loadState = () => {
+            invoke<[boolean, number, number, boolean, string, string]>('get_clicker_state')
+                .then(([running, cps, , , key]) => {
+                    setIsRunning(running);
+                    setTargetCps(cps);
+                    setToggleKey(key);
+                })
+                .catch(console.error);
+        };
+        loadState();
+        const unlistenProfile = listen('profile-changed', loadState);
+        const unlistenReload = listen('config-reloaded', loadState);
 
         return () => {
             unlisten.then((fn) => fn());
+            unlistenProfile.then((fn) => fn());
+            unlistenReload.then((fn) => fn());
         };
     }, []);
 
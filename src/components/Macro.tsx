@@ -1,8 +1,12 @@
 import React, { useState, useEffect, useCallback } from 'react';
-import { Crosshair, Keyboard, Settings2 } from 'lucide-react';
+import { Crosshair, Keyboard, ListOrdered, Plus, Trash2, ChevronUp, ChevronDown, Circle, FileCode, Play, Square, RefreshCw, OctagonX, Download } from 'lucide-react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { clsx, type ClassValue } from 'clsx';
 import { twMerge } from 'tailwind-merge';
+import { downloadText } from '../download';
+import { errorText, recordKey } from '../hotkey';
+import TriggerPicker, { type Trigger } from './TriggerPicker';
 
 function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs));
@@ -20,241 +24,554 @@ const Card = ({ title, icon: Icon, children, className }: { title: string, icon:
     </div>
 );
 
-type CapturingTarget = 'safe_pocket' | 'quick_use' | null;
+type MouseButton = 'left' | 'right' | 'middle' | 'x1' | 'x2';
 
-const Macro: React.FC = () => {
-    const loadState = <T,>(key: string, defaultVal: T): T => {
-        const saved = localStorage.getItem(key);
-        if (saved !== null) {
-            try { return JSON.parse(saved); } catch (_e) { return defaultVal; }
-        }
-        return defaultVal;
+export type MacroStep =
+    | { type: 'keyDown'; key: string }
+    | { type: 'keyUp'; key: string }
+    | { type: 'keyTap'; key: string; holdMs: number }
+    | { type: 'mouseDown'; button: MouseButton }
+    | { type: 'mouseUp'; button: MouseButton }
+    | { type: 'click'; button: MouseButton; holdMs: number }
+    | { type: 'move'; x: number; y: number; relative: boolean }
+    | { type: 'drag'; from: [number, number]; to: [number, number] }
+    | { type: 'scroll'; notches: number }
+    | { type: 'wait'; ms: number }
+    | { type: 'repeat'; times: number; steps: MacroStep[] }
+    | { type: 'script'; source: string };
+
+interface ScriptError {
+    line: number;
+    column: number;
+    message: string;
+}
+
+export interface MacroDef {
+    name: string;
+    hotkey: string;
+    trigger: Trigger;
+    steps: MacroStep[];
+}
+
+interface ScriptBinding {
+    file: string;
+    hotkey: string;
+    trigger: Trigger;
+}
+
+type Corner = 'topLeft' | 'topRight' | 'bottomLeft' | 'bottomRight';
+
+interface FailsafeConfig {
+    enabled: boolean;
+    corner: Corner;
+    margin: number;
+}
+
+const CORNERS: { value: Corner; label: string }[] = [
+    { value: 'topLeft', label: 'Top Left' },
+    { value: 'topRight', label: 'Top Right' },
+    { value: 'bottomLeft', label: 'Bottom Left' },
+    { value: 'bottomRight', label: 'Bottom Right' },
+];
+
+interface MacroConfig {
+    macros: MacroDef[];
+    recordKey: string;
+    recordMaxGapMs: number;
+    panicKey: string;
+    failsafe: FailsafeConfig;
+    scripts: ScriptBinding[];
+    scriptTimeLimitMs: number;
+}
+
+const STEP_TYPES: { value: MacroStep['type']; label: string }[] = [
+    { value: 'keyTap', label: 'Key Tap' },
+    { value: 'keyDown', label: 'Key Down' },
+    { value: 'keyUp', label: 'Key Up' },
+    { value: 'click', label: 'Click' },
+    { value: 'mouseDown', label: 'Mouse Down' },
+    { value: 'mouseUp', label: 'Mouse Up' },
+    { value: 'move', label: 'Move' },
+    { value: 'drag', label: 'Drag' },
+    { value: 'scroll', label: 'Scroll' },
+    { value: 'wait', label: 'Wait' },
+    { value: 'repeat', label: 'Repeat' },
+    { value: 'script', label: 'Script' },
+];
+
+const BUTTONS: MouseButton[] = ['left', 'right', 'middle', 'x1', 'x2'];
+
+const defaultStep = (type: MacroStep['type']): MacroStep => {
+    switch (type) {
+        case 'keyDown': case 'keyUp': return { type, key: 'KeyQ' };
+        case 'keyTap': return { type, key: 'KeyQ', holdMs: 30 };
+        case 'mouseDown': case 'mouseUp': return { type, button: 'left' };
+        case 'click': return { type, button: 'left', holdMs: 30 };
+        case 'move': return { type, x: 0, y: 0, relative: false };
+        case 'drag': return { type, from: [0, 0], to: [0, 0] };
+        case 'scroll': return { type, notches: 1 };
+        case 'wait': return { type, ms: 50 };
+        case 'repeat': return { type, times: 2, steps: [] };
+        case 'script': return { type, source: 'repeat 10 {\n    click left\n    wait 100ms\n}' };
+    }
+};
+
+/** Key picker; `chord` is for hotkeys, which may include modifiers */
+const KeyButton = ({ value, onChange, chord = false }: { value: string, onChange: (key: string) => void, chord?: boolean }) => {
+    const [recording, setRecording] = useState(false);
+    return (
+        <button
+            onClick={() => {
+                setRecording(true);
+                recordKey((key) => { onChange(key); setRecording(false); }, chord);
+            }}
+            className={cn(
+                "px-3 py-1.5 text-xs font-bold font-mono border rounded-lg transition-all min-w-[90px]",
+                recording ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse" : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:text-zinc-200"
+            )}
+        >
+            {recording ? "PRESS..." : value || "None"}
+        </button>
+    );
+};
+
+const NumberField = ({ label, value, onChange }: { label: string, value: number, onChange: (v: number) => void }) => (
+    <label className="flex items-center gap-1 text-[10px] font-bold text-zinc-500 uppercase">
+        {label}
+        <input
+            type="number"
+            value={value}
+            onChange={(e) => onChange(parseInt(e.target.value) || 0)}
+            className="w-16 px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs font-mono text-zinc-300"
+        />
+    </label>
+);
+
+const CaptureButton = ({ onCapture }: { onCapture: (x: number, y: number) => void }) => {
+    const [capturing, setCapturing] = useState(false);
+    return (
+        <button
+            title="Capture cursor position (press any key)"
+            disabled={capturing}
+            onClick={async () => {
+                setCapturing(true);
+                try {
+                    const [x, y] = await invoke<[number, number]>('capture_position');
+                    onCapture(x, y);
+                } catch (e) {
+                    console.error(e);
+                }
+                setCapturing(false);
+            }}
+            className={cn("p-1 rounded text-zinc-500 hover:text-zinc-200", capturing && "text-indigo-400 animate-pulse")}
+        >
+            <Crosshair className="w-3.5 h-3.5" />
+        </button>
+    );
+};
+
+const StepEditor = ({ step, onChange }: { step: MacroStep, onChange: (s: MacroStep) => void }) => {
+    switch (step.type) {
+        case 'keyDown': case 'keyUp':
+            return <KeyButton value={step.key} onChange={(key) => onChange({ ...step, key })} />;
+        case 'keyTap':
+            return (
+                <>
+                    <KeyButton value={step.key} onChange={(key) => onChange({ ...step, key })} />
+                    <NumberField label="Hold" value={step.holdMs} onChange={(holdMs) => onChange({ ...step, holdMs })} />
+                </>
+            );
+        case 'mouseDown': case 'mouseUp': case 'click':
+            return (
+                <>
+                    <select
+                        value={step.button}
+                        onChange={(e) => onChange({ ...step, button: e.target.value as MouseButton })}
+                        className="px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs font-mono text-zinc-300"
+                    >
+                        {BUTTONS.map((b) => <option key={b} value={b}>{b}</option>)}
+                    </select>
+                    {step.type === 'click' && (
+                        <NumberField label="Hold" value={step.holdMs} onChange={(holdMs) => onChange({ ...step, holdMs })} />
+                    )}
+                </>
+            );
+        case 'move':
+            return (
+                <>
+                    <NumberField label="X" value={step.x} onChange={(x) => onChange({ ...step, x })} />
+                    <NumberField label="Y" value={step.y} onChange={(y) => onChange({ ...step, y })} />
+                    <label className="flex items-center gap-1 text-[10px] font-bold text-zinc-500 uppercase">
+                        <input type="checkbox" checked={step.relative} onChange={(e) => onChange({ ...step, relative: e.target.checked })} />
+                        Rel
+                    </label>
+                    {!step.relative && <CaptureButton onCapture={(x, y) => onChange({ ...step, x, y })} />}
+                </>
+            );
+        case 'drag':
+            return (
+                <>
+                    <span className="text-[10px] font-mono text-zinc-500">({step.from[0]},{step.from[1]})</span>
+                    <CaptureButton onCapture={(x, y) => onChange({ ...step, from: [x, y] })} />
+                    <span className="text-zinc-600">→</span>
+                    <span className="text-[10px] font-mono text-zinc-500">({step.to[0]},{step.to[1]})</span>
+                    <CaptureButton onCapture={(x, y) => onChange({ ...step, to: [x, y] })} />
+                </>
+            );
+        case 'scroll':
+            return <NumberField label="Notches" value={step.notches} onChange={(notches) => onChange({ ...step, notches })} />;
+        case 'wait':
+            return <NumberField label="ms" value={step.ms} onChange={(ms) => onChange({ ...step, ms })} />;
+        case 'repeat':
+            return <NumberField label="Times" value={step.times} onChange={(times) => onChange({ ...step, times })} />;
+        case 'script':
+            return null;
+    }
+};
+
+const ScriptEditor = ({ source, onChange }: { source: string, onChange: (source: string) => void }) => {
+    const [error, setError] = useState<ScriptError | null>(null);
+
+    useEffect(() => {
+        const timer = setTimeout(() => {
+            invoke('validate_script', { source })
+                .then(() => setError(null))
+                .catch((e: ScriptError) => setError(e));
+        }, 300);
+        return () => clearTimeout(timer);
+    }, [source]);
+
+    return (
+        <div className="pl-4 space-y-1">
+            <textarea
+                value={source}
+                onChange={(e) => onChange(e.target.value)}
+                spellCheck={false}
+                rows={Math.max(3, source.split('\n').length)}
+                className={cn(
+                    "w-full px-2 py-1.5 bg-zinc-950 border rounded text-xs font-mono text-zinc-300 resize-y",
+                    error ? "border-red-500/50" : "border-zinc-800"
+                )}
+            />
+            {error && (
+                <p className="text-[10px] font-mono text-red-400">
+                    Line {error.line}, col {error.column}: {error.message}
+                </p>
+            )}
+        </div>
+    );
+};
+
+const StepList = ({ steps, onChange, depth = 0 }: { steps: MacroStep[], onChange: (steps: MacroStep[]) => void, depth?: number }) => {
+    const update = (i: number, step: MacroStep) => onChange(steps.map((s, j) => (j === i ? step : s)));
+    const remove = (i: number) => onChange(steps.filter((_, j) => j !== i));
+    const swap = (i: number, j: number) => {
+        if (j < 0 || j >= steps.length) return;
+        const next = [...steps];
+        [next[i], next[j]] = [next[j], next[i]];
+        onChange(next);
     };
 
-    const [part1Key, setPart1Key] = useState(() => loadState('cliky_macro_p1', 'F7'));
-    const [part2Key, setPart2Key] = useState(() => loadState('cliky_macro_p2', 'F8'));
-    const [dodgeKey, setDodgeKey] = useState(() => loadState('cliky_macro_dodge', 'AltLeft'));
-    const [safePocketPos, setSafePocketPos] = useState<{ x: number; y: number }>(() => loadState('cliky_macro_sp', { x: 0, y: 0 }));
-    const [quickUsePos, setQuickUsePos] = useState<{ x: number; y: number }>(() => loadState('cliky_macro_qu', { x: 0, y: 0 }));
-    const [delayMs, setDelayMs] = useState(() => loadState('cliky_macro_delay', 50));
+    return (
+        <div className={cn("space-y-1.5", depth > 0 && "pl-4 border-l border-zinc-800")}>
+            {steps.map((step, i) => (
+                <div key={i} className="space-y-1.5">
+                    <div className="flex items-center gap-2 p-1.5 bg-zinc-950/30 rounded-lg border border-zinc-800/30">
+                        <span className="text-[10px] font-mono text-zinc-600 w-4 text-right">{i + 1}</span>
+                        <select
+                            value={step.type}
+                            onChange={(e) => update(i, defaultStep(e.target.value as MacroStep['type']))}
+                            className="px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs font-bold text-zinc-300"
+                        >
+                            {STEP_TYPES.map((t) => <option key={t.value} value={t.value}>{t.label}</option>)}
+                        </select>
+                        <div className="flex items-center gap-2 flex-1">
+                            <StepEditor step={step} onChange={(s) => update(i, s)} />
+                        </div>
+                        <button onClick={() => swap(i, i - 1)} className="p-1 text-zinc-600 hover:text-zinc-300"><ChevronUp className="w-3.5 h-3.5" /></button>
+                        <button onClick={() => swap(i, i + 1)} className="p-1 text-zinc-600 hover:text-zinc-300"><ChevronDown className="w-3.5 h-3.5" /></button>
+                        <button onClick={() => remove(i)} className="p-1 text-zinc-600 hover:text-red-400"><Trash2 className="w-3.5 h-3.5" /></button>
+                    </div>
+                    {step.type === 'repeat' && (
+                        <StepList steps={step.steps} onChange={(inner) => update(i, { ...step, steps: inner })} depth={depth + 1} />
+                    )}
+                    {step.type === 'script' && (
+                        <ScriptEditor source={step.source} onChange={(source) => update(i, { ...step, source })} />
+                    )}
+                </div>
+            ))}
+            <button
+                onClick={() => onChange([...steps, defaultStep('keyTap')])}
+                className="flex items-center gap-1 px-2 py-1 text-[10px] font-bold uppercase text-zinc-500 hover:text-zinc-200"
+            >
+                <Plus className="w-3 h-3" /> Add Step
+            </button>
+        </div>
+    );
+};
+
+const Macro: React.FC = () => {
+    const [macros, setMacros] = useState<MacroDef[]>([]);
+    const [recordKey, setRecordKey] = useState('F10');
+    const [recordMaxGapMs, setRecordMaxGapMs] = useState(0);
+    const [isRecording, setIsRecording] = useState(false);
+    const [loaded, setLoaded] = useState(false);
+    const [selected, setSelected] = useState(0);
+    const [panicKey, setPanicKey] = useState('F12');
+    const [failsafe, setFailsafe] = useState<FailsafeConfig>({ enabled: true, corner: 'topLeft', margin: 2 });
+    const [scripts, setScripts] = useState<ScriptBinding[]>([]);
+    const [scriptTimeLimitMs, setScriptTimeLimitMs] = useState(60000);
+    const [scriptFiles, setScriptFiles] = useState<string[]>([]);
+    const [scriptStatus, setScriptStatus] = useState<string | null>(null);
+    const [saveError, setSaveError] = useState<string | null>(null);
+
+    const refreshScriptFiles = useCallback(() => {
+        invoke<string[]>('list_scripts').then(setScriptFiles).catch(console.error);
+    }, []);
 
-    const [recordingKey, setRecordingKey] = useState<'p1' | 'p2' | 'dodge' | null>(null);
-    const [capturing, setCapturing] = useState<CapturingTarget>(null);
+    // Load from backend (the active profile in settings.json)
+    const loadConfig = useCallback(() => {
+        invoke<MacroConfig>('get_macro_config')
+            .then((cfg) => {
+                setMacros(cfg.macros);
+                setRecordKey(cfg.recordKey);
+                setRecordMaxGapMs(cfg.recordMaxGapMs);
+                setPanicKey(cfg.panicKey);
+                setFailsafe(cfg.failsafe);
+                setScripts(cfg.scripts);
+                setScriptTimeLimitMs(cfg.scriptTimeLimitMs);
+            })
+            .catch(console.error)
+            .finally(() => setLoaded(true));
+    }, []);
 
-    // Persist settings
     useEffect(() => {
-        localStorage.setItem('cliky_macro_p1', JSON.stringify(part1Key));
-        localStorage.setItem('cliky_macro_p2', JSON.stringify(part2Key));
-        localStorage.setItem('cliky_macro_dodge', JSON.stringify(dodgeKey));
-        localStorage.setItem('cliky_macro_sp', JSON.stringify(safePocketPos));
-        localStorage.setItem('cliky_macro_qu', JSON.stringify(quickUsePos));
-        localStorage.setItem('cliky_macro_delay', JSON.stringify(delayMs));
-    }, [part1Key, part2Key, dodgeKey, safePocketPos, quickUsePos, delayMs]);
+        loadConfig();
+        const unlistenProfile = listen('profile-changed', () => {
+            setSelected(0);
+            loadConfig();
+        });
+        const unlistenReload = listen('config-reloaded', loadConfig);
+        const unlistenRecorded = listen<MacroDef>('macro-recorded', (event) => {
+            setMacros((ms) => {
+                setSelected(ms.length);
+                return [...ms, event.payload];
+            });
+        });
+        const unlistenState = listen<boolean>('recording-state-changed', (event) => {
+            setIsRecording(event.payload);
+        });
+        const unlistenScript = listen<{ file: string; error: string | null }>('script-finished', (event) => {
+            const { file, error } = event.payload;
+            setScriptStatus(error ? `${file}: ${error}` : `${file}: done`);
+        });
+        refreshScriptFiles();
+        return () => {
+            unlistenProfile.then((fn) => fn());
+            unlistenReload.then((fn) => fn());
+            unlistenRecorded.then((fn) => fn());
+            unlistenState.then((fn) => fn());
+            unlistenScript.then((fn) => fn());
+        };
+    }, [loadConfig, refreshScriptFiles]);
 
     // Push config to backend
     useEffect(() => {
-        invoke('update_macro_config', {
-            part1Key, part2Key, dodgeKey,
-            safePocketX: safePocketPos.x, safePocketY: safePocketPos.y,
-            quickUseX: quickUsePos.x, quickUseY: quickUsePos.y,
-            delayMs,
-        }).catch(console.error);
-    }, [part1Key, part2Key, dodgeKey, safePocketPos, quickUsePos, delayMs]);
-
-    const handleKeyRecord = useCallback((target: 'p1' | 'p2' | 'dodge') => {
-        setRecordingKey(target);
-        const setKey = target === 'p1' ? setPart1Key : target === 'p2' ? setPart2Key : setDodgeKey;
-        const cleanup = () => {
-            window.removeEventListener('keyup', keyUpHandler);
-            window.removeEventListener('keydown', keyDownHandler);
-            window.removeEventListener('mouseup', mouseUpHandler);
-            window.removeEventListener('mousedown', mouseDownHandler);
-        };
-        const keyUpHandler = (e: KeyboardEvent) => {
-            e.preventDefault();
-            setKey(e.code);
-            setRecordingKey(null);
-            cleanup();
-        };
-        const keyDownHandler = (e: KeyboardEvent) => { e.preventDefault(); };
-        const mouseUpHandler = (e: MouseEvent) => {
-            e.preventDefault();
-            let key = "";
-            switch (e.button) {
-                case 3: key = "Mouse4"; break;
-                case 4: key = "Mouse5"; break;
-                case 2: key = "Mouse3"; break;
-                default: return;
-            }
-            if (key) {
-                setKey(key);
-                setRecordingKey(null);
-                cleanup();
-            }
-        };
-        const mouseDownHandler = (e: MouseEvent) => { if (e.button > 2) e.preventDefault(); };
-        window.addEventListener('keyup', keyUpHandler);
-        window.addEventListener('keydown', keyDownHandler);
-        window.addEventListener('mouseup', mouseUpHandler);
-        window.addEventListener('mousedown', mouseDownHandler);
+        if (!loaded) return;
+        const config: MacroConfig = { macros, recordKey, recordMaxGapMs, panicKey, failsafe, scripts, scriptTimeLimitMs };
+        invoke('update_macro_config', { config })
+            .then(() => setSaveError(null))
+            .catch((e) => setSaveError(errorText(e)));
+    }, [macros, recordKey, recordMaxGapMs, panicKey, failsafe, scripts, scriptTimeLimitMs, loaded]);
+
+    const updateScript = (i: number, patch: Partial<ScriptBinding>) => {
+        setScripts((ss) => ss.map((s, j) => (j === i ? { ...s, ...patch } : s)));
+    };
+
+    const runScript = (file: string) => {
+        setScriptStatus(`${file}: running`);
+        invoke('run_script', { file }).catch((e) => setScriptStatus(`${file}: ${e}`));
+    };
+
+    const updateMacro = useCallback((i: number, patch: Partial<MacroDef>) => {
+        setMacros((ms) => ms.map((m, j) => (j === i ? { ...m, ...patch } : m)));
     }, []);
 
-    const startCapture = useCallback(async (target: CapturingTarget) => {
-        if (capturing || !target) return;
-        setCapturing(target);
-        try {
-            const [x, y] = await invoke<[number, number]>('capture_position');
-            if (target === 'safe_pocket') {
-                setSafePocketPos({ x, y });
-            } else {
-                setQuickUsePos({ x, y });
-            }
-        } catch (e) {
-            console.error(e);
-        }
-        setCapturing(null);
-    }, [capturing]);
-
-    const positionsSet = safePocketPos.x !== 0 && safePocketPos.y !== 0 && quickUsePos.x !== 0 && quickUsePos.y !== 0;
+    const addMacro = () => {
+        setMacros((ms) => {
+            // Names must be unique, so take the first free number. New macros
+        // start unbound so they cannot clash with another hotkey.
+            let n = 1;
+            while (ms.some((m) => m.name === `Macro ${n}`)) n++;
+            return [...ms, { name: `Macro ${n}`, hotkey: '', trigger: { type: 'press' }, steps: [] }];
+        });
+        setSelected(macros.length);
+    };
+
+    const removeMacro = (i: number) => {
+        setMacros((ms) => ms.filter((_, j) => j !== i));
+        setSelected(0);
+    };
+
+    const current = macros[selected];
+
+    const exportMacro = (name: string) => {
+        invoke<string>('export_bundle', { profiles: [], macros: [name] })
+            .then((text) => downloadText(`${name}.cliky.json`, text))
+            .catch(console.error);
+    };
 
     return (
-        <div className="grid grid-cols-1 md:grid-cols-2 gap-4 h-full content-start">
-            {/* Bindings Card */}
-            <Card title="Macro Keybinds" icon={Keyboard} className="md:col-span-2">
-                <div className="space-y-4">
-                    {/* Part 1 */}
-                    <div className="flex items-center justify-between p-3 bg-zinc-950/30 rounded-xl border border-zinc-800/30">
-                        <div>
-                            <span className="text-sm font-bold text-zinc-200">Part 1</span>
-                            <div className="text-[10px] text-zinc-500 font-mono mt-0.5">Inv: Safe → Quick</div>
-                        </div>
+        <div className="grid grid-cols-1 md:grid-cols-3 gap-4 h-full content-start pb-20">
+            {saveError && (
+                <div className="md:col-span-3 p-3 rounded-xl border border-red-500/30 bg-red-500/10 text-xs text-red-300">
+                    Macros were not saved: {saveError}
+                </div>
+            )}
+            {/* Macro List */}
+            <Card title="Macros" icon={Keyboard}>
+                <div className="space-y-2">
+                    {macros.map((m, i) => (
                         <button
-                            onClick={() => handleKeyRecord('p1')}
+                            key={i}
+                            onClick={() => setSelected(i)}
                             className={cn(
-                                "px-4 py-2 text-xs font-bold font-mono border rounded-lg transition-all min-w-[100px]",
-                                recordingKey === 'p1' ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse" : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:text-zinc-200"
+                                "w-full flex items-center justify-between p-2.5 rounded-xl border transition-all text-left",
+                                i === selected ? "bg-indigo-500/10 border-indigo-500/50" : "bg-zinc-950/30 border-zinc-800/30 hover:border-zinc-700"
                             )}
                         >
-                            {recordingKey === 'p1' ? "PRESS..." : part1Key}
+                            <span className="text-sm font-bold text-zinc-200 truncate">{m.name}</span>
+                            <span className="text-[10px] font-mono text-zinc-500">{m.hotkey}</span>
                         </button>
+                    ))}
+                    <button
+                        onClick={addMacro}
+                        className="w-full flex items-center justify-center gap-1 py-2 text-xs font-bold rounded-lg border border-dashed border-zinc-700 text-zinc-500 hover:text-zinc-200 hover:border-zinc-500"
+                    >
+                        <Plus className="w-3.5 h-3.5" /> New Macro
+                    </button>
+                </div>
+
+                {/* Recorder */}
+                <div className="mt-4 pt-4 border-t border-zinc-800/50 space-y-3">
+                    <div className="flex items-center justify-between">
+                        <span className="flex items-center gap-2 text-sm font-bold text-zinc-400">
+                            <Circle className={cn("w-3 h-3", isRecording ? "fill-red-500 text-red-500 animate-pulse" : "text-zinc-600")} />
+                            Record
+                        </span>
+                        <KeyButton value={recordKey} onChange={setRecordKey} chord />
                     </div>
+                    <div className="flex items-center justify-between">
+                        <span className="text-[10px] text-zinc-500 font-medium">Max pause (0 = as recorded)</span>
+                        <NumberField label="ms" value={recordMaxGapMs} onChange={setRecordMaxGapMs} />
+                    </div>
+                </div>
 
-                    {/* Part 2 */}
-                    <div className="flex items-center justify-between p-3 bg-zinc-950/30 rounded-xl border border-zinc-800/30">
-                        <div>
-                            <span className="text-sm font-bold text-zinc-200">Part 2</span>
-                            <div className="text-[10px] text-zinc-500 font-mono mt-0.5">Inv: Quick → Safe</div>
-                        </div>
-                        <button
-                            onClick={() => handleKeyRecord('p2')}
-                            className={cn(
-                                "px-4 py-2 text-xs font-bold font-mono border rounded-lg transition-all min-w-[100px]",
-                                recordingKey === 'p2' ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse" : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:text-zinc-200"
-                            )}
+                {/* Emergency Stop */}
+                <div className="mt-4 pt-4 border-t border-zinc-800/50 space-y-2">
+                    <div className="flex items-center justify-between">
+                        <span className="flex items-center gap-2 text-sm font-bold text-zinc-400">
+                            <OctagonX className="w-3.5 h-3.5 text-red-500" />
+                            Panic Key
+                        </span>
+                        <KeyButton value={panicKey} onChange={setPanicKey} chord />
+                    </div>
+                    <p className="text-[10px] text-zinc-500">Stops the clicker and any running macro or script, releasing held keys.</p>
+                    <div className="flex items-center justify-between">
+                        <label className="flex items-center gap-2 text-xs font-bold text-zinc-400">
+                            <input
+                                type="checkbox"
+                                checked={failsafe.enabled}
+                                onChange={(e) => setFailsafe({ ...failsafe, enabled: e.target.checked })}
+                            />
+                            Corner Fail-safe
+                        </label>
+                        <select
+                            value={failsafe.corner}
+                            disabled={!failsafe.enabled}
+                            onChange={(e) => setFailsafe({ ...failsafe, corner: e.target.value as Corner })}
+                            className="px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs text-zinc-300 disabled:opacity-50"
                         >
-                            {recordingKey === 'p2' ? "PRESS..." : part2Key}
-                        </button>
+                            {CORNERS.map((c) => <option key={c.value} value={c.value}>{c.label}</option>)}
+                        </select>
                     </div>
+                    <p className="text-[10px] text-zinc-500">Moving the cursor into this corner of the primary screen does the same.</p>
+                </div>
+            </Card>
 
-                    {/* Dodge */}
-                    <div className="flex items-center justify-between p-3 bg-zinc-950/30 rounded-xl border border-zinc-800/30">
-                        <div>
-                            <span className="text-sm font-bold text-zinc-200">Dodge Roll</span>
-                            <div className="text-[10px] text-zinc-500 font-mono mt-0.5">Animation Cancel</div>
+            {/* Macro Editor */}
+            <Card title="Steps" icon={ListOrdered} className="md:col-span-2">
+                {current ? (
+                    <div className="space-y-4">
+                        <div className="flex items-center gap-3">
+                            <input
+                                value={current.name}
+                                onChange={(e) => updateMacro(selected, { name: e.target.value })}
+                                className="flex-1 px-3 py-1.5 bg-zinc-950 border border-zinc-800 rounded-lg text-sm font-bold text-zinc-200"
+                            />
+                            <KeyButton value={current.hotkey} onChange={(hotkey) => updateMacro(selected, { hotkey })} chord />
+                            <TriggerPicker value={current.trigger} onChange={(trigger) => updateMacro(selected, { trigger })} />
+                            <button title="Export" onClick={() => exportMacro(current.name)} className="p-2 text-zinc-500 hover:text-zinc-200">
+                                <Download className="w-4 h-4" />
+                            </button>
+                            <button onClick={() => removeMacro(selected)} className="p-2 text-zinc-500 hover:text-red-400">
+                                <Trash2 className="w-4 h-4" />
+                            </button>
                         </div>
-                        <button
-                            onClick={() => handleKeyRecord('dodge')}
-                            className={cn(
-                                "px-4 py-2 text-xs font-bold font-mono border rounded-lg transition-all min-w-[100px]",
-                                recordingKey === 'dodge' ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse" : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:text-zinc-200"
-                            )}
-                        >
-                            {recordingKey === 'dodge' ? "PRESS..." : dodgeKey}
-                        </button>
+                        <StepList steps={current.steps} onChange={(steps) => updateMacro(selected, { steps })} />
                     </div>
-                </div>
+                ) : (
+                    <div className="text-xs text-zinc-500">No macros yet. Create one to get started.</div>
+                )}
             </Card>
 
-            {/* Positions Card */}
-            <Card title="Coordinates" icon={Crosshair}>
-                <div className="space-y-4">
-                    {/* Safe Pocket */}
-                    <div className="space-y-2">
-                        <div className="flex justify-between items-center text-xs font-bold text-zinc-500 uppercase tracking-wider">
-                            <span>Safe Pocket</span>
-                            <span className="text-zinc-600 font-mono">X:{safePocketPos.x} Y:{safePocketPos.y}</span>
+            {/* Rhai Scripts */}
+            <Card title="Scripts" icon={FileCode} className="md:col-span-3">
+                <div className="space-y-2">
+                    {scripts.map((binding, i) => (
+                        <div key={i} className="flex items-center gap-2 p-1.5 bg-zinc-950/30 rounded-lg border border-zinc-800/30">
+                            <select
+                                value={binding.file}
+                                onChange={(e) => updateScript(i, { file: e.target.value })}
+                                className="flex-1 px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs font-mono text-zinc-300"
+                            >
+                                {!scriptFiles.includes(binding.file) && <option value={binding.file}>{binding.file || '(choose a script)'}</option>}
+                                {scriptFiles.map((f) => <option key={f} value={f}>{f}</option>)}
+                            </select>
+                            <KeyButton value={binding.hotkey} onChange={(hotkey) => updateScript(i, { hotkey })} chord />
+                            <TriggerPicker value={binding.trigger} onChange={(trigger) => updateScript(i, { trigger })} />
+                            <button onClick={() => runScript(binding.file)} title="Run now" className="p-1 text-zinc-500 hover:text-emerald-400">
+                                <Play className="w-3.5 h-3.5" />
+                            </button>
+                            <button onClick={() => setScripts((ss) => ss.filter((_, j) => j !== i))} className="p-1 text-zinc-600 hover:text-red-400">
+                                <Trash2 className="w-3.5 h-3.5" />
+                            </button>
                         </div>
+                    ))}
+                    <div className="flex items-center gap-2">
                         <button
-                            onClick={() => startCapture('safe_pocket')}
-                            disabled={capturing !== null}
-                            className={cn(
-                                "w-full py-2.5 text-xs font-bold rounded-lg transition-all border",
-                                capturing === 'safe_pocket'
-                                    ? "bg-indigo-500/10 border-indigo-500/50 text-indigo-400 animate-pulse"
-                                    : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:bg-zinc-700 hover:text-zinc-200"
-                            )}
+                            onClick={() => setScripts((ss) => [...ss, { file: scriptFiles[0] ?? '', hotkey: 'F8', trigger: { type: 'press' } }])}
+                            className="flex items-center gap-1 px-2 py-1 text-[10px] font-bold uppercase text-zinc-500 hover:text-zinc-200"
                         >
-                            {capturing === 'safe_pocket' ? 'PRESS ANY KEY TO CAPTURE...' : 'Set Coordinate'}
+                            <Plus className="w-3 h-3" /> Bind Script
+                        </button>
+                        <button onClick={refreshScriptFiles} title="Rescan the scripts folder" className="p-1 text-zinc-500 hover:text-zinc-200">
+                            <RefreshCw className="w-3.5 h-3.5" />
                         </button>
-                    </div>
-
-                    {/* Quick Use */}
-                    <div className="space-y-2">
-                        <div className="flex justify-between items-center text-xs font-bold text-zinc-500 uppercase tracking-wider">
-                            <span>Quick Use Slot</span>
-                            <span className="text-zinc-600 font-mono">X:{quickUsePos.x} Y:{quickUsePos.y}</span>
-                        </div>
                         <button
-                            onClick={() => startCapture('quick_use')}
-                            disabled={capturing !== null}
-                            className={cn(
-                                "w-full py-2.5 text-xs font-bold rounded-lg transition-all border",
-                                capturing === 'quick_use'
-                                    ? "bg-indigo-500/10 border-indigo-500/50 text-indigo-400 animate-pulse"
-                                    : "bg-zinc-800 border-zinc-700 text-zinc-400 hover:bg-zinc-700 hover:text-zinc-200"
-                            )}
+                            onClick={() => invoke('emergency_stop').catch(console.error)}
+                            className="flex items-center gap-1 px-2 py-1 text-[10px] font-bold uppercase text-zinc-500 hover:text-red-400"
                         >
-                            {capturing === 'quick_use' ? 'PRESS ANY KEY TO CAPTURE...' : 'Set Coordinate'}
+                            <Square className="w-3 h-3" /> Stop
                         </button>
-                    </div>
-                </div>
-            </Card>
-
-            {/* Config Card */}
-            <Card title="Configuration" icon={Settings2}>
-                <div className="space-y-6">
-                    <div className="space-y-3">
-                        <div className="flex justify-between items-center">
-                            <span className="text-sm font-bold text-zinc-400">Step Delay</span>
-                            <span className="bg-indigo-500/10 text-indigo-400 px-2 py-0.5 rounded text-xs font-bold font-mono">
-                                {delayMs}ms
-                            </span>
-                        </div>
-                        <input
-                            type="range"
-                            min={0}
-                            max={500}
-                            step={10}
-                            value={delayMs}
-                            onChange={(e) => setDelayMs(parseInt(e.target.value))}
-                            className="w-full h-2 bg-zinc-800 rounded-full appearance-none cursor-pointer accent-indigo-500 hover:accent-indigo-400 transition-all"
-                        />
-                    </div>
-
-                    <div className="pt-4 border-t border-zinc-800/50">
-                        <div className="flex items-center justify-between py-2">
-                            <span className="text-sm font-bold text-zinc-400">Macro Ready</span>
-                            <div className={cn("w-2.5 h-2.5 rounded-full shadow-[0_0_8px_rgba(0,0,0,0.5)] transition-all",
-                                positionsSet ? "bg-emerald-500 shadow-emerald-500/20" : "bg-red-500/50")}
-                            />
-                        </div>
-                        <div className="flex items-center justify-between py-2">
-                            <span className="text-sm font-bold text-zinc-400">Status</span>
-                            <span className={cn("text-xs font-bold tracking-wider uppercase", positionsSet ? "text-emerald-500" : "text-amber-500")}>
-                                {positionsSet ? "OPERATIONAL" : "NEEDS CONFIG"}
-                            </span>
+                        <div className="ml-auto">
+                            <NumberField label="Time limit ms" value={scriptTimeLimitMs} onChange={setScriptTimeLimitMs} />
                         </div>
                     </div>
+                    {scriptStatus && <p className="text-[10px] font-mono text-zinc-500">{scriptStatus}</p>}
+                    <p className="text-[10px] text-zinc-600">
+                        Put <span className="font-mono">.rhai</span> files in the <span className="font-mono">scripts</span> folder next to settings.json.
+                    </p>
                 </div>
             </Card>
         </div>
@@ -0,0 +1,315 @@
+import React, { useState, useEffect, useRef } from 'react';
+import { Users, Plus, Copy, Pencil, Trash2, Check, AppWindow, Download, Upload } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { clsx, type ClassValue } from 'clsx';
+import { twMerge } from 'tailwind-merge';
+import { downloadText } from '../download';
+import { errorText, recordKey } from '../hotkey';
+
+function cn(...inputs: ClassValue[]) {
+    return twMerge(clsx(inputs));
+}
+
+interface AutoProfileRule {
+    profile: string;
+    class: string;
+    title: string;
+}
+
+interface ImportReport {
+    profiles: string[];
+    macros: string[];
+    warnings: string[];
+    skipped: string[];
+}
+
+type Conflict = 'skip' | 'rename' | 'replace';
+
+interface ProfilesState {
+    names: string[];
+    active: string;
+    cycle_key: string;
+    auto_rules: AutoProfileRule[];
+}
+
+const Profiles: React.FC<{ className?: string }> = ({ className }) => {
+    const [profiles, setProfiles] = useState<ProfilesState>({ names: [], active: '', cycle_key: '', auto_rules: [] });
+    const [editing, setEditing] = useState<string | null>(null);
+    const [draft, setDraft] = useState('');
+    const [error, setError] = useState<string | null>(null);
+    const [isRecordingKey, setIsRecordingKey] = useState(false);
+    const [conflict, setConflict] = useState<Conflict>('rename');
+    const [report, setReport] = useState<ImportReport | null>(null);
+    const fileInput = useRef<HTMLInputElement>(null);
+
+    useEffect(() => {
+        invoke<ProfilesState>('list_profiles').then(setProfiles).catch(console.error);
+        const unlistenProfile = listen<ProfilesState>('profile-changed', (event) => setProfiles(event.payload));
+        const unlistenReload = listen<ProfilesState>('config-reloaded', (event) => setProfiles(event.payload));
+        return () => {
+            unlistenProfile.then((fn) => fn());
+            unlistenReload.then((fn) => fn());
+        };
+    }, []);
+
+    const run = (command: string, args: Record<string, unknown>) => {
+        invoke<ProfilesState>(command, args)
+            .then((p) => {
+                setProfiles(p);
+                setError(null);
+            })
+            .catch((e) => setError(errorText(e)));
+    };
+
+    const uniqueName = (base: string) => {
+        let name = base;
+        for (let i = 2; profiles.names.includes(name); i++) name = `${base} ${i}`;
+        return name;
+    };
+
+    const commitRename = (name: string) => {
+        setEditing(null);
+        if (draft.trim() && draft.trim() !== name) run('rename_profile', { name, newName: draft });
+    };
+
+    const setRules = (rules: AutoProfileRule[]) => {
+        // Keep the local copy while typing; a late reply would undo keystrokes
+        setProfiles((p) => ({ ...p, auto_rules: rules }));
+        invoke('set_auto_profile_rules', { rules })
+            .then(() => setError(null))
+            .catch((e) => setError(errorText(e)));
+    };
+
+    const updateRule = (i: number, patch: Partial<AutoProfileRule>) => {
+        setRules(profiles.auto_rules.map((r, j) => (j === i ? { ...r, ...patch } : r)));
+    };
+
+    const exportProfile = (name: string) => {
+        invoke<string>('export_bundle', { profiles: [name], macros: [] })
+            .then((text) => downloadText(`${name}.cliky.json`, text))
+            .catch((e) => setError(errorText(e)));
+    };
+
+    const importFile = (file: File) => {
+        file.text()
+            .then((text) => invoke<ImportReport>('import_bundle', { text, conflict }))
+            .then((r) => {
+                setReport(r);
+                setError(null);
+                return invoke<ProfilesState>('list_profiles').then(setProfiles);
+            })
+            .catch((e) => setError(errorText(e)));
+    };
+
+    const recordCycleKey = () => {
+        setIsRecordingKey(true);
+        recordKey((key) => {
+            setIsRecordingKey(false);
+            // Escape clears the binding
+            run('set_profile_cycle_key', { key: key === 'Escape' ? '' : key });
+        }, true);
+    };
+
+    return (
+        <div className={cn("bg-zinc-900/60 backdrop-blur-xl border border-zinc-800/50 rounded-2xl p-5 shadow-sm transition-all hover:border-zinc-700/50 flex flex-col", className)}>
+            <div className="flex items-center justify-between mb-4">
+                <div className="flex items-center gap-2 text-zinc-400">
+                    <div className="p-1.5 rounded-lg bg-zinc-800/50">
+                        <Users className="w-4 h-4" />
+                    </div>
+                    <span className="text-xs font-bold uppercase tracking-wider">Profiles</span>
+                </div>
+                <button
+                    onClick={() => run('create_profile', { name: uniqueName('Profile') })}
+                    className="flex items-center gap-1 px-2.5 py-1 text-xs font-bold rounded-lg bg-zinc-800 text-zinc-300 hover:bg-zinc-700 hover:text-white transition-all"
+                >
+                    <Plus className="w-3.5 h-3.5" /> New
+                </button>
+            </div>
+
+            <div className="space-y-2">
+                {profiles.names.map((name) => (
+                    <div
+                        key={name}
+                        className={cn(
+                            "flex items-center gap-2 p-2 rounded-xl border transition-all",
+                            name === profiles.active ? "bg-indigo-500/10 border-indigo-500/50" : "bg-zinc-950/30 border-zinc-800/30"
+                        )}
+                    >
+                        {editing === name ? (
+                            <input
+                                autoFocus
+                                value={draft}
+                                onChange={(e) => setDraft(e.target.value)}
+                                onBlur={() => commitRename(name)}
+                                onKeyDown={(e) => {
+                                    if (e.key === 'Enter') commitRename(name);
+                                    if (e.key === 'Escape') setEditing(null);
+                                }}
+                                className="flex-1 bg-zinc-950 border border-zinc-700 rounded-lg px-2 py-1 text-sm text-zinc-200 outline-none"
+                            />
+                        ) : (
+                            <button
+                                onClick={() => run('activate_profile', { name })}
+                                className="flex-1 flex items-center gap-2 text-left text-sm font-bold text-zinc-200 truncate"
+                            >
+                                {name === profiles.active && <Check className="w-3.5 h-3.5 text-indigo-400" />}
+                                {name}
+                            </button>
+                        )}
+                        <button
+                            title="Rename"
+                            onClick={() => {
+                                setEditing(name);
+                                setDraft(name);
+                            }}
+                            className="p-1 text-zinc-500 hover:text-zinc-200"
+                        >
+                            <Pencil className="w-3.5 h-3.5" />
+                        </button>
+                        <button
+                            title="Duplicate"
+                            onClick={() => run('duplicate_profile', { name, newName: uniqueName(`${name} copy`) })}
+                            className="p-1 text-zinc-500 hover:text-zinc-200"
+                        >
+                            <Copy className="w-3.5 h-3.5" />
+                        </button>
+                        <button
+                            title="Export"
+                            onClick={() => exportProfile(name)}
+                            className="p-1 text-zinc-500 hover:text-zinc-200"
+                        >
+                            <Download className="w-3.5 h-3.5" />
+                        </button>
+                        <button
+                            title="Delete"
+                            disabled={profiles.names.length === 1}
+                            onClick={() => run('delete_profile', { name })}
+                            className="p-1 text-zinc-500 hover:text-red-400 disabled:opacity-30 disabled:hover:text-zinc-500"
+                        >
+                            <Trash2 className="w-3.5 h-3.5" />
+                        </button>
+                    </div>
+                ))}
+            </div>
+
+            <div className="flex items-center justify-between mt-4 pt-4 border-t border-zinc-800/50">
+                <div>
+                    <span className="text-sm font-bold text-zinc-300 block">Next Profile Key</span>
+                    <span className="text-[10px] text-zinc-500">Escape clears it</span>
+                </div>
+                <button
+                    onClick={recordCycleKey}
+                    className={cn(
+                        "px-4 py-2 text-xs font-bold font-mono border rounded-lg transition-all min-w-[100px]",
+                        isRecordingKey
+                            ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse"
+                            : "bg-zinc-950 border-zinc-800 text-zinc-400 hover:border-zinc-600 hover:text-zinc-200"
+                    )}
+                >
+                    {isRecordingKey ? "PRESS KEY" : profiles.cycle_key || "None"}
+                </button>
+            </div>
+
+            <div className="mt-4 pt-4 border-t border-zinc-800/50 space-y-2">
+                <div className="flex items-center justify-between">
+                    <div className="flex items-center gap-2">
+                        <AppWindow className="w-4 h-4 text-zinc-500" />
+                        <div>
+                            <span className="text-sm font-bold text-zinc-300 block">Switch by Window</span>
+                            <span className="text-[10px] text-zinc-500">Window class and title contain the text (X11). First match wins.</span>
+                        </div>
+                    </div>
+                    <button
+                        onClick={() => setRules([...profiles.auto_rules, { profile: profiles.active, class: '', title: '' }])}
+                        className="p-1.5 rounded-lg bg-zinc-800 text-zinc-300 hover:bg-zinc-700 hover:text-white transition-all"
+                    >
+                        <Plus className="w-3.5 h-3.5" />
+                    </button>
+                </div>
+                {profiles.auto_rules.map((rule, i) => (
+                    <div key={i} className="flex items-center gap-2">
+                        <input
+                            value={rule.class}
+                            placeholder="Class"
+                            onChange={(e) => updateRule(i, { class: e.target.value })}
+                            className="w-1/3 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-xs font-mono text-zinc-300 outline-none focus:border-zinc-600"
+                        />
+                        <input
+                            value={rule.title}
+                            placeholder="Title"
+                            onChange={(e) => updateRule(i, { title: e.target.value })}
+                            className="w-1/3 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-300 outline-none focus:border-zinc-600"
+                        />
+                        <select
+                            value={rule.profile}
+                            onChange={(e) => updateRule(i, { profile: e.target.value })}
+                            className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-300 outline-none"
+                        >
+                            {profiles.names.map((name) => (
+                                <option key={name} value={name}>{name}</option>
+                            ))}
+                        </select>
+                        <button
+                            onClick={() => setRules(profiles.auto_rules.filter((_, j) => j !== i))}
+                            className="p-1 text-zinc-500 hover:text-red-400"
+                        >
+                            <Trash2 className="w-3.5 h-3.5" />
+                        </button>
+                    </div>
+                ))}
+            </div>
+
+            <div className="mt-4 pt-4 border-t border-zinc-800/50 space-y-2">
+                <div className="flex items-center justify-between gap-2">
+                    <div>
+                        <span className="text-sm font-bold text-zinc-300 block">Import</span>
+                        <span className="text-[10px] text-zinc-500">Profiles and macros from a .cliky.json bundle</span>
+                    </div>
+                    <div className="flex items-center gap-2">
+                        <select
+                            value={conflict}
+                            onChange={(e) => setConflict(e.target.value as Conflict)}
+                            className="bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-300 outline-none"
+                        >
+                            <option value="rename">Keep both</option>
+                            <option value="replace">Replace</option>
+                            <option value="skip">Skip existing</option>
+                        </select>
+                        <button
+                            onClick={() => fileInput.current?.click()}
+                            className="flex items-center gap-1 px-2.5 py-1 text-xs font-bold rounded-lg bg-zinc-800 text-zinc-300 hover:bg-zinc-700 hover:text-white transition-all"
+                        >
+                            <Upload className="w-3.5 h-3.5" /> Import
+                        </button>
+                        <input
+                            ref={fileInput}
+                            type="file"
+                            accept=".json"
+                            className="hidden"
+                            onChange={(e) => {
+                                const file = e.target.files?.[0];
+                                if (file) importFile(file);
+                                e.target.value = '';
+                            }}
+                        />
+                    </div>
+                </div>
+                {report && (
+                    <div className="text-[10px] space-y-1">
+                        <p className="text-zinc-400">
+                            Imported {report.profiles.length} profile(s) and {report.macros.length} macro(s).
+                        </p>
+                        {report.warnings.map((w, i) => <p key={`w${i}`} className="text-amber-400">{w}</p>)}
+                        {report.skipped.map((s, i) => <p key={`s${i}`} className="text-red-400">Not imported: {s}</p>)}
+                    </div>
+                )}
+            </div>
+
+            {error && <p className="mt-3 text-[10px] text-red-400">{error}</p>}
+        </div>
+    );
+};
+
+export default Profiles;
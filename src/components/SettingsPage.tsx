@@ -2,6 +2,8 @@ import React from 'react';
 import { Settings2, RefreshCw, Download } from 'lucide-react';
 import { clsx, type ClassValue } from 'clsx';
 import { twMerge } from 'tailwind-merge';
+import Profiles from './Profiles';
+import WindowKeys from './WindowKeys';
 
 function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs));
@@ -55,6 +57,9 @@ const SettingsPage: React.FC = () => {
     return (
         <div className="h-full flex flex-col justify-center">
             <div className="grid grid-cols-1 md:grid-cols-2 gap-6 max-w-2xl mx-auto w-full">
+                <Profiles className="md:col-span-2" />
+                <WindowKeys className="md:col-span-2" />
+
                 {/* Update Checker */}
                 <Card title="Software Update" icon={RefreshCw} className="h-full min-h-[180px]">
                     <div className="flex flex-col gap-4 items-center justify-between flex-1 w-full text-center">
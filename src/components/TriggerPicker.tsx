@@ -0,0 +1,54 @@
+export type Trigger =
+    | { type: 'press' }
+    | { type: 'release' }
+    | { type: 'hold' }
+    | { type: 'doubleTap'; windowMs: number }
+    | { type: 'longPress'; holdMs: number };
+
+const TRIGGERS: { value: Trigger['type']; label: string }[] = [
+    { value: 'press', label: 'On Press' },
+    { value: 'release', label: 'On Release' },
+    { value: 'hold', label: 'While Held' },
+    { value: 'doubleTap', label: 'Double Tap' },
+    { value: 'longPress', label: 'Long Press' },
+];
+
+const defaultTrigger = (type: Trigger['type']): Trigger => {
+    switch (type) {
+        case 'doubleTap': return { type, windowMs: 300 };
+        case 'longPress': return { type, holdMs: 500 };
+        default: return { type };
+    }
+};
+
+/** When a hotkey acts: trigger type, plus its time for double tap and long press */
+const TriggerPicker = ({ value, onChange }: { value: Trigger, onChange: (t: Trigger) => void }) => {
+    const ms = value.type === 'doubleTap' ? value.windowMs : value.type === 'longPress' ? value.holdMs : null;
+    const setMs = (n: number) => {
+        if (value.type === 'doubleTap') onChange({ ...value, windowMs: n });
+        if (value.type === 'longPress') onChange({ ...value, holdMs: n });
+    };
+    return (
+        <div className="flex items-center gap-1">
+            <select
+                value={value.type}
+                onChange={(e) => onChange(defaultTrigger(e.target.value as Trigger['type']))}
+                className="px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs text-zinc-300"
+            >
+                {TRIGGERS.map((t) => <option key={t.value} value={t.value}>{t.label}</option>)}
+            </select>
+            {ms !== null && (
+                <input
+                    type="number"
+                    min={1}
+                    value={ms}
+                    title={value.type === 'doubleTap' ? 'Second tap within (ms)' : 'Hold for (ms)'}
+                    onChange={(e) => setMs(Math.max(1, Number(e.target.value)))}
+                    className="w-16 px-2 py-1 bg-zinc-950 border border-zinc-800 rounded text-xs font-mono text-zinc-300"
+                />
+            )}
+        </div>
+    );
+};
+
+export default TriggerPicker;
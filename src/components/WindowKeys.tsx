@@ -0,0 +1,90 @@
+import React, { useState, useEffect } from 'react';
+import { AppWindow } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { clsx, type ClassValue } from 'clsx';
+import { twMerge } from 'tailwind-merge';
+import { errorText, recordKey } from '../hotkey';
+
+function cn(...inputs: ClassValue[]) {
+    return twMerge(clsx(inputs));
+}
+
+interface WindowKeysState {
+    window_key: string;
+    overlay_key: string;
+}
+
+type Binding = keyof WindowKeysState;
+
+const ROWS: { binding: Binding; label: string; hint: string }[] = [
+    { binding: 'window_key', label: 'Show / Hide Window', hint: 'Swaps the main window and the overlay' },
+    { binding: 'overlay_key', label: 'Toggle Overlay', hint: 'Shows or hides only the overlay' },
+];
+
+const WindowKeys: React.FC<{ className?: string }> = ({ className }) => {
+    const [keys, setKeys] = useState<WindowKeysState>({ window_key: '', overlay_key: '' });
+    const [recording, setRecording] = useState<Binding | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = () => invoke<WindowKeysState>('get_window_keys').then(setKeys).catch(console.error);
+        load();
+        const unlisten = listen('config-reloaded', load);
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    const record = (binding: Binding) => {
+        setRecording(binding);
+        recordKey((key) => {
+            setRecording(null);
+            // Escape clears the binding
+            const next = { ...keys, [binding]: key === 'Escape' ? '' : key };
+            invoke<WindowKeysState>('set_window_keys', { windowKey: next.window_key, overlayKey: next.overlay_key })
+                .then((k) => {
+                    setKeys(k);
+                    setError(null);
+                })
+                .catch((e) => setError(errorText(e)));
+        }, true);
+    };
+
+    return (
+        <div className={cn("bg-zinc-900/60 backdrop-blur-xl border border-zinc-800/50 rounded-2xl p-5 shadow-sm transition-all hover:border-zinc-700/50 flex flex-col", className)}>
+            <div className="flex items-center gap-2 mb-4 text-zinc-400">
+                <div className="p-1.5 rounded-lg bg-zinc-800/50">
+                    <AppWindow className="w-4 h-4" />
+                </div>
+                <span className="text-xs font-bold uppercase tracking-wider">Window Keys</span>
+            </div>
+
+            <div className="space-y-3">
+                {ROWS.map(({ binding, label, hint }) => (
+                    <div key={binding} className="flex items-center justify-between">
+                        <div>
+                            <span className="text-sm font-bold text-zinc-300 block">{label}</span>
+                            <span className="text-[10px] text-zinc-500">{hint}. Escape clears it</span>
+                        </div>
+                        <button
+                            onClick={() => record(binding)}
+                            className={cn(
+                                "px-4 py-2 text-xs font-bold font-mono border rounded-lg transition-all min-w-[100px]",
+                                recording === binding
+                                    ? "bg-indigo-500/20 border-indigo-500 text-indigo-400 animate-pulse"
+                                    : "bg-zinc-950 border-zinc-800 text-zinc-400 hover:border-zinc-600 hover:text-zinc-200"
+                            )}
+                        >
+                            {recording === binding ? "PRESS KEY" : keys[binding] || "None"}
+                        </button>
+                    </div>
+                ))}
+            </div>
+
+            {error && <p className="mt-3 text-[10px] text-red-400">{error}</p>}
+        </div>
+    );
+};
+
+export default WindowKeys;
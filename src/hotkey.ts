@@ -0,0 +1,66 @@
+/// Modifier prefixes in the order the backend prints them, with the key codes of each
+const MODIFIERS: [string, string[], (e: KeyboardEvent | MouseEvent) => boolean][] = [
+    ['Ctrl', ['ControlLeft', 'ControlRight'], (e) => e.ctrlKey],
+    ['Shift', ['ShiftLeft', 'ShiftRight'], (e) => e.shiftKey],
+    ['Alt', ['AltLeft', 'AltRight'], (e) => e.altKey],
+    ['Meta', ['MetaLeft', 'MetaRight'], (e) => e.metaKey],
+];
+
+const isModifier = (code: string) => MODIFIERS.some(([, codes]) => codes.includes(code));
+
+/// "Ctrl+Shift+F6"-style text: the held modifiers, except the key's own, then the key
+const chordName = (e: KeyboardEvent | MouseEvent, key: string) =>
+    MODIFIERS
+        .filter(([, codes, held]) => held(e) && !codes.includes(key))
+        .map(([name]) => `${name}+`)
+        .join('') + key;
+
+/**
+ * Record the next key or extra mouse button as a `KeyboardEvent.code`-style
+ * string. With `chord`, modifiers held with it are included ("Ctrl+Shift+F6").
+ * A key is taken when pressed; a modifier only when released alone, so that
+ * it can start a chord.
+ */
+export function recordKey(onKey: (key: string) => void, chord = false) {
+    const name = (e: KeyboardEvent | MouseEvent, key: string) => chord ? chordName(e, key) : key;
+    const cleanup = () => {
+        window.removeEventListener('keyup', keyUpHandler);
+        window.removeEventListener('keydown', keyDownHandler);
+        window.removeEventListener('mouseup', mouseUpHandler);
+        window.removeEventListener('mousedown', mouseDownHandler);
+    };
+    const keyDownHandler = (e: KeyboardEvent) => {
+        e.preventDefault();
+        if (e.repeat || isModifier(e.code)) return;
+        onKey(name(e, e.code));
+        cleanup();
+    };
+    const keyUpHandler = (e: KeyboardEvent) => {
+        e.preventDefault();
+        if (!isModifier(e.code)) return;
+        onKey(name(e, e.code));
+        cleanup();
+    };
+    const mouseUpHandler = (e: MouseEvent) => {
+        e.preventDefault();
+        let key = "";
+        switch (e.button) {
+            case 3: key = "Mouse4"; break;
+            case 4: key = "Mouse5"; break;
+            case 2: key = "Mouse3"; break;
+            default: return;
+        }
+        onKey(name(e, key));
+        cleanup();
+    };
+    const mouseDownHandler = (e: MouseEvent) => { if (e.button > 2) e.preventDefault(); };
+    window.addEventListener('keyup', keyUpHandler);
+    window.addEventListener('keydown', keyDownHandler);
+    window.addEventListener('mouseup', mouseUpHandler);
+    window.addEventListener('mousedown', mouseDownHandler);
+}
+
+/// A rejected update's message: settings commands fail with `{ message, conflicts }`
+/// when hotkeys clash, other commands with plain text
+export const errorText = (e: unknown) =>
+    typeof e === 'object' && e !== null && 'message' in e ? String(e.message) : String(e);